@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rts_assignment::c1_tp063879::intersections::{
+    Intersection, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::{Lane, LaneStore, OccupancyMetric};
+use rts_assignment::c1_tp063879::simulation::{
+    collect_traffic_data, collect_traffic_data_from_store, collect_traffic_data_serial,
+};
+use std::sync::RwLock;
+
+// A 10x20 grid (200 intersections) with a lane to the right and below every intersection.
+fn build_grid(rows: i8, cols: i8) -> (Vec<Intersection>, Vec<Lane>) {
+    let mut intersections = Vec::new();
+    let mut lanes = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let intersection = Intersection::new(
+                format!("I{}_{}", row, col),
+                row,
+                col,
+                col == 0,
+                col == cols - 1,
+                IntersectionControl::TrafficLight,
+            );
+            let from = intersection.id;
+            intersections.push(intersection);
+            if col + 1 < cols {
+                let to = IntersectionId(row, col + 1);
+                lanes.push(Lane::new(format!("{:?}->{:?}", from, to), from, to, 200.0).unwrap());
+            }
+            if row + 1 < rows {
+                let to = IntersectionId(row + 1, col);
+                lanes.push(Lane::new(format!("{:?}->{:?}", from, to), from, to, 200.0).unwrap());
+            }
+        }
+    }
+    (intersections, lanes)
+}
+
+fn bench_collect_traffic_data(c: &mut Criterion) {
+    let (intersections, lanes) = build_grid(10, 20);
+
+    let mut group = c.benchmark_group("collect_traffic_data_200_intersections");
+    group.bench_function("serial", |b| {
+        b.iter(|| black_box(collect_traffic_data_serial(&lanes, &intersections, vec![])))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(collect_traffic_data(&lanes, &intersections, vec![])))
+    });
+    group.finish();
+}
+
+// Per-tick data collection on the shared state: cloning every lane and intersection first
+// versus reading them in place under their locks.
+fn bench_tick_data_collection(c: &mut Criterion) {
+    let (intersections, lanes) = build_grid(10, 20);
+    let intersections = RwLock::new(intersections);
+    let lanes = LaneStore::new(lanes);
+
+    let mut group = c.benchmark_group("tick_data_collection");
+    group.bench_function("clone_snapshot", |b| {
+        b.iter(|| {
+            let lanes_snapshot = lanes.snapshot();
+            let intersections_snapshot = intersections.read().unwrap().clone();
+            black_box(collect_traffic_data(
+                &lanes_snapshot,
+                &intersections_snapshot,
+                vec![],
+            ))
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            black_box(collect_traffic_data_from_store(
+                &lanes,
+                &intersections.read().unwrap(),
+                vec![],
+                OccupancyMetric::Length,
+            ))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_collect_traffic_data,
+    bench_tick_data_collection
+);
+criterion_main!(benches);
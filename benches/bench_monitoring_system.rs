@@ -1,9 +1,11 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use rts_assignment::c4_tp071994::traffic_monitoring_system::{
     listen_congestion_alerts, listen_light_adjustments, listen_traffic_data, listen_traffic_event,
+    OutputPaths,
 };
+use rts_assignment::global_variables::DEFAULT_CONGESTION_BINDING;
 use std::time::Duration;
-use tokio;
+use tokio::sync::watch;
 
 fn bench_monitoring_system(c: &mut Criterion) {
     // Create a single runtime
@@ -18,10 +20,18 @@ fn bench_monitoring_system(c: &mut Criterion) {
     group.bench_function("listen_congestion_alerts", |b| {
         b.iter(|| {
             rt.block_on(async {
+                let (shutdown_tx, shutdown_rx) = watch::channel(false);
                 tokio::select! {
-                    _ = listen_congestion_alerts() => {},
+                    _ = listen_congestion_alerts(
+                        None,
+                        DEFAULT_CONGESTION_BINDING.to_string(),
+                        OutputPaths::default(),
+                        Default::default(),
+                        shutdown_rx,
+                    ) => {},
                     _ = tokio::time::sleep(Duration::from_millis(100)) => {},
                 }
+                let _ = shutdown_tx.send(true);
             })
         });
     });
@@ -29,10 +39,12 @@ fn bench_monitoring_system(c: &mut Criterion) {
     group.bench_function("listen_light_adjustments", |b| {
         b.iter(|| {
             rt.block_on(async {
+                let (shutdown_tx, shutdown_rx) = watch::channel(false);
                 tokio::select! {
-                    _ = listen_light_adjustments() => {},
+                    _ = listen_light_adjustments(None, OutputPaths::default(), Default::default(), shutdown_rx) => {},
                     _ = tokio::time::sleep(Duration::from_millis(100)) => {},
                 }
+                let _ = shutdown_tx.send(true);
             })
         });
     });
@@ -40,10 +52,12 @@ fn bench_monitoring_system(c: &mut Criterion) {
     group.bench_function("listen_traffic_data", |b| {
         b.iter(|| {
             rt.block_on(async {
+                let (shutdown_tx, shutdown_rx) = watch::channel(false);
                 tokio::select! {
-                    _ = listen_traffic_data() => {},
+                    _ = listen_traffic_data(None, OutputPaths::default(), Default::default(), shutdown_rx) => {},
                     _ = tokio::time::sleep(Duration::from_millis(100)) => {},
                 }
+                let _ = shutdown_tx.send(true);
             })
         });
     });
@@ -51,10 +65,12 @@ fn bench_monitoring_system(c: &mut Criterion) {
     group.bench_function("listen_traffic_event", |b| {
         b.iter(|| {
             rt.block_on(async {
+                let (shutdown_tx, shutdown_rx) = watch::channel(false);
                 tokio::select! {
-                    _ = listen_traffic_event() => {},
+                    _ = listen_traffic_event(None, OutputPaths::default(), Default::default(), shutdown_rx) => {},
                     _ = tokio::time::sleep(Duration::from_millis(100)) => {},
                 }
+                let _ = shutdown_tx.send(true);
             })
         });
     });
@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::sync::{Mutex, RwLock};
+use std::thread;
+
+const READERS: usize = 4;
+const READS_PER_READER: usize = 2_000;
+const WRITE_EVERY: usize = 500;
+
+// Four threads query is_lane_green concurrently while the first one also advances the
+// lights every WRITE_EVERY reads, with the controller behind a Mutex versus an RwLock.
+fn bench_shared_state_reads(c: &mut Criterion) {
+    let lanes = create_lanes();
+    let queries: Vec<_> = lanes.iter().map(|l| (l.from, l.name.clone())).collect();
+
+    let mut group = c.benchmark_group("controller_concurrent_reads");
+    group.sample_size(20);
+
+    let mutex_controller = Mutex::new(TrafficLightController::initialize(
+        create_intersections(),
+        &lanes,
+    ));
+    group.bench_function("mutex", |b| {
+        b.iter(|| {
+            thread::scope(|s| {
+                for reader in 0..READERS {
+                    let controller = &mutex_controller;
+                    let queries = &queries;
+                    s.spawn(move || {
+                        for i in 0..READS_PER_READER {
+                            if reader == 0 && i % WRITE_EVERY == 0 {
+                                controller.lock().unwrap().update_all();
+                            }
+                            let (id, name) = &queries[i % queries.len()];
+                            black_box(controller.lock().unwrap().is_lane_green(*id, name));
+                        }
+                    });
+                }
+            });
+        })
+    });
+
+    let rwlock_controller = RwLock::new(TrafficLightController::initialize(
+        create_intersections(),
+        &lanes,
+    ));
+    group.bench_function("rwlock", |b| {
+        b.iter(|| {
+            thread::scope(|s| {
+                for reader in 0..READERS {
+                    let controller = &rwlock_controller;
+                    let queries = &queries;
+                    s.spawn(move || {
+                        for i in 0..READS_PER_READER {
+                            if reader == 0 && i % WRITE_EVERY == 0 {
+                                controller.write().unwrap().update_all();
+                            }
+                            let (id, name) = &queries[i % queries.len()];
+                            black_box(controller.read().unwrap().is_lane_green(*id, name));
+                        }
+                    });
+                }
+            });
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_shared_state_reads);
+criterion_main!(benches);
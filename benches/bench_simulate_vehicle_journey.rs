@@ -1,34 +1,39 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
-use tokio;
 
-use rts_assignment::c1_tp063879::intersections::create_intersections;
-use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{create_intersections, entry_only_intersections};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
 use rts_assignment::c1_tp063879::route_generation::generate_shortest_lane_route;
-use rts_assignment::c1_tp063879::simulation::simulate_vehicle_journey;
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
 use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
 use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
 
 fn bench_simulate_vehicle_journey(c: &mut Criterion) {
-    let intersections = Arc::new(Mutex::new(create_intersections()));
-    let lanes = Arc::new(Mutex::new(create_lanes()));
+    let intersections = Arc::new(RwLock::new(create_intersections()));
+    let lanes = Arc::new(LaneStore::new(create_lanes()));
 
     // Initialize traffic light controller
     let tc_instance = {
-        let intersections_snapshot = intersections.lock().unwrap().clone();
-        let lanes_snapshot = lanes.lock().unwrap().clone();
+        let intersections_snapshot = intersections.read().unwrap().clone();
+        let lanes_snapshot = lanes.snapshot();
         TrafficLightController::initialize(intersections_snapshot, &lanes_snapshot)
     };
-    let traffic_controller = Arc::new(Mutex::new(tc_instance));
+    let traffic_controller = Arc::new(RwLock::new(tc_instance));
 
     // Spawn the traffic light controller update loop
     let rt = tokio::runtime::Runtime::new().unwrap();
-    rt.spawn(TrafficLightController::run_update_loop(Arc::clone(
-        &traffic_controller,
-    )));
+    rt.spawn(TrafficLightController::run_update_loop(
+        Arc::clone(&traffic_controller),
+        1.0,
+        Arc::new(AtomicBool::new(false)),
+        Duration::from_secs(1),
+    ));
 
     let active_ids = Arc::new(Mutex::new(HashSet::new()));
     let vehicle_events = Arc::new(Mutex::new(Vec::new()));
@@ -41,22 +46,29 @@ fn bench_simulate_vehicle_journey(c: &mut Criterion) {
         b.iter_custom(|_iters| {
             let start = std::time::Instant::now();
             rt.block_on(async {
-                // Lock intersections and lanes to generate a random route.
-                let intersections_guard = intersections.lock().unwrap();
-                let lanes_guard = lanes.lock().unwrap();
-                let entry_points: Vec<_> =
-                    intersections_guard.iter().filter(|i| i.is_entry).collect();
-                let exit_points: Vec<_> =
-                    intersections_guard.iter().filter(|i| i.is_exit).collect();
-
                 let mut rng = rand::rng();
-                let entry = entry_points[rng.random_range(0..entry_points.len())];
-                let exit = exit_points[rng.random_range(0..exit_points.len())];
-                let entry_id = entry.id;
-                let exit_id = exit.id;
+                // Lock intersections and snapshot the lanes to generate a random route; the
+                // guard is scoped so it is released before the journey is awaited.
+                let (entry_id, exit_id, route) = {
+                    let intersections_guard = intersections.read().unwrap();
+                    let lanes_guard = lanes.snapshot();
+                    let entry_points: Vec<_> =
+                        intersections_guard.iter().filter(|i| i.is_entry).collect();
+                    let exit_points: Vec<_> =
+                        intersections_guard.iter().filter(|i| i.is_exit).collect();
+
+                    let entry = entry_points[rng.random_range(0..entry_points.len())];
+                    let exit = exit_points[rng.random_range(0..exit_points.len())];
 
-                let route = generate_shortest_lane_route(&lanes_guard, entry_id, exit_id)
+                    let route = generate_shortest_lane_route(
+                        &lanes_guard,
+                        entry.id,
+                        exit.id,
+                        &entry_only_intersections(&intersections_guard),
+                    )
                     .expect("Route should be found");
+                    (entry.id, exit.id, route)
+                };
 
                 println!(
                     "\nVehicle Route: {}",
@@ -67,29 +79,22 @@ fn bench_simulate_vehicle_journey(c: &mut Criterion) {
                         .join(" -> ")
                 );
 
-                drop(intersections_guard);
-                drop(lanes_guard);
-
-                let intersections_clone = Arc::clone(&intersections);
-                let lanes_clone = Arc::clone(&lanes);
-                let tc_clone = Arc::clone(&traffic_controller);
-                let active_ids_clone = Arc::clone(&active_ids);
-                let vehicle_events_clone = Arc::clone(&vehicle_events);
+                let ctx = JourneyContext {
+                    active_ids: Arc::clone(&active_ids),
+                    vehicle_events: Arc::clone(&vehicle_events),
+                    ..JourneyContext::new(
+                        Arc::clone(&intersections),
+                        Arc::clone(&lanes),
+                        Arc::clone(&traffic_controller),
+                        Arc::new(SimulationConfig::default()),
+                    )
+                };
 
                 // Create a single vehicle for simplicity.
                 let speed = rng.random_range(80.0..140.0);
 
                 let vehicle = Vehicle::new(1, VehicleType::Car, entry_id, exit_id, speed);
-                simulate_vehicle_journey(
-                    vehicle,
-                    route,
-                    intersections_clone,
-                    lanes_clone,
-                    tc_clone,
-                    active_ids_clone,
-                    vehicle_events_clone,
-                )
-                .await;
+                simulate_vehicle_journey(vehicle, route, SmallRng::seed_from_u64(42), ctx).await;
             });
             start.elapsed()
         })
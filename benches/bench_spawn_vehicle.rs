@@ -2,22 +2,26 @@ use criterion::{
     black_box, criterion_group, criterion_main, AxisScale, BenchmarkId, Criterion,
     PlotConfiguration,
 };
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
 use rts_assignment::c1_tp063879::intersections::create_intersections;
-use rts_assignment::c1_tp063879::lanes::create_lanes;
-use rts_assignment::c1_tp063879::simulation::{collect_traffic_data, spawn_vehicle};
-use std::sync::{Arc, Mutex};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{
+    collect_traffic_data, routable_lanes, spawn_vehicle, spawn_vehicle_from,
+};
+use std::sync::{Arc, RwLock};
 use std::vec;
 
 fn bench_spawn_vehicle_batches(c: &mut Criterion) {
-    let intersections = Arc::new(Mutex::new(create_intersections()));
-    let lanes = Arc::new(Mutex::new(create_lanes()));
+    let intersections = Arc::new(RwLock::new(create_intersections()));
+    let lanes = Arc::new(LaneStore::new(create_lanes()));
 
-    let lanes_guard = lanes.lock().unwrap();
-    let intersections_guard = intersections.lock().unwrap();
-    let traffic_data = collect_traffic_data(&lanes_guard, &intersections_guard, vec![]);
-    drop(lanes_guard);
+    let intersections_guard = intersections.read().unwrap();
+    let traffic_data = collect_traffic_data(&lanes.snapshot(), &intersections_guard, vec![]);
     drop(intersections_guard);
 
+    let config = SimulationConfig::default();
     let batch_sizes = [50, 100, 200];
 
     let mut group = c.benchmark_group("spawn_vehicle_batch");
@@ -32,12 +36,16 @@ fn bench_spawn_vehicle_batches(c: &mut Criterion) {
                 b.iter(|| {
                     // In each iteration, spawn 'size' vehicles
                     let mut next_vehicle_id = 1;
+                    let mut rng = SmallRng::seed_from_u64(42);
                     for _ in 0..size {
                         let result = spawn_vehicle(
                             &intersections,
                             &lanes,
                             &traffic_data,
                             &mut next_vehicle_id,
+                            &mut rng,
+                            &config,
+                            12,
                         );
                         black_box(result);
                     }
@@ -49,5 +57,54 @@ fn bench_spawn_vehicle_batches(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_spawn_vehicle_batches);
+// One tick's batch of 5: spawning each vehicle on its own (lane snapshot and lock per vehicle)
+// versus sharing one filtered lane snapshot and one lock across the batch.
+fn bench_spawn_tick_batch(c: &mut Criterion) {
+    let intersections = Arc::new(RwLock::new(create_intersections()));
+    let lanes = Arc::new(LaneStore::new(create_lanes()));
+    let traffic_data =
+        collect_traffic_data(&lanes.snapshot(), &intersections.read().unwrap(), vec![]);
+    let config = SimulationConfig::default();
+
+    let mut group = c.benchmark_group("spawn_tick_batch_of_5");
+    group.bench_function("per_vehicle", |b| {
+        b.iter(|| {
+            let mut next_vehicle_id = 1;
+            let mut rng = SmallRng::seed_from_u64(42);
+            for _ in 0..5 {
+                black_box(spawn_vehicle(
+                    &intersections,
+                    &lanes,
+                    &traffic_data,
+                    &mut next_vehicle_id,
+                    &mut rng,
+                    &config,
+                    12,
+                ));
+            }
+        });
+    });
+    group.bench_function("shared_snapshot", |b| {
+        b.iter(|| {
+            let mut next_vehicle_id = 1;
+            let mut rng = SmallRng::seed_from_u64(42);
+            let routable = routable_lanes(lanes.snapshot(), &traffic_data, &config);
+            let intersections_guard = intersections.read().unwrap();
+            for _ in 0..5 {
+                black_box(spawn_vehicle_from(
+                    &intersections_guard,
+                    &routable,
+                    &mut next_vehicle_id,
+                    &mut rng,
+                    &config,
+                    None,
+                    12,
+                ));
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_spawn_vehicle_batches, bench_spawn_tick_batch);
 criterion_main!(benches);
@@ -5,7 +5,7 @@ use criterion::{
 use std::collections::{HashMap, HashSet, VecDeque};
 
 use rts_assignment::c2_tp063881::traffic_analyzer::{
-    analyze_traffic_data, predict_future_traffic_weighted, HistoricalData,
+    analyze_traffic_data, predict_future_traffic_weighted, AnalyzerThresholds, HistoricalData,
 };
 use rts_assignment::shared_data::TrafficData;
 
@@ -36,6 +36,9 @@ fn generate_dummy_traffic_data_batch(batch_size: usize) -> TrafficData {
         intersection_congestion,
         intersection_waiting_time,
         vehicle_data,
+        intersection_max_direction_congestion: HashMap::new(),
+        lane_waiting_time: HashMap::new(),
+        lane_speeds: HashMap::new(),
     }
 }
 
@@ -81,7 +84,10 @@ fn bench_batch_traffic_data(c: &mut Criterion) {
             &batch,
             |b, &_batch| {
                 b.iter(|| {
-                    let alerts = analyze_traffic_data(black_box(&traffic_data));
+                    let alerts = analyze_traffic_data(
+                        black_box(&traffic_data),
+                        &AnalyzerThresholds::default(),
+                    );
                     black_box(alerts);
                 });
             },
@@ -1,4 +1,6 @@
 // benches/bench_update.rs
+// The dummy types mirror the controller's fields even where the benchmark never reads them.
+#![allow(dead_code)]
 use criterion::{
     black_box, AxisScale, Criterion, PlotConfiguration, criterion_group, criterion_main,
 };
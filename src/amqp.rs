@@ -0,0 +1,143 @@
+// amqp.rs
+use crate::c1_tp063879::intersections::IntersectionId;
+use crate::global_variables::EXCHANGE_CONGESTION_ALERTS;
+use amiquip::{
+    Channel, Connection, Consumer, ConsumerMessage, Exchange, ExchangeDeclareOptions, ExchangeType,
+    Result as AmiquipResult,
+};
+use rand::Rng;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// Defaults used by every consumer/publisher when opening the broker connection.
+pub const CONNECT_MAX_ATTEMPTS: u32 = 6;
+pub const CONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+// How often an idle consumer wakes up to check for a shutdown signal.
+pub const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// How a connection URL is opened: plain TCP for amqp://, TLS for amqps://.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectMode {
+    Insecure,
+    Tls,
+}
+
+impl ConnectMode {
+    pub fn for_url(url: &str) -> Self {
+        let is_tls = url
+            .get(..8)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("amqps://"));
+        if is_tls {
+            ConnectMode::Tls
+        } else {
+            ConnectMode::Insecure
+        }
+    }
+}
+
+// Opens a single connection to `url` using the mode implied by its scheme.
+pub fn open_connection(url: &str) -> AmiquipResult<Connection> {
+    match ConnectMode::for_url(url) {
+        ConnectMode::Tls => Connection::open(url),
+        ConnectMode::Insecure => Connection::insecure_open(url),
+    }
+}
+
+// Opens a connection to `url`, retrying up to `max_attempts` times with exponential backoff
+// (base_delay, 2 * base_delay, 4 * base_delay, ...) plus random jitter between attempts.
+// Returns the last error if every attempt fails.
+pub fn connect_with_retry(
+    url: &str,
+    max_attempts: u32,
+    base_delay: Duration,
+) -> AmiquipResult<Connection> {
+    retry_with_backoff(|| open_connection(url), max_attempts, base_delay)
+}
+
+// Retry loop behind connect_with_retry, with the connect step passed in as a closure.
+pub fn retry_with_backoff<T, E, F>(
+    mut connect: F,
+    max_attempts: u32,
+    base_delay: Duration,
+) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Result<T, E>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match connect() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= max_attempts => {
+                eprintln!(
+                    "Connection attempt {}/{} failed: {}. Giving up.",
+                    attempt, max_attempts, e
+                );
+                return Err(e);
+            }
+            Err(e) => {
+                let delay = backoff_delay(base_delay, attempt);
+                eprintln!(
+                    "Connection attempt {}/{} failed: {}. Retrying in {:.2} seconds.",
+                    attempt,
+                    max_attempts,
+                    e,
+                    delay.as_secs_f64()
+                );
+                thread::sleep(delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// Delay before the next attempt: base_delay * 2^(attempt - 1) plus up to 50% jitter so that
+// several clients restarting together don't hammer the broker in lockstep.
+fn backoff_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let exponential = base_delay.saturating_mul(1 << (attempt - 1).min(16));
+    let jitter_ms = exponential.as_millis() as u64 / 2;
+    let jitter = if jitter_ms > 0 {
+        rand::rng().random_range(0..=jitter_ms)
+    } else {
+        0
+    };
+    exponential + Duration::from_millis(jitter)
+}
+
+// Waits for the next message on `consumer`, checking `shutdown` between polls. Returns None once
+// shutdown has been signalled or the consumer's channel has closed.
+pub fn next_message(
+    consumer: &Consumer,
+    shutdown: &watch::Receiver<bool>,
+) -> Option<ConsumerMessage> {
+    loop {
+        if *shutdown.borrow() {
+            return None;
+        }
+        match consumer.receiver().recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+            Ok(message) => return Some(message),
+            Err(e) if e.is_timeout() => continue,
+            Err(_) => return None,
+        }
+    }
+}
+
+// Declares the topic exchange congestion alerts are routed through.
+pub fn declare_congestion_exchange(channel: &Channel) -> AmiquipResult<Exchange<'_>> {
+    channel.exchange_declare(
+        ExchangeType::Topic,
+        EXCHANGE_CONGESTION_ALERTS,
+        ExchangeDeclareOptions::default(),
+    )
+}
+
+// Routing key for an alert about `intersection`, e.g. "IntersectionId(0, 2)" becomes
+// "congestion.row0.col2". Alerts without recognisable coordinates use "congestion.unknown".
+pub fn congestion_routing_key(intersection: Option<&str>) -> String {
+    match intersection.and_then(|s| s.parse::<IntersectionId>().ok()) {
+        Some(IntersectionId(row, col)) => format!("congestion.row{}.col{}", row, col),
+        None => "congestion.unknown".to_string(),
+    }
+}
@@ -1,15 +1,141 @@
 // simulation_main.rs
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::event_log::replay_from_log;
 use rts_assignment::c1_tp063879::intersections::create_intersections;
-use rts_assignment::c1_tp063879::lanes::create_lanes;
-use rts_assignment::c1_tp063879::simulation::run_simulation;
-use std::sync::{Arc, Mutex};
+use rts_assignment::c1_tp063879::lanes::{apply_geometric_lengths, create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::route_generation::validate_connectivity;
+use rts_assignment::c1_tp063879::simulation::{
+    listen_simulation_control, run_simulation, RunContext,
+};
+use rts_assignment::c1_tp063879::snapshot::SimulationState;
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    lane_accident_risk, load_accident_history,
+};
+use rts_assignment::shared_data::current_timestamp;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
-    let intersections = Arc::new(Mutex::new(create_intersections()));
-    let lanes = Arc::new(Mutex::new(create_lanes()));
+    // REPLAY_EVENT_LOG replays a recorded event log instead of running the simulation.
+    if let Ok(path) = std::env::var("REPLAY_EVENT_LOG") {
+        match replay_from_log(&path, |entry| {
+            println!("[{}] {:?}", entry.timestamp, entry.event)
+        }) {
+            Ok(count) => println!("Replayed {} events from {}", count, path),
+            Err(e) => eprintln!("Error replaying {}: {}", path, e),
+        }
+        return;
+    }
 
-    run_simulation(intersections, lanes).await;
+    // Simulation parameters come from SIMULATION_CONFIG (default simulation_config.toml);
+    // without a config file the built-in defaults are used.
+    let config_path =
+        std::env::var("SIMULATION_CONFIG").unwrap_or_else(|_| "simulation_config.toml".to_string());
+    let mut config = if std::path::Path::new(&config_path).exists() {
+        match SimulationConfig::load(&config_path) {
+            Ok(config) => {
+                println!("Loaded simulation config from {}", config_path);
+                config
+            }
+            Err(e) => {
+                eprintln!("Error loading {}: {}. Using defaults.", config_path, e);
+                SimulationConfig::default()
+            }
+        }
+    } else {
+        SimulationConfig::default()
+    };
+
+    // Lanes that have seen many accidents in earlier runs crash more often in this one.
+    if let Some(path) = config.accident_history_path.clone() {
+        match load_accident_history(std::path::Path::new(&path)) {
+            Ok(records) => {
+                for risk in lane_accident_risk(&records, config.high_risk_accidents_per_hour) {
+                    if risk.high_risk && !config.high_risk_lanes.contains(&risk.lane) {
+                        println!(
+                            "High-risk lane {}: {:.2} accidents per hour",
+                            risk.lane, risk.accidents_per_hour
+                        );
+                        config.high_risk_lanes.push(risk.lane);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Error loading accident history {}: {}", path, e),
+        }
+    }
+
+    let intersections = create_intersections();
+    let mut lanes = create_lanes();
+    match config.lane_meters_per_cell {
+        Some(meters_per_cell) if meters_per_cell > 0.0 => {
+            apply_geometric_lengths(&mut lanes, meters_per_cell)
+        }
+        Some(meters_per_cell) => eprintln!(
+            "Ignoring lane_meters_per_cell = {}: it must be positive.",
+            meters_per_cell
+        ),
+        None => {}
+    }
+    for id in validate_connectivity(&intersections, &lanes) {
+        eprintln!(
+            "Warning: intersection {:?} is unreachable from every entry or cannot reach any exit.",
+            id
+        );
+    }
+    let intersections = Arc::new(RwLock::new(intersections));
+    let lanes = Arc::new(LaneStore::new(lanes));
+
+    // Pass SIMULATION_SEED to reproduce a previous run.
+    let seed = std::env::var("SIMULATION_SEED")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(current_timestamp);
+    println!("Simulation seed: {}", seed);
+
+    // Tag published messages so consumers can filter to this run (RUN_ID, defaults to the seed).
+    let run_id = std::env::var("RUN_ID").unwrap_or_else(|_| format!("run-{}", seed));
+    println!("Simulation run id: {}", run_id);
+
+    // TIME_SCALE > 1.0 runs faster than real time, < 1.0 in slow motion.
+    let time_scale = std::env::var("TIME_SCALE")
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(1.0);
+
+    // Pause/resume commands arrive from the admin CLI on the simulation_control queue.
+    let paused = Arc::new(AtomicBool::new(false));
+    {
+        let paused = Arc::clone(&paused);
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = listen_simulation_control(paused) {
+                eprintln!("Simulation control listener error: {}", e);
+            }
+        });
+    }
+
+    // RESUME_SNAPSHOT resumes from a state saved via `snapshot_path`.
+    let initial_state =
+        std::env::var("RESUME_SNAPSHOT").ok().and_then(
+            |path| match SimulationState::load_snapshot(&path) {
+                Ok(state) => {
+                    println!("Resuming from snapshot {}", path);
+                    Some(state)
+                }
+                Err(e) => {
+                    eprintln!("Error loading snapshot {}: {}. Starting fresh.", path, e);
+                    None
+                }
+            },
+        );
+
+    let run = RunContext {
+        time_scale,
+        paused,
+        initial_state,
+        ..RunContext::new(seed, run_id)
+    };
+    run_simulation(intersections, lanes, config, run).await;
 }
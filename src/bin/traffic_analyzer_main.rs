@@ -1,11 +1,59 @@
-use rts_assignment::c2_tp063881::traffic_analyzer::start_analyzer_rabbitmq;
+use rts_assignment::bus::AmiquipBus;
+use rts_assignment::c2_tp063881::traffic_analyzer::{start_analyzer_rabbitmq, AnalyzerThresholds};
+use rts_assignment::c4_tp071994::traffic_monitoring_system::replay_traffic_data_csv;
+use rts_assignment::global_variables::amqp_url;
+use rts_assignment::health::{spawn_health_from_env, SharedLiveness};
+use rts_assignment::shared_data::run_id_filter_from_env;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::watch;
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
     println!("Starting traffic analyzer...");
 
-    if let Err(e) = start_analyzer_rabbitmq().await {
+    // Served on HEALTH_PORT when set.
+    let liveness = SharedLiveness::default();
+    spawn_health_from_env(&liveness);
+
+    // REPLAY_TRAFFIC_DATA feeds a recorded traffic_data.csv to this analyzer instead of waiting
+    // for a simulation, one update every REPLAY_INTERVAL_MS (default 1000).
+    if let Ok(path) = std::env::var("REPLAY_TRAFFIC_DATA") {
+        let interval = std::env::var("REPLAY_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map_or(Duration::from_secs(1), Duration::from_millis);
+        tokio::task::spawn_blocking(move || {
+            let result = AmiquipBus::connect(&amqp_url())
+                .map_err(|e| e.into())
+                .and_then(|bus| replay_traffic_data_csv(Path::new(&path), &bus, interval));
+            match result {
+                Ok(summary) => println!(
+                    "Replayed {} updates from {} ({} rows skipped)",
+                    summary.published, path, summary.skipped
+                ),
+                Err(e) => eprintln!("Error replaying {}: {}", path, e),
+            }
+        });
+    }
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            println!("Shutting down analyzer...");
+            let _ = shutdown_tx.send(true);
+        }
+    });
+
+    if let Err(e) = start_analyzer_rabbitmq(
+        run_id_filter_from_env(),
+        AnalyzerThresholds::default(),
+        liveness,
+        shutdown_rx,
+    )
+    .await
+    {
         eprintln!("Analyzer error: {}", e);
     }
 }
@@ -1,11 +1,71 @@
-use rts_assignment::c3_tp063987::traffic_light_controller::start_traffic_controller_rabbitmq;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c2_tp063881::traffic_analyzer::AnalyzerThresholds;
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    listen_traffic_predictions, start_traffic_controller_rabbitmq, TrafficLightController,
+    DEFAULT_MAX_GREEN_SECS,
+};
+use rts_assignment::health::{spawn_health_from_env, SharedLiveness};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::watch;
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
     println!("Starting traffic controller...");
 
-    if let Err(e) = start_traffic_controller_rabbitmq().await {
+    // CONTROLLER_MAX_GREEN_SECS caps how far congestion alerts can stretch a phase.
+    let max_green = std::env::var("CONTROLLER_MAX_GREEN_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_GREEN_SECS);
+    let controller = Arc::new(RwLock::new(
+        TrafficLightController::initialize(create_intersections(), &create_lanes())
+            .with_max_green(max_green),
+    ));
+    // The lights run in real time so an extension lengthens the green that is actually on.
+    tokio::spawn(TrafficLightController::run_update_loop(
+        Arc::clone(&controller),
+        1.0,
+        Arc::default(),
+        Duration::from_secs(1),
+    ));
+    // Served on HEALTH_PORT when set; alerts and predictions both count as activity.
+    let liveness = SharedLiveness::default();
+    spawn_health_from_env(&liveness);
+    // Ctrl-C tells both consumers to close their connections and return.
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    {
+        let shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                println!("\nShutting down traffic controller...");
+                let _ = shutdown_tx.send(true);
+            }
+        });
+    }
+    // Predictions from the analyzer adjust the lights ahead of congestion alongside the alerts.
+    let prediction_listener = {
+        let controller = Arc::clone(&controller);
+        let liveness = Arc::clone(&liveness);
+        let shutdown = shutdown_rx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = listen_traffic_predictions(
+                controller,
+                AnalyzerThresholds::default(),
+                liveness,
+                shutdown,
+            )
+            .await
+            {
+                eprintln!("Prediction listener error: {}", e);
+            }
+        })
+    };
+    if let Err(e) = start_traffic_controller_rabbitmq(controller, liveness, shutdown_rx).await {
         eprintln!("Controller error: {}", e);
     }
+    let _ = shutdown_tx.send(true);
+    let _ = prediction_listener.await;
 }
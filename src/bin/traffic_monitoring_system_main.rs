@@ -1,44 +1,96 @@
+use rts_assignment::c4_tp071994::metrics::{metrics_port_from_env, serve_metrics, MetricsState};
 use rts_assignment::c4_tp071994::traffic_monitoring_system::{
     listen_congestion_alerts, listen_light_adjustments, listen_traffic_data, listen_traffic_event,
-    run_cli,
+    run_cli, OutputPaths,
 };
+use rts_assignment::global_variables::congestion_binding;
+use rts_assignment::shared_data::run_id_filter_from_env;
+use std::sync::{Arc, Mutex};
 use tokio::join;
+use tokio::sync::watch;
 
 #[tokio::main]
 async fn main() {
+    // Optionally restrict logging to a single simulation run.
+    let run_filter = run_id_filter_from_env();
+    // Logs, charts and reports go under MONITORING_OUTPUT_DIR (default: current directory).
+    let paths = OutputPaths::from_env();
+
+    // Listener counters, optionally served for Prometheus on METRICS_PORT.
+    let metrics = Arc::new(Mutex::new(MetricsState::default()));
+    if let Some(port) = metrics_port_from_env() {
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(e) = serve_metrics(metrics, port).await {
+                eprintln!("Error in metrics server: {}", e);
+            }
+        });
+    }
+
+    // Ctrl-C (or exiting the CLI) tells every listener to close its connection and return.
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    {
+        let shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                println!("\nShutting down listeners...");
+                let _ = shutdown_tx.send(true);
+            }
+        });
+    }
+
     // Spawn listeners for the three RabbitMQ channels concurrently.
-    let congestion_listener = tokio::spawn(async {
-        if let Err(e) = listen_congestion_alerts().await {
+    let filter = run_filter.clone();
+    let shutdown = shutdown_rx.clone();
+    let output = paths.clone();
+    let counters = Arc::clone(&metrics);
+    let congestion_listener = tokio::spawn(async move {
+        if let Err(e) =
+            listen_congestion_alerts(filter, congestion_binding(), output, counters, shutdown).await
+        {
             eprintln!("Error in congestion alerts listener: {}", e);
         }
     });
-    let light_adjustments_listener = tokio::spawn(async {
-        if let Err(e) = listen_light_adjustments().await {
+    let filter = run_filter.clone();
+    let shutdown = shutdown_rx.clone();
+    let output = paths.clone();
+    let counters = Arc::clone(&metrics);
+    let light_adjustments_listener = tokio::spawn(async move {
+        if let Err(e) = listen_light_adjustments(filter, output, counters, shutdown).await {
             eprintln!("Error in light adjustments listener: {}", e);
         }
     });
-    let traffic_data_listener = tokio::spawn(async {
-        if let Err(e) = listen_traffic_data().await {
+    let filter = run_filter.clone();
+    let shutdown = shutdown_rx.clone();
+    let output = paths.clone();
+    let counters = Arc::clone(&metrics);
+    let traffic_data_listener = tokio::spawn(async move {
+        if let Err(e) = listen_traffic_data(filter, output, counters, shutdown).await {
             eprintln!("Error in traffic data listener: {}", e);
         }
     });
-    let traffic_event_listener = tokio::spawn(async {
-        if let Err(e) = listen_traffic_event().await {
+    let output = paths.clone();
+    let counters = Arc::clone(&metrics);
+    let traffic_event_listener = tokio::spawn(async move {
+        if let Err(e) = listen_traffic_event(run_filter, output, counters, shutdown_rx).await {
             eprintln!("Error in traffic event listener: {}", e);
         }
     });
 
     // Run the admin CLI concurrently.
-    let cli_handle = tokio::spawn(async {
-        run_cli().await;
+    tokio::spawn(async move {
+        run_cli(paths).await;
+        let _ = shutdown_tx.send(true);
     });
 
-    // Wait for all tasks to complete (the CLI will exit on its own).
+    // Wait for all listeners to close their connections.
     let _ = join!(
         congestion_listener,
         light_adjustments_listener,
         traffic_data_listener,
-        traffic_event_listener,
-        cli_handle
+        traffic_event_listener
     );
+    println!("All listeners closed.");
+    // After Ctrl-C the CLI may still be blocked reading stdin, so don't wait for it.
+    std::process::exit(0);
 }
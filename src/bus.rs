@@ -0,0 +1,489 @@
+// bus.rs
+use crate::amqp::{
+    connect_with_retry, declare_congestion_exchange, CONNECT_BASE_DELAY, CONNECT_MAX_ATTEMPTS,
+    SHUTDOWN_POLL_INTERVAL,
+};
+use amiquip::{
+    Channel, Connection, ConsumerMessage, ConsumerOptions, Exchange, Publish, QueueDeclareOptions,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::watch;
+
+pub type BusResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Message transport the services publish and consume through, so their logic doesn't depend on
+// a live broker.
+pub trait MessageBus: Send + Sync {
+    // Sends `payload` to the named queue.
+    fn publish(&self, queue: &str, payload: &[u8]) -> BusResult<()>;
+
+    // Messages arriving on the named queue, in order. Acknowledge each one with
+    // Subscription::ack once it has been processed.
+    fn subscribe(&self, queue: &str) -> BusResult<Subscription>;
+
+    // Sends a congestion alert with the given topic routing key (see congestion_routing_key).
+    // Transports without topic routing treat the key as a queue name.
+    fn publish_congestion_alert(&self, routing_key: &str, payload: &[u8]) -> BusResult<()> {
+        self.publish(routing_key, payload)
+    }
+
+    // Reopens the transport after publishing has kept failing. Must not block the caller: a
+    // transport that has to wait on the broker does so in the background. Transports without a
+    // connection to lose have nothing to do.
+    fn reconnect(&self) -> BusResult<()> {
+        Ok(())
+    }
+
+    // Messages waiting on the named queue, or None if the transport can't tell.
+    fn queue_depth(&self, _queue: &str) -> BusResult<Option<u32>> {
+        Ok(None)
+    }
+}
+
+// Consecutive failed publishes after which PublishRecovery asks the bus to reconnect.
+pub const PUBLISH_FAILURES_BEFORE_RECONNECT: u32 = 3;
+
+// Calls to PublishRecovery::is_backlogged per queue depth read, unless set with
+// with_depth_sample_interval. Reading the depth is a round trip to the broker.
+pub const DEPTH_SAMPLE_INTERVAL: u32 = 10;
+
+// Messages a RabbitMQ subscription holds without an acknowledgement. The broker stops
+// delivering once this many are waiting on the subscriber.
+pub const SUBSCRIPTION_PREFETCH: u16 = 16;
+
+// Publishes for long-running loops that must outlive broker hiccups: a failed publish is logged
+// instead of returned, and after PUBLISH_FAILURES_BEFORE_RECONNECT failures in a row the bus is
+// reconnected.
+// With a max_queue_depth set it also sheds load on one queue: see is_backlogged.
+#[derive(Debug, Default)]
+pub struct PublishRecovery {
+    consecutive_failures: u32,
+    max_queue_depth: Option<u32>,
+    // None reads the depth every DEPTH_SAMPLE_INTERVAL calls.
+    depth_sample_interval: Option<u32>,
+    // Calls left before the depth is read again, and whether the last read was over the limit.
+    calls_until_sample: u32,
+    backlogged: bool,
+    // Messages held back since the queue last had room.
+    shed: u64,
+}
+
+impl PublishRecovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_queue_depth(mut self, max_queue_depth: Option<u32>) -> Self {
+        self.max_queue_depth = max_queue_depth;
+        self
+    }
+
+    // Reads the queue depth on every `interval`-th call to is_backlogged (at least every call).
+    pub fn with_depth_sample_interval(mut self, interval: u32) -> Self {
+        self.depth_sample_interval = Some(interval.max(1));
+        self
+    }
+
+    // Whether the caller should skip this message because `queue` holds more than
+    // max_queue_depth messages, i.e. its consumers aren't keeping up. The depth is only read
+    // every depth sample interval calls; in between the last reading stands. Counts the skipped
+    // message and logs when shedding starts and stops. Always false without a limit, or when the
+    // depth can't be read.
+    pub fn is_backlogged(&mut self, bus: &dyn MessageBus, queue: &str) -> bool {
+        let Some(max_depth) = self.max_queue_depth else {
+            return false;
+        };
+        if self.calls_until_sample > 0 {
+            self.calls_until_sample -= 1;
+            if self.backlogged {
+                self.shed += 1;
+            }
+            return self.backlogged;
+        }
+        self.calls_until_sample = self.depth_sample_interval.unwrap_or(DEPTH_SAMPLE_INTERVAL) - 1;
+        let depth = match bus.queue_depth(queue) {
+            Ok(Some(depth)) => depth,
+            Ok(None) => {
+                self.backlogged = false;
+                return false;
+            }
+            Err(e) => {
+                println!("[Bus] Error reading depth of '{}': {}", queue, e);
+                self.backlogged = false;
+                return false;
+            }
+        };
+        self.backlogged = depth > max_depth;
+        if self.backlogged {
+            if self.shed == 0 {
+                println!(
+                    "[Bus] Queue '{}' holds {} messages (limit {}); shedding load until it drains.",
+                    queue, depth, max_depth
+                );
+            }
+            self.shed += 1;
+            return true;
+        }
+        if self.shed > 0 {
+            println!(
+                "[Bus] Queue '{}' back to {} messages; resuming after skipping {} message(s).",
+                queue, depth, self.shed
+            );
+            self.shed = 0;
+        }
+        false
+    }
+
+    // Returns whether the message was sent.
+    pub fn publish(&mut self, bus: &dyn MessageBus, queue: &str, payload: &[u8]) -> bool {
+        match bus.publish(queue, payload) {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                true
+            }
+            Err(e) => {
+                println!("[Bus] Error publishing to '{}': {}", queue, e);
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= PUBLISH_FAILURES_BEFORE_RECONNECT {
+                    println!(
+                        "[Bus] {} publishes failed in a row; reconnecting...",
+                        self.consecutive_failures
+                    );
+                    match bus.reconnect() {
+                        Ok(()) => self.consecutive_failures = 0,
+                        Err(e) => eprintln!("[Bus] Reconnect failed: {}", e),
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+// Stream of message bodies from one queue. Each message taken stays unacknowledged until ack is
+// called for it; the broker redelivers unacknowledged messages once the subscriber goes away.
+// Dropping it stops the delivery.
+pub struct Subscription {
+    receiver: Receiver<Vec<u8>>,
+    closed: Arc<AtomicBool>,
+    // None for transports without acknowledgements.
+    acks: Option<Sender<()>>,
+}
+
+impl Subscription {
+    fn new(receiver: Receiver<Vec<u8>>) -> (Self, Arc<AtomicBool>) {
+        let closed = Arc::new(AtomicBool::new(false));
+        let subscription = Self {
+            receiver,
+            closed: Arc::clone(&closed),
+            acks: None,
+        };
+        (subscription, closed)
+    }
+
+    // Acknowledges the oldest message taken but not yet acknowledged. Call it once the message
+    // has been processed, including when it was ignored.
+    pub fn ack(&self) {
+        if let Some(acks) = &self.acks {
+            let _ = acks.send(());
+        }
+    }
+
+    // Waits for the next message, checking `shutdown` between polls. Returns None once shutdown
+    // has been signalled or the transport has stopped delivering.
+    pub fn next_until(&self, shutdown: &watch::Receiver<bool>) -> Option<Vec<u8>> {
+        loop {
+            if *shutdown.borrow() {
+                return None;
+            }
+            match self.receiver.recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+                Ok(payload) => return Some(payload),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    // Next message if one is already waiting.
+    pub fn try_next(&self) -> Option<Vec<u8>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+// Messages taken through the iterator still need ack.
+impl Iterator for Subscription {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+// RabbitMQ transport. Publishes on one shared channel; every subscription consumes on its own
+// connection in a background thread, which acknowledges each message once the subscriber has.
+// The publishing channel and connection are shared with the background thread a reconnect runs
+// on.
+pub struct AmiquipBus {
+    url: String,
+    channel: Arc<Mutex<Channel>>,
+    declared: Arc<Mutex<HashSet<String>>>,
+    // Kept open for the publishing channel; None once the bus is closed.
+    connection: Arc<Mutex<Option<Connection>>>,
+    // Set while a reconnect is running.
+    reconnecting: Arc<AtomicBool>,
+}
+
+impl AmiquipBus {
+    pub fn connect(url: &str) -> amiquip::Result<Self> {
+        let mut connection = connect_with_retry(url, CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+        let channel = connection.open_channel(None)?;
+        Ok(Self {
+            url: url.to_string(),
+            channel: Arc::new(Mutex::new(channel)),
+            declared: Arc::default(),
+            connection: Arc::new(Mutex::new(Some(connection))),
+            reconnecting: Arc::default(),
+        })
+    }
+
+    // Whether a reconnect started by reconnect() is still running.
+    pub fn is_reconnecting(&self) -> bool {
+        self.reconnecting.load(Ordering::SeqCst)
+    }
+
+    pub fn close(self) -> amiquip::Result<()> {
+        match self.connection.lock().unwrap().take() {
+            Some(connection) => connection.close(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl MessageBus for AmiquipBus {
+    fn publish(&self, queue: &str, payload: &[u8]) -> BusResult<()> {
+        let channel = self.channel.lock().unwrap();
+        if self.declared.lock().unwrap().insert(queue.to_string()) {
+            channel.queue_declare(queue, QueueDeclareOptions::default())?;
+        }
+        Exchange::direct(&channel).publish(Publish::new(payload, queue))?;
+        Ok(())
+    }
+
+    fn subscribe(&self, queue: &str) -> BusResult<Subscription> {
+        let (sender, receiver) = mpsc::channel();
+        let (ack_sender, acks) = mpsc::channel();
+        let (mut subscription, closed) = Subscription::new(receiver);
+        subscription.acks = Some(ack_sender);
+        let url = self.url.clone();
+        let queue = queue.to_string();
+        thread::spawn(move || {
+            if let Err(e) = forward_queue(&url, &queue, &sender, &acks, &closed) {
+                eprintln!("[Bus] Subscription to '{}' ended: {}", queue, e);
+            }
+        });
+        Ok(subscription)
+    }
+
+    fn publish_congestion_alert(&self, routing_key: &str, payload: &[u8]) -> BusResult<()> {
+        let channel = self.channel.lock().unwrap();
+        declare_congestion_exchange(&channel)?.publish(Publish::new(payload, routing_key))?;
+        Ok(())
+    }
+
+    // Opens a fresh connection and publishing channel in place of the current ones. The connect
+    // and its backoff run on a background thread so the caller, usually an async loop, never
+    // sleeps through them; publishes keep failing until the new channel is in place. A call while
+    // a reconnect is already running does nothing. Queues are declared again on their next
+    // publish.
+    fn reconnect(&self) -> BusResult<()> {
+        if self.reconnecting.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let url = self.url.clone();
+        let channel = Arc::clone(&self.channel);
+        let declared = Arc::clone(&self.declared);
+        let current = Arc::clone(&self.connection);
+        let reconnecting = Arc::clone(&self.reconnecting);
+        thread::spawn(move || {
+            let reopened = connect_with_retry(&url, CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)
+                .and_then(|mut connection| {
+                    let new_channel = connection.open_channel(None)?;
+                    Ok((connection, new_channel))
+                });
+            match reopened {
+                Ok((connection, new_channel)) => {
+                    let mut current = current.lock().unwrap();
+                    if current.is_none() {
+                        // The bus was closed while reconnecting.
+                        let _ = connection.close();
+                    } else {
+                        *channel.lock().unwrap() = new_channel;
+                        if let Some(old) = current.replace(connection) {
+                            let _ = old.close();
+                        }
+                        declared.lock().unwrap().clear();
+                        println!("[Bus] Reconnected to the broker.");
+                    }
+                }
+                Err(e) => eprintln!("[Bus] Reconnect failed: {}", e),
+            }
+            reconnecting.store(false, Ordering::SeqCst);
+        });
+        Ok(())
+    }
+
+    // Re-declaring the queue with the options it was created with reports its message count
+    // without changing it.
+    fn queue_depth(&self, queue: &str) -> BusResult<Option<u32>> {
+        let channel = self.channel.lock().unwrap();
+        let declared = channel.queue_declare(queue, QueueDeclareOptions::default())?;
+        self.declared.lock().unwrap().insert(queue.to_string());
+        Ok(declared.declared_message_count())
+    }
+}
+
+// Consumes `queue` and hands each body to `sender` until the subscription is dropped. A delivery
+// is acknowledged to the broker when the subscriber acknowledges it on `acks`; at most
+// SUBSCRIPTION_PREFETCH are handed on unacknowledged. Closing the connection returns the rest
+// to the queue.
+fn forward_queue(
+    url: &str,
+    queue: &str,
+    sender: &Sender<Vec<u8>>,
+    acks: &Receiver<()>,
+    closed: &AtomicBool,
+) -> amiquip::Result<()> {
+    let mut connection = connect_with_retry(url, CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+    let channel = connection.open_channel(None)?;
+    channel.qos(0, SUBSCRIPTION_PREFETCH, false)?;
+    let consumer = channel
+        .queue_declare(queue, QueueDeclareOptions::default())?
+        .consume(ConsumerOptions::default())?;
+    let mut unacked = VecDeque::new();
+    while !closed.load(Ordering::SeqCst) {
+        while acks.try_recv().is_ok() {
+            if let Some(delivery) = unacked.pop_front() {
+                consumer.ack(delivery)?;
+            }
+        }
+        if unacked.len() >= usize::from(SUBSCRIPTION_PREFETCH) {
+            // The broker won't send more until the subscriber catches up.
+            match acks.recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+                Ok(()) => {
+                    if let Some(delivery) = unacked.pop_front() {
+                        consumer.ack(delivery)?;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            continue;
+        }
+        match consumer.receiver().recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+            Ok(ConsumerMessage::Delivery(delivery)) => {
+                if sender.send(delivery.body.clone()).is_err() {
+                    break;
+                }
+                unacked.push_back(delivery);
+            }
+            Ok(other) => {
+                println!("[Bus] Consumer for '{}' ended: {:?}", queue, other);
+                break;
+            }
+            Err(e) if e.is_timeout() => continue,
+            Err(_) => break,
+        }
+    }
+    connection.close()
+}
+
+// In-process transport for tests and embedding. Every published message is kept, and is also
+// delivered to the current subscribers of its queue. A message published to a queue nobody
+// subscribes to counts towards that queue's depth, as it would sit in a broker queue.
+#[derive(Default)]
+pub struct InMemoryBus {
+    published: Mutex<Vec<(String, Vec<u8>)>>,
+    subscribers: Mutex<HashMap<String, Vec<Sender<Vec<u8>>>>>,
+    depths: Mutex<HashMap<String, u32>>,
+}
+
+impl InMemoryBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Bodies published to `queue` so far, oldest first.
+    pub fn published_to(&self, queue: &str) -> Vec<Vec<u8>> {
+        self.published
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(q, _)| q == queue)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    // Every (queue, body) published so far, oldest first.
+    pub fn published(&self) -> Vec<(String, Vec<u8>)> {
+        self.published.lock().unwrap().clone()
+    }
+
+    // Overrides the depth of `queue`, e.g. to simulate a consumer draining it.
+    pub fn set_queue_depth(&self, queue: &str, depth: u32) {
+        self.depths.lock().unwrap().insert(queue.to_string(), depth);
+    }
+}
+
+impl MessageBus for InMemoryBus {
+    fn publish(&self, queue: &str, payload: &[u8]) -> BusResult<()> {
+        self.published
+            .lock()
+            .unwrap()
+            .push((queue.to_string(), payload.to_vec()));
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let delivered = match subscribers.get_mut(queue) {
+            Some(senders) => {
+                senders.retain(|sender| sender.send(payload.to_vec()).is_ok());
+                !senders.is_empty()
+            }
+            None => false,
+        };
+        if !delivered {
+            *self
+                .depths
+                .lock()
+                .unwrap()
+                .entry(queue.to_string())
+                .or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
+    fn queue_depth(&self, queue: &str) -> BusResult<Option<u32>> {
+        Ok(Some(
+            self.depths.lock().unwrap().get(queue).copied().unwrap_or(0),
+        ))
+    }
+
+    fn subscribe(&self, queue: &str) -> BusResult<Subscription> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry(queue.to_string())
+            .or_default()
+            .push(sender);
+        Ok(Subscription::new(receiver).0)
+    }
+}
@@ -0,0 +1,545 @@
+// config.rs
+use crate::c1_tp063879::lanes::OccupancyMetric;
+use crate::c1_tp063879::vehicles::{VehicleType, VehicleWeights};
+use crate::c1_tp063879::weather::WeatherChange;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::time::Duration;
+
+// Min/max speed (km/h) a vehicle type is spawned with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeedRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl SpeedRange {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    // A vehicle is considered overspeeding if its speed is in the upper 10% of its spawn range.
+    pub fn overspeed_threshold(&self) -> f64 {
+        self.min + 0.90 * (self.max - self.min)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedRanges {
+    pub car: SpeedRange,
+    pub bus: SpeedRange,
+    pub truck: SpeedRange,
+    pub emergency_van: SpeedRange,
+}
+
+impl Default for SpeedRanges {
+    fn default() -> Self {
+        Self {
+            car: SpeedRange::new(80.0, 140.0),
+            bus: SpeedRange::new(70.0, 100.0),
+            truck: SpeedRange::new(60.0, 90.0),
+            emergency_van: SpeedRange::new(120.0, 180.0),
+        }
+    }
+}
+
+impl SpeedRanges {
+    pub fn for_type(&self, vehicle_type: VehicleType) -> SpeedRange {
+        match vehicle_type {
+            VehicleType::Car => self.car,
+            VehicleType::Bus => self.bus,
+            VehicleType::Truck => self.truck,
+            VehicleType::EmergencyVan => self.emergency_van,
+        }
+    }
+}
+
+// Rush-hour spawn cycle: the spawn count ramps from min_spawn to max_spawn over the first half
+// of the cycle and back down over the second half.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RushHourConfig {
+    pub cycle_secs: u64,
+    pub min_spawn: usize,
+    pub max_spawn: usize,
+}
+
+impl Default for RushHourConfig {
+    fn default() -> Self {
+        Self {
+            cycle_secs: 40,
+            min_spawn: 2,
+            max_spawn: 5,
+        }
+    }
+}
+
+impl RushHourConfig {
+    pub fn new(cycle_secs: u64, min_spawn: usize, max_spawn: usize) -> Self {
+        Self {
+            cycle_secs,
+            min_spawn,
+            max_spawn,
+        }
+    }
+
+    // Number of vehicles to spawn `elapsed` simulated seconds into the run. Increases linearly
+    // from min_spawn to max_spawn during the first half of the cycle, then decreases back to
+    // min_spawn over the second half, and repeats (2 to 5 over 40 seconds by default).
+    // Shared by every runner so they all show the same peak/off-peak dynamics.
+    pub fn spawn_count(&self, elapsed: u64) -> usize {
+        let cycle_duration = self.cycle_secs.max(2); // full cycle length in seconds
+        let half_cycle = cycle_duration / 2;
+        let cycle_time = elapsed % cycle_duration;
+        let min_spawn = self.min_spawn as f64;
+        let spawn_range = self.max_spawn as f64 - min_spawn;
+        let spawn_count = if cycle_time <= half_cycle {
+            // Increasing phase: min_spawn to max_spawn vehicles.
+            let ratio = cycle_time as f64 / half_cycle as f64;
+            min_spawn + ratio * spawn_range
+        } else {
+            // Decreasing phase: max_spawn to min_spawn vehicles.
+            let ratio = (cycle_time - half_cycle) as f64 / half_cycle as f64;
+            min_spawn + spawn_range - ratio * spawn_range
+        };
+        spawn_count.round().max(0.0) as usize
+    }
+}
+
+// Demand suppression: when the grid-wide mean intersection congestion rises above `high_water`
+// the spawn batch is cut by `backoff`, and once it falls below `low_water` it grows back by
+// `recovery` per tick, never above the rush-hour (or demand profile) count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnMeteringConfig {
+    pub enabled: bool,
+    pub high_water: f64,
+    pub low_water: f64,
+    pub backoff: f64,
+    pub recovery: f64,
+}
+
+impl Default for SpawnMeteringConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_water: 0.7,
+            low_water: 0.5,
+            backoff: 0.5,
+            recovery: 0.1,
+        }
+    }
+}
+
+// Ramp meter for one entry intersection: at most `max_vehicles` are released onto the network
+// in each `window_secs` window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RampMeterConfig {
+    pub max_vehicles: u32,
+    #[serde(default = "default_ramp_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_ramp_window_secs() -> u64 {
+    60
+}
+
+// Vehicle mix used from `start_hour` up to (not including) `end_hour` of the simulated day, in
+// hours 0-23. A period whose start is after its end wraps past midnight, e.g. 22 to 5.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleMixPeriod {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub mix: VehicleWeights,
+}
+
+impl VehicleMixPeriod {
+    pub fn covers(&self, hour: u32) -> bool {
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+// Hour of the (UTC) day a Unix timestamp falls in.
+pub fn hour_of_day(unix_secs: u64) -> u32 {
+    ((unix_secs % 86_400) / 3_600) as u32
+}
+
+// Crash severities and how long each one blocks its lane. Severity is drawn from
+// min_severity..=max_severity; with `minor_skew` above 1.0 each step up in severity is that many
+// times less likely than the one below (1.0 = uniform). A crash keeps its lane blocked for
+// `clearance_secs_per_severity` seconds per severity point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrashSeverityConfig {
+    pub min_severity: i8,
+    pub max_severity: i8,
+    pub minor_skew: f64,
+    pub clearance_secs_per_severity: f64,
+}
+
+impl Default for CrashSeverityConfig {
+    fn default() -> Self {
+        Self {
+            min_severity: 1,
+            max_severity: 3,
+            minor_skew: 1.0,
+            clearance_secs_per_severity: 1.5,
+        }
+    }
+}
+
+impl CrashSeverityConfig {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> i8 {
+        let min = self.min_severity;
+        let max = self.max_severity.max(min);
+        if self.minor_skew <= 1.0 {
+            return rng.random_range(min..=max);
+        }
+        // Weight of severity min + k is minor_skew^-k.
+        let weights: Vec<f64> = (0..=(max as i32 - min as i32))
+            .map(|k| self.minor_skew.powi(-k))
+            .collect();
+        let mut roll = rng.random_range(0.0..weights.iter().sum::<f64>());
+        for (k, weight) in weights.iter().enumerate() {
+            if roll < *weight {
+                return min + k as i8;
+            }
+            roll -= weight;
+        }
+        max
+    }
+
+    // Seconds a crash of the given severity blocks its lane.
+    pub fn clearance_secs(&self, severity: i8) -> f64 {
+        severity as f64 * self.clearance_secs_per_severity
+    }
+}
+
+// Tunable simulation parameters. Any field missing from the TOML file keeps its default,
+// which matches the values the simulation has always used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub vehicle_mix: VehicleWeights,
+    // Time-of-day overrides of vehicle_mix; the first period covering the hour wins.
+    pub vehicle_mix_schedule: Vec<VehicleMixPeriod>,
+    // Hour of the simulated day (0-23) a run starts at. The simulated clock advances with
+    // simulated elapsed time only, so a seeded run sees the same hours whenever it is run.
+    pub start_hour: u32,
+    pub speed_ranges: SpeedRanges,
+    pub rush_hour: RushHourConfig,
+    pub spawn_metering: SpawnMeteringConfig,
+    // Ramp meters keyed by entry intersection name. Unlisted entries release without limit.
+    pub ramp_meters: HashMap<String, RampMeterConfig>,
+    // Crash probability per lane for vehicles within / above their speed limit.
+    pub crash_probability: f64,
+    pub overspeed_crash_probability: f64,
+    // Collision probability with a slower vehicle directly ahead, scaled by lane occupancy.
+    pub collision_probability: f64,
+    pub crash_severity: CrashSeverityConfig,
+    // Lanes with a history of accidents and how many times likelier a crash is on them. Either
+    // listed directly or, with accident_history_path set, taken from the lanes a monitoring
+    // system's accident_info.csv shows above high_risk_accidents_per_hour.
+    pub high_risk_lanes: Vec<String>,
+    pub high_risk_crash_multiplier: f64,
+    pub accident_history_path: Option<String>,
+    pub high_risk_accidents_per_hour: f64,
+    // Lanes above this occupancy are avoided when routing newly spawned vehicles.
+    pub route_occupancy_threshold: f64,
+    // Extra routing cost (in meters) emergency vehicles pay per intersection, so they prefer
+    // routes with fewer stops.
+    pub emergency_intersection_penalty_meters: f64,
+    // Whether published lane occupancy is length-based (default) or vehicle-count-based.
+    pub occupancy_metric: OccupancyMetric,
+    // When set, every lane is as long as the straight-line distance between its intersections'
+    // grid positions times this many meters, instead of its hand-set length.
+    pub lane_meters_per_cell: Option<f64>,
+    // Optional CSV of per-entry demand profiles. When set, it replaces the rush-hour curve.
+    pub demand_profiles_path: Option<String>,
+    // Optional JSON-lines file every spawn, move, crash and light change is appended to.
+    pub event_log_path: Option<String>,
+    // Optional file the full simulation state is checkpointed to every `snapshot_interval_ticks`.
+    pub snapshot_path: Option<String>,
+    pub snapshot_interval_ticks: u64,
+    // Weather changes over the run; Clear until the first entry starts.
+    pub weather_schedule: Vec<WeatherChange>,
+    // Simulated milliseconds between spawn iterations of the main loop, and between updates of
+    // the traffic lights. The two are independent.
+    pub tick_millis: u64,
+    pub light_tick_millis: u64,
+    // Traffic updates are skipped while the traffic_data queue holds more messages than this, so
+    // a slow analyzer doesn't let it grow without bound. None publishes every tick regardless.
+    pub max_traffic_data_backlog: Option<u32>,
+    // Vehicle events of skipped traffic updates held for the next published one. Beyond this the
+    // oldest are dropped.
+    pub max_deferred_vehicle_events: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            vehicle_mix: VehicleWeights::default(),
+            vehicle_mix_schedule: Vec::new(),
+            start_hour: 0,
+            speed_ranges: SpeedRanges::default(),
+            rush_hour: RushHourConfig::default(),
+            spawn_metering: SpawnMeteringConfig::default(),
+            ramp_meters: HashMap::new(),
+            crash_probability: 0.10,
+            overspeed_crash_probability: 0.15,
+            collision_probability: 0.30,
+            crash_severity: CrashSeverityConfig::default(),
+            high_risk_lanes: Vec::new(),
+            high_risk_crash_multiplier: 2.0,
+            accident_history_path: None,
+            high_risk_accidents_per_hour: 2.0,
+            route_occupancy_threshold: 0.75,
+            emergency_intersection_penalty_meters: 200.0,
+            occupancy_metric: OccupancyMetric::Length,
+            lane_meters_per_cell: None,
+            demand_profiles_path: None,
+            event_log_path: None,
+            snapshot_path: None,
+            snapshot_interval_ticks: 60,
+            weather_schedule: Vec::new(),
+            tick_millis: 1000,
+            light_tick_millis: 1000,
+            max_traffic_data_backlog: Some(100),
+            max_deferred_vehicle_events: 1000,
+        }
+    }
+}
+
+// Why a loaded config was rejected. Each of these would otherwise panic mid-run when a vehicle
+// is spawned or a crash is sampled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    // A vehicle type's speed range is empty (min not below max).
+    EmptySpeedRange {
+        vehicle_type: VehicleType,
+        min: f64,
+        max: f64,
+    },
+    // A vehicle mix has no positive weight; `period` is the schedule index, None for vehicle_mix.
+    ZeroVehicleMix {
+        period: Option<usize>,
+    },
+    // Severities run backwards or the skew can't weight them.
+    InvalidCrashSeverity {
+        min: i8,
+        max: i8,
+        minor_skew: f64,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::EmptySpeedRange {
+                vehicle_type,
+                min,
+                max,
+            } => write!(
+                f,
+                "speed range for {:?} must have min below max, got {} to {}",
+                vehicle_type, min, max
+            ),
+            ConfigError::ZeroVehicleMix { period: None } => {
+                write!(f, "vehicle_mix needs at least one positive weight")
+            }
+            ConfigError::ZeroVehicleMix {
+                period: Some(index),
+            } => write!(
+                f,
+                "vehicle_mix_schedule[{}] needs at least one positive weight",
+                index
+            ),
+            ConfigError::InvalidCrashSeverity {
+                min,
+                max,
+                minor_skew,
+            } => write!(
+                f,
+                "crash severity needs min_severity <= max_severity and a finite positive \
+                 minor_skew, got {} to {} with skew {}",
+                min, max, minor_skew
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+fn has_positive_weight(weights: &VehicleWeights) -> bool {
+    [
+        weights.car,
+        weights.truck,
+        weights.bus,
+        weights.emergency_van,
+    ]
+    .iter()
+    .any(|w| *w > 0.0)
+}
+
+impl SimulationConfig {
+    // Parses and validates a config; see `validate` for what is rejected.
+    pub fn from_toml_str(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Self = toml::from_str(contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Rejects values the simulation can't sample from: empty speed ranges, vehicle mixes
+    // without a positive weight and crash severities that can't be drawn.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for vehicle_type in [
+            VehicleType::Car,
+            VehicleType::Bus,
+            VehicleType::Truck,
+            VehicleType::EmergencyVan,
+        ] {
+            let range = self.speed_ranges.for_type(vehicle_type);
+            if range.min.is_nan() || range.max.is_nan() || range.min >= range.max {
+                return Err(ConfigError::EmptySpeedRange {
+                    vehicle_type,
+                    min: range.min,
+                    max: range.max,
+                });
+            }
+        }
+        if !has_positive_weight(&self.vehicle_mix) {
+            return Err(ConfigError::ZeroVehicleMix { period: None });
+        }
+        if let Some(index) = self
+            .vehicle_mix_schedule
+            .iter()
+            .position(|period| !has_positive_weight(&period.mix))
+        {
+            return Err(ConfigError::ZeroVehicleMix {
+                period: Some(index),
+            });
+        }
+        let severity = &self.crash_severity;
+        if severity.min_severity > severity.max_severity
+            || !severity.minor_skew.is_finite()
+            || severity.minor_skew <= 0.0
+        {
+            return Err(ConfigError::InvalidCrashSeverity {
+                min: severity.min_severity,
+                max: severity.max_severity,
+                minor_skew: severity.minor_skew,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Self::from_toml_str(&contents)
+    }
+
+    // Hour of the simulated day `elapsed_secs` simulated seconds into the run.
+    pub fn hour_at(&self, elapsed_secs: u64) -> u32 {
+        hour_of_day(u64::from(self.start_hour % 24) * 3_600 + elapsed_secs)
+    }
+
+    // Vehicle type weights in effect at the given hour of the simulated day.
+    pub fn vehicle_mix_at(&self, hour: u32) -> &VehicleWeights {
+        self.vehicle_mix_schedule
+            .iter()
+            .find(|period| period.covers(hour))
+            .map(|period| &period.mix)
+            .unwrap_or(&self.vehicle_mix)
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_millis(self.tick_millis.max(1))
+    }
+
+    pub fn light_tick_duration(&self) -> Duration {
+        Duration::from_millis(self.light_tick_millis.max(1))
+    }
+}
+
+// One row of a demand profile CSV: from `minute` onwards, `entry_id` (the entry intersection's
+// name) receives `vehicles_per_minute` until the next row for the same entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemandRecord {
+    pub entry_id: String,
+    pub minute: u64,
+    pub vehicles_per_minute: f64,
+}
+
+// Slack when turning carried fractional arrivals into whole vehicles.
+const SPAWN_CARRY_EPSILON: f64 = 1e-9;
+
+// Time-varying arrival rates per entry intersection.
+#[derive(Debug, Clone, Default)]
+pub struct DemandProfiles {
+    // Entry name -> (minute, vehicles_per_minute) steps, sorted by minute.
+    profiles: HashMap<String, Vec<(u64, f64)>>,
+}
+
+impl DemandProfiles {
+    pub fn from_records(records: Vec<DemandRecord>) -> Self {
+        let mut profiles: HashMap<String, Vec<(u64, f64)>> = HashMap::new();
+        for record in records {
+            profiles
+                .entry(record.entry_id)
+                .or_default()
+                .push((record.minute, record.vehicles_per_minute.max(0.0)));
+        }
+        for steps in profiles.values_mut() {
+            steps.sort_by_key(|(minute, _)| *minute);
+        }
+        Self { profiles }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)?;
+        let mut records = Vec::new();
+        for result in rdr.deserialize() {
+            let record: DemandRecord = result?;
+            records.push(record);
+        }
+        Ok(Self::from_records(records))
+    }
+
+    // Arrival rate for an entry at the given simulated second. Entries without a profile, or
+    // times before the entry's first row, have no demand.
+    pub fn vehicles_per_minute(&self, entry_id: &str, elapsed_secs: u64) -> f64 {
+        let minute = elapsed_secs / 60;
+        self.profiles
+            .get(entry_id)
+            .and_then(|steps| steps.iter().take_while(|(m, _)| *m <= minute).last())
+            .map(|(_, rate)| *rate)
+            .unwrap_or(0.0)
+    }
+
+    // Number of vehicles to spawn at an entry this tick. Fractional arrivals are carried over in
+    // `carry` so low rates still spawn a vehicle every few ticks. The carry is floored with a
+    // little slack, since e.g. ten 0.1-vehicle ticks add up to just under one in floating point.
+    pub fn spawns_for_tick(
+        &self,
+        entry_id: &str,
+        elapsed_secs: u64,
+        tick_secs: f64,
+        carry: &mut f64,
+    ) -> usize {
+        *carry += self.vehicles_per_minute(entry_id, elapsed_secs) * tick_secs / 60.0;
+        let count = (*carry + SPAWN_CARRY_EPSILON).floor();
+        *carry = (*carry - count).max(0.0);
+        count as usize
+    }
+}
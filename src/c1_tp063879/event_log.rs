@@ -0,0 +1,124 @@
+// event_log.rs
+use crate::c1_tp063879::intersections::IntersectionId;
+use crate::shared_data::current_timestamp;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+// Everything that changes the state of the network during a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SimulationEvent {
+    VehicleSpawned {
+        vehicle_id: u64,
+        vehicle_type: String,
+        entry: IntersectionId,
+        exit: IntersectionId,
+        route: Vec<String>,
+    },
+    VehicleMoved {
+        vehicle_id: u64,
+        lane: String,
+    },
+    VehicleCrashed {
+        vehicle_id: u64,
+        lane: String,
+        severity: i8,
+    },
+    VehicleArrived {
+        vehicle_id: u64,
+        waiting_time: u64,
+    },
+    LightChanged {
+        intersection: IntersectionId,
+        phase_index: usize,
+        green_lanes: Vec<String>,
+    },
+}
+
+// One line of the JSON-lines event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub timestamp: u64,
+    pub event: SimulationEvent,
+}
+
+// Cheap-to-clone handle every vehicle task writes through. Events go to the log file and/or the
+// stream receiver, whichever are set; a disabled log ignores all events.
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    writer: Option<Arc<Mutex<LineWriter<File>>>>,
+    stream: Option<Sender<SimulationEvent>>,
+}
+
+impl EventLog {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    // Appends to `path`, creating the file if needed.
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Some(Arc::new(Mutex::new(LineWriter::new(file)))),
+            stream: None,
+        })
+    }
+
+    // Also hands every recorded event to `sender`, so other tasks can react to moves as they
+    // happen. A dropped receiver just stops the stream.
+    pub fn with_stream(mut self, sender: Sender<SimulationEvent>) -> Self {
+        self.stream = Some(sender);
+        self
+    }
+
+    pub fn record(&self, event: SimulationEvent) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.send(event.clone());
+        }
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let entry = LoggedEvent {
+            timestamp: current_timestamp(),
+            event,
+        };
+        match serde_json::to_string(&entry) {
+            Ok(line) => {
+                if let Err(e) = writeln!(writer.lock().unwrap(), "{}", line) {
+                    eprintln!("Error writing event log: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Error serializing event: {}", e),
+        }
+    }
+}
+
+pub fn read_event_log(path: &str) -> Result<Vec<LoggedEvent>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line)?);
+    }
+    Ok(events)
+}
+
+// Re-drives a recorded run without the RNG-driven engine: every logged event is handed to
+// `emit` in the order it was written. Returns the number of events replayed.
+pub fn replay_from_log<F>(path: &str, mut emit: F) -> Result<usize, Box<dyn Error>>
+where
+    F: FnMut(&LoggedEvent),
+{
+    let events = read_event_log(path)?;
+    for event in &events {
+        emit(event);
+    }
+    Ok(events.len())
+}
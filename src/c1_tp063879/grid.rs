@@ -0,0 +1,121 @@
+// grid.rs
+use crate::c1_tp063879::intersections::{Intersection, IntersectionControl, IntersectionId};
+use crate::c1_tp063879::lanes::{Lane, LaneError, DEFAULT_LANE_SPEED_LIMIT_KMH};
+
+// Length used for every lane unless with_lane_length says otherwise.
+pub const DEFAULT_GRID_LANE_LENGTH_METERS: f64 = 200.0;
+
+type CellRule<T> = Box<dyn Fn(i8, i8) -> T>;
+
+// Generates a rows x cols grid network: one intersection per cell and a lane in each direction
+// between every pair of horizontal and vertical neighbours. By default the left column is the
+// entries, the right column the exits, every intersection has a traffic light and every lane is
+// DEFAULT_GRID_LANE_LENGTH_METERS long with a DEFAULT_LANE_SPEED_LIMIT_KMH limit; each rule can be
+// replaced with the with_* methods.
+pub struct GridBuilder {
+    rows: i8,
+    cols: i8,
+    is_entry: CellRule<bool>,
+    is_exit: CellRule<bool>,
+    control: CellRule<IntersectionControl>,
+    lane_length: Box<dyn Fn(IntersectionId, IntersectionId) -> f64>,
+    speed_limit: f64,
+}
+
+impl GridBuilder {
+    pub fn rectangular(rows: i8, cols: i8) -> Self {
+        Self {
+            rows,
+            cols,
+            is_entry: Box::new(|_, col| col == 0),
+            is_exit: Box::new(move |_, col| col == cols - 1),
+            control: Box::new(|_, _| IntersectionControl::TrafficLight),
+            lane_length: Box::new(|_, _| DEFAULT_GRID_LANE_LENGTH_METERS),
+            speed_limit: DEFAULT_LANE_SPEED_LIMIT_KMH,
+        }
+    }
+
+    // Which (row, col) cells vehicles spawn at.
+    pub fn with_entries(mut self, rule: impl Fn(i8, i8) -> bool + 'static) -> Self {
+        self.is_entry = Box::new(rule);
+        self
+    }
+
+    // Which (row, col) cells vehicles leave the network at.
+    pub fn with_exits(mut self, rule: impl Fn(i8, i8) -> bool + 'static) -> Self {
+        self.is_exit = Box::new(rule);
+        self
+    }
+
+    pub fn with_control(mut self, rule: impl Fn(i8, i8) -> IntersectionControl + 'static) -> Self {
+        self.control = Box::new(rule);
+        self
+    }
+
+    // Length in meters of the lane from one intersection to its neighbour.
+    pub fn with_lane_length(
+        mut self,
+        length: impl Fn(IntersectionId, IntersectionId) -> f64 + 'static,
+    ) -> Self {
+        self.lane_length = Box::new(length);
+        self
+    }
+
+    // Posted limit (km/h) of every lane.
+    pub fn with_speed_limit(mut self, speed_limit: f64) -> Self {
+        self.speed_limit = speed_limit;
+        self
+    }
+
+    // Intersections in row-major order and lanes in the order they were generated. Fails if the
+    // length function gives a lane a non-positive length.
+    pub fn build(&self) -> Result<(Vec<Intersection>, Vec<Lane>), LaneError> {
+        let mut intersections = Vec::new();
+        let mut lanes = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                intersections.push(Intersection::new(
+                    self.intersection_name(row, col),
+                    row,
+                    col,
+                    (self.is_entry)(row, col),
+                    (self.is_exit)(row, col),
+                    (self.control)(row, col),
+                ));
+                let here = IntersectionId(row, col);
+                let mut neighbours = Vec::new();
+                if col + 1 < self.cols {
+                    neighbours.push(IntersectionId(row, col + 1));
+                }
+                if row + 1 < self.rows {
+                    neighbours.push(IntersectionId(row + 1, col));
+                }
+                for there in neighbours {
+                    lanes.push(self.lane(here, there)?);
+                    lanes.push(self.lane(there, here)?);
+                }
+            }
+        }
+        Ok((intersections, lanes))
+    }
+
+    fn lane(&self, from: IntersectionId, to: IntersectionId) -> Result<Lane, LaneError> {
+        Lane::new(
+            format!("({},{}) -> ({},{})", from.0, from.1, to.0, to.1),
+            from,
+            to,
+            (self.lane_length)(from, to),
+        )
+        .map(|lane| lane.with_speed_limit(self.speed_limit))
+    }
+
+    // Same "Intersection 12" naming as the built-in map; grids wider than 10 separate row and
+    // column so the names stay unique.
+    fn intersection_name(&self, row: i8, col: i8) -> String {
+        if self.rows <= 10 && self.cols <= 10 {
+            format!("Intersection {}{}", row, col)
+        } else {
+            format!("Intersection {}_{}", row, col)
+        }
+    }
+}
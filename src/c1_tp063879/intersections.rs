@@ -1,12 +1,67 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct IntersectionId(pub i8, pub i8);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// Canonical text form, "IntersectionId(row, col)", used wherever an intersection is named in a
+// published message, log or routing key.
+impl fmt::Display for IntersectionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IntersectionId({}, {})", self.0, self.1)
+    }
+}
+
+// Numeric form for messages that carry a u32 id: the row in the high byte and the column in the
+// low byte, so IntersectionId(1, 2) is 0x0102 (258). Each i8 keeps its bit pattern, so negative
+// coordinates still map to distinct ids.
+impl From<IntersectionId> for u32 {
+    fn from(id: IntersectionId) -> Self {
+        ((id.0 as u8 as u32) << 8) | id.1 as u8 as u32
+    }
+}
+
+// Text that isn't an intersection id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIntersectionIdError(pub String);
+
+impl fmt::Display for ParseIntersectionIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid intersection id '{}': expected IntersectionId(row, col) or row,col",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseIntersectionIdError {}
+
+// Parses the canonical form or a bare "row,col" as typed by a user; whitespace is ignored.
+impl FromStr for IntersectionId {
+    type Err = ParseIntersectionIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let inner = trimmed
+            .strip_prefix("IntersectionId(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(trimmed);
+        inner
+            .split_once(',')
+            .and_then(|(row, col)| Some(Self(row.trim().parse().ok()?, col.trim().parse().ok()?)))
+            .ok_or_else(|| ParseIntersectionIdError(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum IntersectionControl {
     Normal,       // Standard intersection without traffic lights
     TrafficLight, // Intersection with traffic light control
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Intersection {
     pub id: IntersectionId,
     pub name: String,
@@ -14,7 +69,14 @@ pub struct Intersection {
     pub is_exit: bool,
     // Defines if the intersection has a traffic light or is a normal junction.
     pub control: IntersectionControl,
+    // Movements vehicles may not make here, as (neighbour arrived from, neighbour left towards)
+    // pairs, e.g. a banned left turn.
+    #[serde(default)]
+    pub forbidden_turns: Vec<(IntersectionId, IntersectionId)>,
+    // Total seconds vehicles have waited here and how many waits that covers.
     waiting_time: f64,
+    #[serde(default)]
+    wait_count: u64,
 }
 
 impl Intersection {
@@ -32,15 +94,42 @@ impl Intersection {
             is_entry,
             is_exit,
             control,
+            forbidden_turns: Vec::new(),
             waiting_time: 0.0,
+            wait_count: 0,
         }
     }
 
+    // Bans driving through this intersection from `from` on to `to`.
+    pub fn with_forbidden_turn(mut self, from: IntersectionId, to: IntersectionId) -> Self {
+        self.forbidden_turns.push((from, to));
+        self
+    }
+
+    // Records one vehicle having waited `secs` at this intersection.
+    pub fn record_wait(&mut self, secs: f64) {
+        self.waiting_time += secs;
+        self.wait_count += 1;
+    }
+
+    // Mean wait per recorded vehicle (0.0 before any vehicle has waited).
     pub fn avg_waiting_time(&self) -> f64 {
-        self.waiting_time
+        if self.wait_count == 0 {
+            return 0.0;
+        }
+        self.waiting_time / self.wait_count as f64
     }
 }
 
+// Entry points that aren't also exits: vehicles start there but may never route into them.
+pub fn entry_only_intersections(intersections: &[Intersection]) -> HashSet<IntersectionId> {
+    intersections
+        .iter()
+        .filter(|i| i.is_entry && !i.is_exit)
+        .map(|i| i.id)
+        .collect()
+}
+
 pub fn create_intersections() -> Vec<Intersection> {
     vec![
         Intersection::new(
@@ -1,79 +1,603 @@
 use crate::c1_tp063879::intersections::IntersectionId;
 use crate::c1_tp063879::vehicles::Vehicle;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
 
-#[derive(Debug, Clone)]
+// Default safe-following gap kept behind every vehicle on a lane.
+pub const DEFAULT_MIN_GAP_METERS: f64 = 2.0;
+// Overtaking is only possible while the lane is less full than this.
+pub const OVERTAKING_MAX_OCCUPANCY: f64 = 0.5;
+// Vehicle length (a car) used to turn a lane's length into a maximum vehicle count.
+pub const REFERENCE_VEHICLE_LENGTH_METERS: f64 = 2.0;
+// Number of most recent vehicle speeds each lane keeps for its speeding statistics.
+pub const LANE_SPEED_SAMPLES: usize = 50;
+// Posted limit (km/h) of the built-in and generated lanes. Above it sits the top sixth of the
+// car spawn range; buses and trucks are spawned below it.
+pub const DEFAULT_LANE_SPEED_LIMIT_KMH: f64 = 130.0;
+// Share of a lane's usable length other vehicles may fill while an emergency vehicle is on it.
+pub const DEFAULT_EMERGENCY_CAPACITY_FACTOR: f64 = 0.5;
+
+// How lane occupancy is measured: by the space vehicles take up, or by how many there are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum OccupancyMetric {
+    #[default]
+    Length,
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Lane {
     pub name: String,
     pub from: IntersectionId,
     pub to: IntersectionId,
     pub length_meters: f64,
+    // Space taken by vehicles on the lane, including each vehicle's following gap.
     pub current_vehicle_length: f64,
+    // Minimum gap kept behind each vehicle, so vehicles don't pack bumper-to-bumper.
+    pub min_gap: f64,
     pub has_emergency_vehicle: bool,
     pub has_accident: bool,
+    // Severity of the worst crash still blocking the lane (0 while it is clear). Vehicles that
+    // enter the lane wait out its clearance time.
+    #[serde(default)]
+    pub accident_severity: i8,
+    // Total seconds vehicles on this lane have waited at its traffic light, and how many waits
+    // that covers.
     pub waiting_time: f64,
+    #[serde(default)]
+    pub wait_count: u64,
     // FIFO queue to store vehicles on the lane.
     pub vehicle_queue: VecDeque<Vehicle>,
+    // Longest the vehicle queue has been over the run.
+    #[serde(default)]
+    pub max_queue_len: usize,
+    // Fraction of length_meters usable by vehicles (1.0 = full capacity).
+    // Scaled down temporarily by active CapacityModifiers.
+    pub capacity_factor: f64,
+    // Whether faster vehicles may pass slower ones on this lane.
+    #[serde(default)]
+    pub allow_overtaking: bool,
+    // Emergency vehicles currently holding this lane clear ahead of them. While non-zero no
+    // other vehicle may enter, so the vehicles already on it drain away.
+    #[serde(default)]
+    pub emergency_reservations: u32,
+    // Share of the usable length other vehicles may still fill while an emergency vehicle is on
+    // the lane, DEFAULT_EMERGENCY_CAPACITY_FACTOR unless set. 0.0 keeps them off the lane entirely.
+    #[serde(default = "default_emergency_capacity_factor")]
+    pub emergency_capacity_factor: f64,
+    // Posted limit (km/h). Lanes from create_lanes and GridBuilder have one; on a hand-built lane
+    // without one, vehicles are judged against their type's spawn range.
+    #[serde(default)]
+    pub speed_limit: Option<f64>,
+    // Top speed of the last LANE_SPEED_SAMPLES vehicles onto the lane and whether each was
+    // overspeeding.
+    #[serde(default)]
+    pub recent_speeds: VecDeque<(f64, bool)>,
+}
+
+fn default_emergency_capacity_factor() -> f64 {
+    DEFAULT_EMERGENCY_CAPACITY_FACTOR
+}
+
+// Direction of travel a lane runs along, ignoring which way. Opposite lanes share an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LaneAxis {
+    // Same row.
+    Horizontal,
+    // Same column.
+    Vertical,
+    // Row and column change in the same direction, e.g. (1,1) -> (2,2).
+    Diagonal,
+    // Row and column change in opposite directions, e.g. (1,2) -> (2,1).
+    AntiDiagonal,
+}
+
+// Why a lane definition was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaneError {
+    // Length is zero, negative or NaN.
+    NonPositiveLength { name: String, length_meters: f64 },
+    // The lane starts and ends at the same intersection.
+    SelfLoop { name: String, at: IntersectionId },
 }
 
+impl fmt::Display for LaneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaneError::NonPositiveLength {
+                name,
+                length_meters,
+            } => write!(
+                f,
+                "lane {} has non-positive length {} m",
+                name, length_meters
+            ),
+            LaneError::SelfLoop { name, at } => {
+                write!(f, "lane {} starts and ends at {:?}", name, at)
+            }
+        }
+    }
+}
+
+impl Error for LaneError {}
+
 impl Lane {
-    pub fn new(name: String, from: IntersectionId, to: IntersectionId, length_meters: f64) -> Self {
-        Self {
+    // Rejects lanes with a non-positive length or the same start and end intersection, which
+    // would break travel times, occupancy ratios and routing.
+    pub fn new(
+        name: String,
+        from: IntersectionId,
+        to: IntersectionId,
+        length_meters: f64,
+    ) -> Result<Self, LaneError> {
+        if length_meters.is_nan() || length_meters <= 0.0 {
+            return Err(LaneError::NonPositiveLength {
+                name,
+                length_meters,
+            });
+        }
+        if from == to {
+            return Err(LaneError::SelfLoop { name, at: from });
+        }
+        Ok(Self {
             name,
             from,
             to,
             length_meters,
             current_vehicle_length: 0.0,
+            min_gap: DEFAULT_MIN_GAP_METERS,
             has_emergency_vehicle: false,
             has_accident: false,
+            accident_severity: 0,
             waiting_time: 0.0,
+            wait_count: 0,
             vehicle_queue: VecDeque::new(),
+            max_queue_len: 0,
+            capacity_factor: 1.0,
+            allow_overtaking: false,
+            emergency_reservations: 0,
+            emergency_capacity_factor: DEFAULT_EMERGENCY_CAPACITY_FACTOR,
+            speed_limit: None,
+            recent_speeds: VecDeque::new(),
+        })
+    }
+
+    pub fn axis(&self) -> LaneAxis {
+        let d_row = self.to.0 as i16 - self.from.0 as i16;
+        let d_col = self.to.1 as i16 - self.from.1 as i16;
+        if d_row == 0 {
+            LaneAxis::Horizontal
+        } else if d_col == 0 {
+            LaneAxis::Vertical
+        } else if d_row.signum() == d_col.signum() {
+            LaneAxis::Diagonal
+        } else {
+            LaneAxis::AntiDiagonal
+        }
+    }
+
+    // Direction of travel as the sign of the row and column change, e.g. (1, 0) towards the next
+    // row. Unlike axis(), opposing flows get different headings.
+    pub fn heading(&self) -> (i8, i8) {
+        let d_row = self.to.0 as i16 - self.from.0 as i16;
+        let d_col = self.to.1 as i16 - self.from.1 as i16;
+        (d_row.signum() as i8, d_col.signum() as i8)
+    }
+
+    // Records one vehicle having waited `secs` at this lane's light.
+    pub fn record_wait(&mut self, secs: f64) {
+        self.waiting_time += secs;
+        self.wait_count += 1;
+    }
+
+    // Mean wait per recorded vehicle (0.0 before any vehicle has waited).
+    pub fn avg_waiting_time(&self) -> f64 {
+        if self.wait_count == 0 {
+            return 0.0;
+        }
+        self.waiting_time / self.wait_count as f64
+    }
+
+    pub fn with_emergency_capacity_factor(mut self, factor: f64) -> Self {
+        self.emergency_capacity_factor = factor.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_speed_limit(mut self, speed_limit: f64) -> Self {
+        self.speed_limit = Some(speed_limit);
+        self
+    }
+
+    pub fn record_speed(&mut self, speed: f64, overspeeding: bool) {
+        if self.recent_speeds.len() == LANE_SPEED_SAMPLES {
+            self.recent_speeds.pop_front();
+        }
+        self.recent_speeds.push_back((speed, overspeeding));
+    }
+
+    pub fn with_overtaking(mut self, allow_overtaking: bool) -> Self {
+        self.allow_overtaking = allow_overtaking;
+        self
+    }
+
+    pub fn with_min_gap(mut self, min_gap: f64) -> Self {
+        self.min_gap = min_gap;
+        self
+    }
+
+    // Space a vehicle occupies on this lane: its own length plus the following gap.
+    pub fn space_needed(&self, vehicle: &Vehicle) -> f64 {
+        vehicle.length + self.min_gap
+    }
+
+    // Usable length of the lane after applying the current capacity factor.
+    pub fn effective_length(&self) -> f64 {
+        self.length_meters * self.capacity_factor
+    }
+
+    // Fraction of the lane taken up by vehicles and their following gaps.
+    pub fn occupancy(&self) -> f64 {
+        self.current_vehicle_length / self.length_meters
+    }
+
+    // Number of reference-length vehicles that fit on the lane with their following gaps.
+    pub fn vehicle_capacity(&self) -> usize {
+        ((self.length_meters / (REFERENCE_VEHICLE_LENGTH_METERS + self.min_gap)).floor() as usize)
+            .max(1)
+    }
+
+    // Vehicles on the lane relative to vehicle_capacity, so a bus counts the same as a car.
+    pub fn occupancy_by_count(&self) -> f64 {
+        self.vehicle_queue.len() as f64 / self.vehicle_capacity() as f64
+    }
+
+    pub fn occupancy_for(&self, metric: OccupancyMetric) -> f64 {
+        match metric {
+            OccupancyMetric::Length => self.occupancy(),
+            OccupancyMetric::Count => self.occupancy_by_count(),
         }
     }
 
     // Check if there is space for a new vehicle.
-    // Note: If an emergency vehicle is already present the lane is blocked.
+    // Note: A lane an emergency vehicle has reserved is blocked. While one is on the lane only
+    // emergency_capacity_factor of the usable length can be filled.
     pub fn can_add_vehicle(&self, vehicle: &Vehicle) -> bool {
-        if self.has_emergency_vehicle {
+        if self.emergency_reservations > 0 {
             return false;
         }
-        self.current_vehicle_length + vehicle.length <= self.length_meters
+        let available = if self.has_emergency_vehicle {
+            self.effective_length() * self.emergency_capacity_factor
+        } else {
+            self.effective_length()
+        };
+        self.current_vehicle_length + self.space_needed(vehicle) <= available
+    }
+
+    pub fn contains_vehicle(&self, vehicle_id: u64) -> bool {
+        self.vehicle_queue.iter().any(|v| v.id == vehicle_id)
     }
 
     // Attempt to add a vehicle onto this lane.
-    // The vehicle is pushed to the back of the FIFO queue.
+    // The vehicle is pushed to the back of the FIFO queue, except emergency vehicles, which weave
+    // ahead of every non-emergency vehicle (staying FIFO among themselves). A vehicle already in
+    // the queue is left where it is, so its length is only counted once.
     pub fn add_vehicle(&mut self, vehicle: &Vehicle) -> bool {
+        if self.contains_vehicle(vehicle.id) {
+            return true;
+        }
         if vehicle.is_emergency() {
             self.has_emergency_vehicle = true;
-            self.vehicle_queue.push_back(vehicle.clone());
-            true
+            let pos = self
+                .vehicle_queue
+                .iter()
+                .position(|v| !v.is_emergency())
+                .unwrap_or(self.vehicle_queue.len());
+            self.vehicle_queue.insert(pos, vehicle.clone());
         } else if self.can_add_vehicle(vehicle) {
-            self.current_vehicle_length += vehicle.length;
+            self.current_vehicle_length += self.space_needed(vehicle);
             self.vehicle_queue.push_back(vehicle.clone());
-            true
         } else {
-            false
+            return false;
+        }
+        self.max_queue_len = self.max_queue_len.max(self.vehicle_queue.len());
+        true
+    }
+
+    // Returns the id of the vehicle directly ahead of `vehicle` in the queue if it is slower,
+    // i.e. the gap between them is closing. Crashed vehicles ahead are ignored.
+    pub fn closing_vehicle_ahead(&self, vehicle: &Vehicle) -> Option<u64> {
+        let pos = self.vehicle_queue.iter().position(|v| v.id == vehicle.id)?;
+        let ahead = self.vehicle_queue.get(pos.checked_sub(1)?)?;
+        if !ahead.is_accident && ahead.speed < vehicle.speed {
+            Some(ahead.id)
+        } else {
+            None
         }
     }
 
+    // Lets `vehicle_id` pass the slower vehicle directly ahead of it by swapping their places in
+    // the queue. Only allowed when the lane permits overtaking, is below OVERTAKING_MAX_OCCUPANCY
+    // and carries no emergency vehicle. Returns the id of the vehicle that was passed.
+    pub fn try_overtake(&mut self, vehicle_id: u64) -> Option<u64> {
+        if !self.allow_overtaking || self.has_emergency_vehicle || self.has_accident {
+            return None;
+        }
+        if self.current_vehicle_length / self.effective_length() >= OVERTAKING_MAX_OCCUPANCY {
+            return None;
+        }
+        let pos = self.vehicle_queue.iter().position(|v| v.id == vehicle_id)?;
+        let ahead_pos = pos.checked_sub(1)?;
+        let (ahead, vehicle) = (&self.vehicle_queue[ahead_pos], &self.vehicle_queue[pos]);
+        if ahead.is_accident || ahead.speed >= vehicle.speed {
+            return None;
+        }
+        let passed = ahead.id;
+        self.vehicle_queue.swap(ahead_pos, pos);
+        Some(passed)
+    }
+
+    // Marks a single vehicle as crashed in the lane queue and blocks the lane.
+    pub fn record_crash(&mut self, vehicle_id: u64, timestamp: u64, severity: i8) {
+        if let Some(v) = self.vehicle_queue.iter_mut().find(|v| v.id == vehicle_id) {
+            v.is_accident = true;
+            v.accident_timestamp = Some(timestamp);
+            v.severity = severity;
+        }
+        self.block_for_accident(severity);
+    }
+
+    // Blocks the lane for a crash of `severity`. A worse crash already blocking it keeps its
+    // severity.
+    pub fn block_for_accident(&mut self, severity: i8) {
+        self.has_accident = true;
+        self.accident_severity = self.accident_severity.max(severity);
+    }
+
+    // Unblocks the lane once its last crashed vehicle is gone; until then it stays blocked for
+    // the worst crash that remains.
+    pub fn unblock_cleared_accidents(&mut self) {
+        match self
+            .vehicle_queue
+            .iter()
+            .filter(|v| v.is_accident)
+            .map(|v| v.severity)
+            .max()
+        {
+            Some(severity) => self.accident_severity = severity,
+            None => {
+                self.has_accident = false;
+                self.accident_severity = 0;
+            }
+        }
+    }
+
+    // Marks both vehicles of a collision as crashed in the lane queue and blocks the lane.
+    // The vehicles' own tasks pick up the crash from the queue entries.
+    pub fn record_collision(
+        &mut self,
+        vehicle_id: u64,
+        other_id: u64,
+        timestamp: u64,
+        severity: i8,
+    ) {
+        for v in self.vehicle_queue.iter_mut() {
+            if v.id == vehicle_id || v.id == other_id {
+                v.is_accident = true;
+                v.accident_timestamp = Some(timestamp);
+                v.severity = severity;
+                v.collided_with = Some(if v.id == vehicle_id {
+                    other_id
+                } else {
+                    vehicle_id
+                });
+            }
+        }
+        self.block_for_accident(severity);
+    }
+
+    // Keeps non-emergency vehicles off the lane until the reservation is released, so an
+    // emergency vehicle heading for it finds it clear.
+    pub fn reserve_for_emergency(&mut self) {
+        self.emergency_reservations += 1;
+    }
+
+    pub fn release_emergency_reservation(&mut self) {
+        self.emergency_reservations = self.emergency_reservations.saturating_sub(1);
+    }
+
     // Remove a vehicle from this lane.
     // In FIFO operation the vehicle at the front is normally removed.
     pub fn remove_vehicle(&mut self, vehicle: &Vehicle) {
         if let Some(pos) = self.vehicle_queue.iter().position(|v| v.id == vehicle.id) {
             self.vehicle_queue.remove(pos);
             if !vehicle.is_emergency() {
-                if self.current_vehicle_length >= vehicle.length {
-                    self.current_vehicle_length -= vehicle.length;
-                }
+                let space = self.space_needed(vehicle);
+                self.current_vehicle_length = (self.current_vehicle_length - space).max(0.0);
             } else {
-                self.has_emergency_vehicle = false;
+                self.has_emergency_vehicle = self.vehicle_queue.iter().any(|v| v.is_emergency());
             }
         }
     }
 }
 
+// The shared lane collection. Every lane sits behind its own lock, so vehicles on different
+// lanes don't contend, and a name -> position index finds a lane in O(1). Never hold two lane
+// locks at once. Lanes are only added or removed through push/remove (before the store is shared)
+// so the index stays in sync.
+#[derive(Debug, Default)]
+pub struct LaneStore {
+    lanes: Vec<Mutex<Lane>>,
+    index: HashMap<String, usize>,
+}
+
+impl LaneStore {
+    pub fn new(lanes: Vec<Lane>) -> Self {
+        let mut store = Self {
+            lanes: lanes.into_iter().map(Mutex::new).collect(),
+            index: HashMap::new(),
+        };
+        store.reindex();
+        store
+    }
+
+    fn reindex(&mut self) {
+        self.index = self
+            .lanes
+            .iter_mut()
+            .enumerate()
+            .map(|(i, lane)| (lane.get_mut().unwrap().name.clone(), i))
+            .collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lanes.is_empty()
+    }
+
+    // Locks a single lane by name.
+    pub fn lock_lane(&self, name: &str) -> Option<MutexGuard<'_, Lane>> {
+        self.index.get(name).map(|&i| self.lanes[i].lock().unwrap())
+    }
+
+    // Copies every lane, locking one lane at a time. Lanes may be from slightly different
+    // instants, which is fine for statistics and routing.
+    pub fn snapshot(&self) -> Vec<Lane> {
+        self.lanes
+            .iter()
+            .map(|l| l.lock().unwrap().clone())
+            .collect()
+    }
+
+    // Locks every lane, in store order, for a consistent read of the whole network. Vehicle tasks
+    // only ever hold one lane lock, so this can't deadlock with them; drop the guards promptly.
+    pub fn lock_all(&self) -> Vec<MutexGuard<'_, Lane>> {
+        self.lanes.iter().map(|l| l.lock().unwrap()).collect()
+    }
+
+    // Runs `f` on every lane in turn, each under its own lock.
+    pub fn for_each_mut<F: FnMut(&mut Lane)>(&self, mut f: F) {
+        for lane in &self.lanes {
+            f(&mut lane.lock().unwrap());
+        }
+    }
+
+    // Overwrites the lanes with the same names as `lanes`; unknown names are ignored.
+    pub fn replace_lanes(&self, lanes: Vec<Lane>) {
+        for lane in lanes {
+            if let Some(mut guard) = self.lock_lane(&lane.name) {
+                *guard = lane;
+            }
+        }
+    }
+
+    // Adds a lane, replacing any existing lane with the same name.
+    pub fn push(&mut self, lane: Lane) {
+        match self.index.get(&lane.name) {
+            Some(&i) => *self.lanes[i].get_mut().unwrap() = lane,
+            None => {
+                self.index.insert(lane.name.clone(), self.lanes.len());
+                self.lanes.push(Mutex::new(lane));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Lane> {
+        let i = self.index.remove(name)?;
+        let lane = self.lanes.remove(i).into_inner().unwrap();
+        self.reindex();
+        Some(lane)
+    }
+
+    pub fn into_vec(self) -> Vec<Lane> {
+        self.lanes
+            .into_iter()
+            .map(|l| l.into_inner().unwrap())
+            .collect()
+    }
+}
+
+impl From<Vec<Lane>> for LaneStore {
+    fn from(lanes: Vec<Lane>) -> Self {
+        Self::new(lanes)
+    }
+}
+
+// A scheduled, temporary reduction (or increase) of lane capacity, e.g. a rainstorm or an incident.
+// Active while start_secs <= elapsed < start_secs + duration_secs (elapsed since simulation start).
+#[derive(Debug, Clone)]
+pub struct CapacityModifier {
+    pub start_secs: u64,
+    pub duration_secs: u64,
+    pub factor: f64,
+    // Names of the affected lanes. An empty list applies the modifier to every lane.
+    pub lanes: Vec<String>,
+}
+
+impl CapacityModifier {
+    pub fn new(start_secs: u64, duration_secs: u64, factor: f64, lanes: Vec<String>) -> Self {
+        Self {
+            start_secs,
+            duration_secs,
+            factor,
+            lanes,
+        }
+    }
+
+    pub fn is_active(&self, elapsed_secs: u64) -> bool {
+        elapsed_secs >= self.start_secs && elapsed_secs < self.start_secs + self.duration_secs
+    }
+
+    pub fn applies_to(&self, lane_name: &str) -> bool {
+        self.lanes.is_empty() || self.lanes.iter().any(|l| l == lane_name)
+    }
+}
+
+// Recomputes every lane's capacity_factor from the modifiers active at `elapsed_secs`.
+// Lanes with no active modifier are restored to full capacity.
+pub fn apply_capacity_modifiers(
+    lanes: &mut [Lane],
+    modifiers: &[CapacityModifier],
+    elapsed_secs: u64,
+) {
+    for lane in lanes.iter_mut() {
+        let factor: f64 = modifiers
+            .iter()
+            .filter(|m| m.is_active(elapsed_secs) && m.applies_to(&lane.name))
+            .map(|m| m.factor)
+            .product();
+        if factor != lane.capacity_factor {
+            println!(
+                "Lane {} capacity factor changed from {:.2} to {:.2}",
+                lane.name, lane.capacity_factor, factor
+            );
+            lane.capacity_factor = factor;
+        }
+    }
+}
+
+// Straight-line distance between two intersections' grid positions, scaled to meters. Symmetric
+// in `from` and `to`.
+pub fn geometric_length(from: IntersectionId, to: IntersectionId, meters_per_cell: f64) -> f64 {
+    let d_row = to.0 as f64 - from.0 as f64;
+    let d_col = to.1 as f64 - from.1 as f64;
+    d_row.hypot(d_col) * meters_per_cell
+}
+
+// Replaces every lane's length with its geometric length, so a lane and its reverse are always
+// the same length.
+pub fn apply_geometric_lengths(lanes: &mut [Lane], meters_per_cell: f64) {
+    for lane in lanes {
+        lane.length_meters = geometric_length(lane.from, lane.to, meters_per_cell);
+    }
+}
+
 pub fn create_lanes() -> Vec<Lane> {
-    vec![
+    let lanes: Result<Vec<Lane>, LaneError> = vec![
         Lane::new(
             "(0,0) -> (0,1)".to_string(),
             IntersectionId(0, 0),
@@ -368,5 +892,22 @@ pub fn create_lanes() -> Vec<Lane> {
             IntersectionId(2, 3),
             400.0,
         ),
+        // Diagonal shortcut road between (1,1) and (2,2).
+        Lane::new(
+            "(1,1) -> (2,2)".to_string(),
+            IntersectionId(1, 1),
+            IntersectionId(2, 2),
+            500.0,
+        ),
+        Lane::new(
+            "(2,2) -> (1,1)".to_string(),
+            IntersectionId(2, 2),
+            IntersectionId(1, 1),
+            500.0,
+        ),
     ]
+    .into_iter()
+    .map(|lane| lane.map(|lane| lane.with_speed_limit(DEFAULT_LANE_SPEED_LIMIT_KMH)))
+    .collect();
+    lanes.expect("built-in lane definitions are valid")
 }
@@ -1,5 +1,11 @@
+pub mod config;
+pub mod event_log;
+pub mod grid;
 pub mod intersections;
 pub mod lanes;
 pub mod route_generation;
 pub mod simulation;
+pub mod snapshot;
+pub mod stepwise;
 pub mod vehicles;
+pub mod weather;
@@ -1,7 +1,7 @@
-use crate::c1_tp063879::intersections::IntersectionId;
+use crate::c1_tp063879::intersections::{Intersection, IntersectionId};
 use crate::c1_tp063879::lanes::Lane;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 #[derive(Debug)]
 struct State {
@@ -12,11 +12,15 @@ struct State {
 // Reverse ordering to use BinaryHeap as a min-heap.
 impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Lower cost gets higher priority.
+        // Lower cost gets higher priority; equal costs go to the lower (row, col).
         other
             .cost
             .partial_cmp(&self.cost)
             .unwrap_or(Ordering::Equal)
+            .then_with(|| {
+                (other.intersection.0, other.intersection.1)
+                    .cmp(&(self.intersection.0, self.intersection.1))
+            })
     }
 }
 
@@ -28,22 +32,43 @@ impl PartialOrd for State {
 
 impl PartialEq for State {
     fn eq(&self, other: &Self) -> bool {
-        self.cost == other.cost
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl Eq for State {}
 
 // Use Dijkstra's algorithm to find the shortest route of Lanes from `entry` to `exit`.
-// Returns None if no path exists.
+// Intersections in `entry_only` are one-way out: the route may start at one but never drives
+// into one. Returns None if no path exists.
+// Among equal-length routes the one whose lanes have the lexicographically smallest names (at
+// each step back from the exit) wins, so the result doesn't depend on the order of `lanes`.
 pub fn generate_shortest_lane_route(
     lanes: &[Lane],
     entry: IntersectionId,
     exit: IntersectionId,
+    entry_only: &HashSet<IntersectionId>,
+) -> Option<Vec<Lane>> {
+    generate_lane_route_by(lanes, entry, exit, entry_only, |lane| lane.length_meters)
+}
+
+// Same as generate_shortest_lane_route, but minimises the sum of `cost_fn` over the route's lanes
+// instead of their length (e.g. travel time or congestion). Negative costs count as 0, and a lane
+// with an infinite or NaN cost is never used.
+pub fn generate_lane_route_by(
+    lanes: &[Lane],
+    entry: IntersectionId,
+    exit: IntersectionId,
+    entry_only: &HashSet<IntersectionId>,
+    cost_fn: impl Fn(&Lane) -> f64,
 ) -> Option<Vec<Lane>> {
     // Build an adjacency list: each intersection -> all lanes going *out* from it.
     let mut graph: HashMap<IntersectionId, Vec<&Lane>> = HashMap::new();
     for lane in lanes {
+        // Lanes leading into an entry-only intersection can't be driven.
+        if entry_only.contains(&lane.to) {
+            continue;
+        }
         // Build adjacency from lane.from
         graph.entry(lane.from).or_default().push(lane);
 
@@ -79,16 +104,24 @@ pub fn generate_shortest_lane_route(
         // Explore outgoing lanes from this intersection
         if let Some(neighbors) = graph.get(&intersection) {
             for &lane in neighbors {
+                let lane_cost = cost_fn(lane);
+                if !lane_cost.is_finite() {
+                    continue;
+                }
                 let next = lane.to;
-                let next_cost = cost + lane.length_meters;
+                let next_cost = cost + lane_cost.max(0.0);
+                let best = *dist.get(&next).unwrap_or(&f64::INFINITY);
 
-                if next_cost < *dist.get(&next).unwrap_or(&f64::INFINITY) {
+                if next_cost < best {
                     dist.insert(next, next_cost);
                     prev.insert(next, lane);
                     heap.push(State {
                         cost: next_cost,
                         intersection: next,
                     });
+                } else if next_cost == best && prev.get(&next).is_some_and(|p| lane.name < p.name) {
+                    // Equally short: keep the lane with the smaller name.
+                    prev.insert(next, lane);
                 }
             }
         }
@@ -113,3 +146,326 @@ pub fn generate_shortest_lane_route(
     route.reverse();
     Some(route)
 }
+
+// Banned movements across the network, as (from, via, to): a vehicle that arrived at `via` from
+// `from` may not continue to `to`.
+pub type TurnRestrictions = HashSet<(IntersectionId, IntersectionId, IntersectionId)>;
+
+// Every intersection's forbidden turns, gathered for route generation.
+pub fn turn_restrictions(intersections: &[Intersection]) -> TurnRestrictions {
+    intersections
+        .iter()
+        .flat_map(|i| {
+            i.forbidden_turns
+                .iter()
+                .map(move |&(from, to)| (from, i.id, to))
+        })
+        .collect()
+}
+
+// Same as generate_lane_route_by, but never makes a movement listed in `restrictions`. The search
+// runs over lanes rather than intersections, since whether a lane may be taken depends on the
+// lane the vehicle arrived by. Without restrictions this is exactly generate_lane_route_by.
+pub fn generate_restricted_lane_route_by(
+    lanes: &[Lane],
+    entry: IntersectionId,
+    exit: IntersectionId,
+    entry_only: &HashSet<IntersectionId>,
+    restrictions: &TurnRestrictions,
+    cost_fn: impl Fn(&Lane) -> f64,
+) -> Option<Vec<Lane>> {
+    if restrictions.is_empty() {
+        return generate_lane_route_by(lanes, entry, exit, entry_only, cost_fn);
+    }
+    let usable: Vec<&Lane> = lanes
+        .iter()
+        .filter(|lane| !entry_only.contains(&lane.to))
+        .collect();
+    let mut graph: HashMap<IntersectionId, Vec<usize>> = HashMap::new();
+    for (index, lane) in usable.iter().enumerate() {
+        graph.entry(lane.from).or_default().push(index);
+    }
+
+    let mut dist: HashMap<Option<usize>, f64> = HashMap::new();
+    let mut prev: HashMap<usize, Option<usize>> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    dist.insert(None, 0.0);
+    heap.push(StepState {
+        cost: 0.0,
+        at: entry,
+        via: None,
+    });
+    let mut reached = None;
+    while let Some(StepState { cost, at, via }) = heap.pop() {
+        if at == exit {
+            reached = Some(via);
+            break;
+        }
+        if cost > *dist.get(&via).unwrap_or(&f64::INFINITY) {
+            continue;
+        }
+        let arrived_from = via.map(|i| usable[i].from);
+        for &next in graph.get(&at).map(Vec::as_slice).unwrap_or(&[]) {
+            let lane = usable[next];
+            if arrived_from.is_some_and(|from| restrictions.contains(&(from, at, lane.to))) {
+                continue;
+            }
+            let lane_cost = cost_fn(lane);
+            if !lane_cost.is_finite() {
+                continue;
+            }
+            let next_cost = cost + lane_cost.max(0.0);
+            if next_cost < *dist.get(&Some(next)).unwrap_or(&f64::INFINITY) {
+                dist.insert(Some(next), next_cost);
+                prev.insert(next, via);
+                heap.push(StepState {
+                    cost: next_cost,
+                    at: lane.to,
+                    via: Some(next),
+                });
+            }
+        }
+    }
+    let Some(mut current) = reached else {
+        println!(
+            "No route found from {:?} to {:?} without a forbidden turn",
+            entry, exit
+        );
+        return None;
+    };
+
+    // Walk the arrival lanes back to the entry.
+    let mut route: Vec<Lane> = Vec::new();
+    while let Some(index) = current {
+        route.push(usable[index].clone());
+        current = *prev.get(&index)?;
+    }
+    route.reverse();
+    Some(route)
+}
+
+// Speed assumed when estimating travel time on a lane without a speed limit (km/h).
+pub const DEFAULT_ROUTE_SPEED_KMH: f64 = 50.0;
+
+// Weights for generate_route_with_objective. The three terms are normalised so their weights are
+// comparable: a lane's distance is measured in multiples of the mean lane length of the network,
+// its travel time (length / speed limit) in multiples of the mean lane travel time, and every
+// change of heading between consecutive lanes costs 1.0 (2.0 for a U-turn). With all three
+// weights at 1.0 a turn is worth as much as driving one average lane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteObjective {
+    pub distance_weight: f64,
+    pub time_weight: f64,
+    pub turn_weight: f64,
+}
+
+impl Default for RouteObjective {
+    // Shortest distance, same as generate_shortest_lane_route.
+    fn default() -> Self {
+        Self {
+            distance_weight: 1.0,
+            time_weight: 0.0,
+            turn_weight: 0.0,
+        }
+    }
+}
+
+fn lane_travel_secs(lane: &Lane) -> f64 {
+    let speed_kmh = lane.speed_limit.unwrap_or(DEFAULT_ROUTE_SPEED_KMH).max(1.0);
+    lane.length_meters / (speed_kmh / 3.6)
+}
+
+// Unit step (row, col) a lane heads in.
+fn heading(lane: &Lane) -> (i16, i16) {
+    (
+        (lane.to.0 as i16 - lane.from.0 as i16).signum(),
+        (lane.to.1 as i16 - lane.from.1 as i16).signum(),
+    )
+}
+
+fn turn_cost(arrived: Option<&Lane>, next: &Lane) -> f64 {
+    let Some(arrived) = arrived else {
+        return 0.0;
+    };
+    let (from, to) = (heading(arrived), heading(next));
+    if from == to {
+        0.0
+    } else if from == (-to.0, -to.1) {
+        2.0
+    } else {
+        1.0
+    }
+}
+
+// Search state for generate_route_with_objective: the intersection reached and the index of the
+// lane it was reached by (None at the entry), since the turn cost depends on that lane.
+#[derive(Debug)]
+struct StepState {
+    cost: f64,
+    at: IntersectionId,
+    via: Option<usize>,
+}
+
+impl Ord for StepState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Lower cost gets higher priority; equal costs go to the earlier lane.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.via.cmp(&self.via))
+    }
+}
+
+impl PartialOrd for StepState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for StepState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for StepState {}
+
+// Cheapest route from `entry` to `exit` under a weighted mix of distance, travel time and turns
+// (see RouteObjective). Intersections in `entry_only` are never driven into. Returns None if no
+// path exists.
+pub fn generate_route_with_objective(
+    lanes: &[Lane],
+    entry: IntersectionId,
+    exit: IntersectionId,
+    entry_only: &HashSet<IntersectionId>,
+    objective: &RouteObjective,
+) -> Option<Vec<Lane>> {
+    let usable: Vec<&Lane> = lanes
+        .iter()
+        .filter(|lane| !entry_only.contains(&lane.to))
+        .collect();
+    if usable.is_empty() {
+        return (entry == exit).then(Vec::new);
+    }
+    let mean_length = usable.iter().map(|l| l.length_meters).sum::<f64>() / usable.len() as f64;
+    let mean_secs = usable.iter().map(|l| lane_travel_secs(l)).sum::<f64>() / usable.len() as f64;
+    let step_cost = |arrived: Option<&Lane>, lane: &Lane| {
+        let distance = if mean_length > 0.0 {
+            lane.length_meters / mean_length
+        } else {
+            0.0
+        };
+        let time = if mean_secs > 0.0 {
+            lane_travel_secs(lane) / mean_secs
+        } else {
+            0.0
+        };
+        (objective.distance_weight * distance
+            + objective.time_weight * time
+            + objective.turn_weight * turn_cost(arrived, lane))
+        .max(0.0)
+    };
+
+    let mut graph: HashMap<IntersectionId, Vec<usize>> = HashMap::new();
+    for (index, lane) in usable.iter().enumerate() {
+        graph.entry(lane.from).or_default().push(index);
+    }
+
+    type Key = (IntersectionId, Option<usize>);
+    let mut dist: HashMap<Key, f64> = HashMap::new();
+    let mut prev: HashMap<Key, Key> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    dist.insert((entry, None), 0.0);
+    heap.push(StepState {
+        cost: 0.0,
+        at: entry,
+        via: None,
+    });
+    let mut reached = None;
+    while let Some(StepState { cost, at, via }) = heap.pop() {
+        if at == exit {
+            reached = Some((at, via));
+            break;
+        }
+        if cost > *dist.get(&(at, via)).unwrap_or(&f64::INFINITY) {
+            continue;
+        }
+        let arrived = via.map(|i| usable[i]);
+        for &next in graph.get(&at).map(Vec::as_slice).unwrap_or(&[]) {
+            let lane = usable[next];
+            let key = (lane.to, Some(next));
+            let next_cost = cost + step_cost(arrived, lane);
+            if next_cost < *dist.get(&key).unwrap_or(&f64::INFINITY) {
+                dist.insert(key, next_cost);
+                prev.insert(key, (at, via));
+                heap.push(StepState {
+                    cost: next_cost,
+                    at: lane.to,
+                    via: Some(next),
+                });
+            }
+        }
+    }
+    let Some(mut current) = reached else {
+        println!("No route found from {:?} to {:?}", entry, exit);
+        return None;
+    };
+
+    // Walk the arrival lanes back to the entry.
+    let mut route: Vec<Lane> = Vec::new();
+    while let (_, Some(index)) = current {
+        route.push(usable[index].clone());
+        current = *prev.get(&current)?;
+    }
+    route.reverse();
+    Some(route)
+}
+
+// Intersections no vehicle can use: not reachable from any entry, or with no exit reachable
+// from them. Sorted by (row, col). An empty result means every intersection is routable.
+pub fn validate_connectivity(
+    intersections: &[Intersection],
+    lanes: &[Lane],
+) -> Vec<IntersectionId> {
+    let mut forward: HashMap<IntersectionId, Vec<IntersectionId>> = HashMap::new();
+    let mut backward: HashMap<IntersectionId, Vec<IntersectionId>> = HashMap::new();
+    for lane in lanes {
+        forward.entry(lane.from).or_default().push(lane.to);
+        backward.entry(lane.to).or_default().push(lane.from);
+    }
+    let entries = intersections.iter().filter(|i| i.is_entry).map(|i| i.id);
+    let exits = intersections.iter().filter(|i| i.is_exit).map(|i| i.id);
+    let from_entries = reachable(entries, &forward);
+    let to_exits = reachable(exits, &backward);
+
+    let mut unreachable: Vec<IntersectionId> = intersections
+        .iter()
+        .map(|i| i.id)
+        .filter(|id| !from_entries.contains(id) || !to_exits.contains(id))
+        .collect();
+    unreachable.sort_by_key(|id| (id.0, id.1));
+    unreachable
+}
+
+// Breadth-first search from every start node at once.
+fn reachable(
+    starts: impl Iterator<Item = IntersectionId>,
+    graph: &HashMap<IntersectionId, Vec<IntersectionId>>,
+) -> HashSet<IntersectionId> {
+    let mut seen: HashSet<IntersectionId> = HashSet::new();
+    let mut queue: VecDeque<IntersectionId> = VecDeque::new();
+    for start in starts {
+        if seen.insert(start) {
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        for &next in graph.get(&node).into_iter().flatten() {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
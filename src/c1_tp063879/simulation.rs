@@ -1,207 +1,937 @@
 // simulation.rs
-use crate::c1_tp063879::intersections::{Intersection, IntersectionControl};
-use crate::c1_tp063879::lanes::Lane;
-use crate::c1_tp063879::route_generation::generate_shortest_lane_route;
-use crate::c1_tp063879::vehicles::{Vehicle, VehicleType};
-use crate::c3_tp063987::traffic_light_controller::TrafficLightController;
-use crate::global_variables::{AMQP_URL, QUEUE_TRAFFIC_DATA};
-use crate::shared_data::current_timestamp;
-use crate::shared_data::{TrafficData, TrafficUpdate, VehicleData};
-
-use amiquip::{Connection, Exchange, Publish, QueueDeclareOptions};
+use crate::amqp::{connect_with_retry, CONNECT_BASE_DELAY, CONNECT_MAX_ATTEMPTS};
+use crate::bus::{AmiquipBus, MessageBus, PublishRecovery, Subscription};
+use crate::c1_tp063879::config::{
+    DemandProfiles, RampMeterConfig, SimulationConfig, SpawnMeteringConfig, SpeedRanges,
+};
+use crate::c1_tp063879::event_log::{EventLog, SimulationEvent};
+use crate::c1_tp063879::intersections::{
+    entry_only_intersections, Intersection, IntersectionControl, IntersectionId,
+};
+use crate::c1_tp063879::lanes::{
+    apply_capacity_modifiers, CapacityModifier, Lane, LaneStore, OccupancyMetric,
+};
+use crate::c1_tp063879::route_generation::{generate_restricted_lane_route_by, turn_restrictions};
+use crate::c1_tp063879::snapshot::{
+    ActiveJourneys, ActiveVehicle, SimulationState, VehiclePositions, VehicleProgress,
+};
+use crate::c1_tp063879::vehicles::{random_vehicle_type, Vehicle, VehicleType};
+use crate::c1_tp063879::weather::{weather_at, SharedWeather};
+use crate::c3_tp063987::traffic_light_controller::{LightState, TrafficLightController};
+use crate::global_variables::{
+    amqp_url, queue_name, QUEUE_SIGNAL_ADJUSTMENTS, QUEUE_SIMULATION_CONTROL,
+    QUEUE_SIMULATION_EVENTS, QUEUE_TRAFFIC_DATA,
+};
+use crate::shared_data::{
+    current_timestamp, pausable_sleep, scale_duration, scaled_elapsed_since, SimulationCommand,
+    SimulationControl,
+};
+use crate::shared_data::{
+    LaneSpeedStats, LightAdjustment, SteadyStateReached, TrafficData, TrafficUpdate, VehicleData,
+};
+
+use amiquip::{ConsumerMessage, ConsumerOptions, QueueDeclareOptions, Result as AmiquipResult};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
 use serde_json;
-use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::time::{sleep, Duration};
 
 // Collect current traffic data from lanes and intersections, including vehicle data.
+// The per-intersection congestion and waiting times are computed in parallel.
 pub fn collect_traffic_data(
     lanes: &[Lane],
     intersections: &[Intersection],
     vehicle_data: Vec<VehicleData>,
 ) -> TrafficData {
-    let mut lane_occupancy = HashMap::new();
-    for lane in lanes {
-        let occupancy = lane.current_vehicle_length / lane.length_meters;
-        lane_occupancy.insert(lane.name.clone(), occupancy);
-    }
+    collect_traffic_data_by(lanes, intersections, vehicle_data, OccupancyMetric::Length)
+}
 
-    let mut accident_lanes = HashSet::new();
-    for lane in lanes {
-        if lane.has_accident {
-            accident_lanes.insert(lane.name.clone());
-        }
-    }
+// Same as collect_traffic_data with occupancy measured by `metric`.
+pub fn collect_traffic_data_by(
+    lanes: &[Lane],
+    intersections: &[Intersection],
+    vehicle_data: Vec<VehicleData>,
+    metric: OccupancyMetric,
+) -> TrafficData {
+    let lanes: Vec<&Lane> = lanes.iter().collect();
+    collect_from_lane_refs(&lanes, intersections, vehicle_data, metric)
+}
 
-    let mut intersection_congestion = HashMap::new();
-    for intersection in intersections {
-        let outgoing: Vec<_> = lanes.iter().filter(|l| l.from == intersection.id).collect();
-        if outgoing.is_empty() {
-            intersection_congestion.insert(format!("{:?}", intersection.id), 0.0);
-        } else {
-            let sum_occ: f64 = outgoing
-                .iter()
-                .map(|l| l.current_vehicle_length / l.length_meters)
-                .sum();
-            let avg = sum_occ / outgoing.len() as f64;
-            intersection_congestion.insert(format!("{:?}", intersection.id), avg);
-        }
+// Same as collect_traffic_data_by, but reads the shared lanes in place under their locks instead
+// of from a cloned snapshot, so no vehicle queue is copied.
+pub fn collect_traffic_data_from_store(
+    lanes: &LaneStore,
+    intersections: &[Intersection],
+    vehicle_data: Vec<VehicleData>,
+    metric: OccupancyMetric,
+) -> TrafficData {
+    let guards = lanes.lock_all();
+    let lanes: Vec<&Lane> = guards.iter().map(|guard| &**guard).collect();
+    collect_from_lane_refs(&lanes, intersections, vehicle_data, metric)
+}
+
+fn collect_from_lane_refs(
+    lanes: &[&Lane],
+    intersections: &[Intersection],
+    vehicle_data: Vec<VehicleData>,
+    metric: OccupancyMetric,
+) -> TrafficData {
+    let (intersection_congestion, intersection_waiting_time, intersection_max_direction_congestion) =
+        intersections
+            .par_iter()
+            .map(|intersection| intersection_stats(intersection, lanes, metric))
+            .fold(
+                || (HashMap::new(), HashMap::new(), HashMap::new()),
+                |(mut congestion, mut waiting, mut directional), stats| {
+                    congestion.insert(stats.key.clone(), stats.avg_occupancy);
+                    directional.insert(stats.key.clone(), stats.max_direction_occupancy);
+                    waiting.insert(stats.key, stats.avg_waiting);
+                    (congestion, waiting, directional)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), HashMap::new(), HashMap::new()),
+                |(mut congestion, mut waiting, mut directional), (c, w, d)| {
+                    congestion.extend(c);
+                    waiting.extend(w);
+                    directional.extend(d);
+                    (congestion, waiting, directional)
+                },
+            );
+
+    TrafficData {
+        lane_occupancy: lane_occupancy(lanes, metric),
+        accident_lanes: accident_lanes(lanes),
+        intersection_congestion,
+        intersection_waiting_time,
+        vehicle_data,
+        intersection_max_direction_congestion,
+        lane_waiting_time: lane_waiting_time(lanes),
+        lane_speeds: lane_speeds(lanes),
     }
+}
 
+// Single-threaded equivalent of collect_traffic_data, kept for comparison in the benchmarks.
+pub fn collect_traffic_data_serial(
+    lanes: &[Lane],
+    intersections: &[Intersection],
+    vehicle_data: Vec<VehicleData>,
+) -> TrafficData {
+    let lanes: Vec<&Lane> = lanes.iter().collect();
+    let lanes = lanes.as_slice();
+    let mut intersection_congestion = HashMap::new();
     let mut intersection_waiting_time = HashMap::new();
+    let mut intersection_max_direction_congestion = HashMap::new();
     for intersection in intersections {
-        let outgoing: Vec<_> = lanes.iter().filter(|l| l.from == intersection.id).collect();
-        if outgoing.is_empty() {
-            intersection_waiting_time.insert(format!("{:?}", intersection.id), 0.0);
-        } else {
-            let total_waiting: f64 = outgoing.iter().map(|l| l.waiting_time).sum();
-            let avg_waiting = total_waiting / outgoing.len() as f64;
-            intersection_waiting_time.insert(format!("{:?}", intersection.id), avg_waiting);
-        }
+        let stats = intersection_stats(intersection, lanes, OccupancyMetric::Length);
+        intersection_congestion.insert(stats.key.clone(), stats.avg_occupancy);
+        intersection_max_direction_congestion
+            .insert(stats.key.clone(), stats.max_direction_occupancy);
+        intersection_waiting_time.insert(stats.key, stats.avg_waiting);
     }
 
     TrafficData {
-        lane_occupancy,
-        accident_lanes,
+        lane_occupancy: lane_occupancy(lanes, OccupancyMetric::Length),
+        accident_lanes: accident_lanes(lanes),
         intersection_congestion,
         intersection_waiting_time,
         vehicle_data,
+        intersection_max_direction_congestion,
+        lane_waiting_time: lane_waiting_time(lanes),
+        lane_speeds: lane_speeds(lanes),
     }
 }
 
-// Helper function to simulate rush hour by adjusting the number of vehicles spawned per iteration.
-// The spawn count increases linearly from 2 to 5 during the first 20 seconds of a 40-second cycle,
-// then decreases from 5 to 2 over the next 20 seconds, and repeats.
-fn simulate_rush_hour(simulation_start: u64, current_time: u64) -> usize {
-    let elapsed = current_time - simulation_start; // elapsed time in seconds
-    let cycle_duration = 40; // full cycle length in seconds
-    let cycle_time = elapsed % cycle_duration;
-    let spawn_count = if cycle_time <= 20 {
-        // Increasing phase: 2 to 5 vehicles.
-        let ratio = cycle_time as f64 / 20.0;
-        2.0 + ratio * 3.0 // 2 + (0 to 3) = 2 to 5
-    } else {
-        // Decreasing phase: 5 to 2 vehicles.
-        let ratio = (cycle_time - 20) as f64 / 20.0;
-        5.0 - ratio * 3.0 // 5 - (0 to 3) = 5 to 2
-    };
-    spawn_count.round() as usize
+fn lane_occupancy(lanes: &[&Lane], metric: OccupancyMetric) -> HashMap<String, f64> {
+    lanes
+        .iter()
+        .map(|lane| (lane.name.clone(), lane.occupancy_for(metric)))
+        .collect()
+}
+
+fn accident_lanes(lanes: &[&Lane]) -> HashSet<String> {
+    lanes
+        .iter()
+        .filter(|lane| lane.has_accident)
+        .map(|lane| lane.name.clone())
+        .collect()
+}
+
+fn lane_waiting_time(lanes: &[&Lane]) -> HashMap<String, f64> {
+    lanes
+        .iter()
+        .map(|lane| (lane.name.clone(), lane.avg_waiting_time()))
+        .collect()
+}
+
+fn lane_speeds(lanes: &[&Lane]) -> HashMap<String, LaneSpeedStats> {
+    lanes
+        .iter()
+        .filter(|lane| !lane.recent_speeds.is_empty())
+        .map(|lane| {
+            let mut speeds: Vec<f64> = lane.recent_speeds.iter().map(|(s, _)| *s).collect();
+            speeds.sort_by(|a, b| a.total_cmp(b));
+            let overspeeding = lane.recent_speeds.iter().filter(|(_, o)| *o).count();
+            let stats = LaneSpeedStats {
+                samples: speeds.len(),
+                speed_p85: percentile(&speeds, 85.0),
+                overspeed_ratio: overspeeding as f64 / speeds.len() as f64,
+            };
+            (lane.name.clone(), stats)
+        })
+        .collect()
+}
+
+struct IntersectionStats {
+    key: String,
+    // Average occupancy over all outgoing lanes, and over the outgoing lanes of the busiest
+    // heading. Both are 0.0 for an intersection without outgoing lanes.
+    avg_occupancy: f64,
+    max_direction_occupancy: f64,
+    avg_waiting: f64,
+}
+
+fn intersection_stats(
+    intersection: &Intersection,
+    lanes: &[&Lane],
+    metric: OccupancyMetric,
+) -> IntersectionStats {
+    let outgoing: Vec<_> = lanes.iter().filter(|l| l.from == intersection.id).collect();
+    let mut by_heading: HashMap<(i8, i8), (f64, usize)> = HashMap::new();
+    for lane in &outgoing {
+        let entry = by_heading.entry(lane.heading()).or_insert((0.0, 0));
+        entry.0 += lane.occupancy_for(metric);
+        entry.1 += 1;
+    }
+    let sum_occ: f64 = by_heading.values().map(|(sum, _)| sum).sum();
+    IntersectionStats {
+        key: intersection.id.to_string(),
+        avg_occupancy: if outgoing.is_empty() {
+            0.0
+        } else {
+            sum_occ / outgoing.len() as f64
+        },
+        max_direction_occupancy: by_heading
+            .values()
+            .map(|(sum, count)| sum / *count as f64)
+            .fold(0.0, f64::max),
+        avg_waiting: intersection.avg_waiting_time(),
+    }
+}
+
+// End-of-run KPIs computed from every vehicle event of a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    pub total_vehicles: u64,
+    pub arrivals: u64,
+    pub crashes: u64,
+    pub mean_waiting_time: f64,
+    pub median_waiting_time: f64,
+    pub p95_waiting_time: f64,
+    // Fraction of finished vehicles that crashed.
+    pub accident_rate: f64,
+}
+
+// Aggregates overall statistics for a run. An empty slice yields all zeros.
+pub fn summarize_run(events: &[VehicleData]) -> RunSummary {
+    if events.is_empty() {
+        return RunSummary::default();
+    }
+    let total_vehicles = events.len() as u64;
+    let crashes = events
+        .iter()
+        .filter(|e| e.accident_timestamp.is_some())
+        .count() as u64;
+    let mut waits: Vec<f64> = events.iter().map(|e| e.waiting_time as f64).collect();
+    waits.sort_by(|a, b| a.total_cmp(b));
+
+    RunSummary {
+        total_vehicles,
+        arrivals: total_vehicles - crashes,
+        crashes,
+        mean_waiting_time: waits.iter().sum::<f64>() / waits.len() as f64,
+        median_waiting_time: median(&waits),
+        p95_waiting_time: percentile(&waits, 95.0),
+        accident_rate: crashes as f64 / total_vehicles as f64,
+    }
+}
+
+// Median of an already sorted slice; 0.0 when empty.
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    match n {
+        0 => 0.0,
+        _ if n % 2 == 1 => sorted[n / 2],
+        _ => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+    }
+}
+
+// Nearest-rank percentile of an already sorted slice; 0.0 when empty.
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Network-average intersection congestion (0.0 when there are no intersections).
+pub fn mean_congestion(data: &TrafficData) -> f64 {
+    if data.intersection_congestion.is_empty() {
+        return 0.0;
+    }
+    data.intersection_congestion.values().sum::<f64>() / data.intersection_congestion.len() as f64
+}
+
+// Scales each tick's spawn count down while the grid is congested and back up as it drains.
+#[derive(Debug, Clone)]
+pub struct SpawnMeter {
+    config: SpawnMeteringConfig,
+    // Fraction of the scheduled spawn count currently let through, in [0, 1].
+    scale: f64,
 }
 
-// Helper function to check if a vehicle is overspeeding.
-// For each vehicle type, we consider the vehicle to be overspeeding if its speed is in the upper 10% of its spawn range.
-fn verify_speed_limit(vehicle: &Vehicle) -> bool {
-    match vehicle.vehicle_type {
-        VehicleType::Car => {
-            // Car speed range: 80.0 - 140.0; threshold = 80 + 0.90*(60) = 134.0
-            vehicle.speed >= 134.0
+impl SpawnMeter {
+    pub fn new(config: SpawnMeteringConfig) -> Self {
+        Self { config, scale: 1.0 }
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    // Adjusts the scale from the latest network-average congestion.
+    pub fn observe(&mut self, average_congestion: f64) {
+        if !self.config.enabled {
+            return;
         }
-        VehicleType::Bus => {
-            // Bus speed range: 70.0 - 100.0; threshold = 70 + 0.90*(30) = 97.0
-            vehicle.speed >= 97.0
+        if average_congestion > self.config.high_water {
+            self.scale = (self.scale * self.config.backoff).clamp(0.0, 1.0);
+        } else if average_congestion < self.config.low_water {
+            self.scale = (self.scale + self.config.recovery).clamp(0.0, 1.0);
         }
-        VehicleType::Truck => {
-            // Truck speed range: 60.0 - 90.0; threshold = 60 + 0.90*(30) = 87.0
-            vehicle.speed >= 87.0
+    }
+
+    // Vehicles to actually spawn when the schedule asks for `scheduled`.
+    pub fn effective_count(&self, scheduled: usize) -> usize {
+        if !self.config.enabled {
+            return scheduled;
         }
-        VehicleType::EmergencyVan => {
-            // EmergencyVan speed range: 120.0 - 180.0; threshold = 120 + 0.90*(60) = 174.0
-            vehicle.speed >= 174.0
+        ((scheduled as f64 * self.scale).round() as usize).min(scheduled)
+    }
+}
+
+// Vehicle events of traffic updates skipped for back-pressure, held for the next update that is
+// published. Holds at most `max_events`; beyond that the oldest are dropped and counted.
+#[derive(Debug, Clone, Default)]
+pub struct DeferredVehicleData {
+    events: VecDeque<VehicleData>,
+    max_events: usize,
+    dropped: u64,
+}
+
+impl DeferredVehicleData {
+    pub fn new(max_events: usize) -> Self {
+        Self {
+            max_events,
+            ..Self::default()
         }
     }
+
+    // Moves `events` to the back of the buffer. Returns how many of the oldest had to be dropped
+    // to stay within max_events.
+    pub fn defer(&mut self, events: &mut Vec<VehicleData>) -> usize {
+        self.events.extend(events.drain(..));
+        let excess = self.events.len().saturating_sub(self.max_events);
+        self.events.drain(..excess);
+        self.dropped += excess as u64;
+        excess
+    }
+
+    // Every held event, oldest first, leaving the buffer empty.
+    pub fn take(&mut self) -> Vec<VehicleData> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    // Events dropped over the run because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
 }
 
-// Spawns a new vehicle and computes its route based on predicted traffic data.
-pub fn spawn_vehicle(
-    intersections: &Arc<Mutex<Vec<Intersection>>>,
-    lanes: &Arc<Mutex<Vec<Lane>>>,
-    current_traffic_data: &TrafficData,
-    next_vehicle_id: &mut u64,
-) -> Option<(Vehicle, Vec<Lane>)> {
-    let intersections_guard = intersections.lock().unwrap();
-    let lanes_guard = lanes.lock().unwrap();
-    let entry_points: Vec<_> = intersections_guard.iter().filter(|i| i.is_entry).collect();
-    let exit_points: Vec<_> = intersections_guard.iter().filter(|i| i.is_exit).collect();
+// Per-entry release counts for the configured ramp meters, and the vehicles waiting at them.
+#[derive(Debug, Clone, Default)]
+pub struct RampMeters {
+    meters: HashMap<String, RampMeterConfig>,
+    // Entry name -> (index of the current window, vehicles released in it).
+    released: HashMap<String, (u64, u32)>,
+    // Vehicles held back at a metered entry, oldest first, and how many wait at each entry.
+    held_back: Vec<IntersectionId>,
+    waiting: HashMap<String, u32>,
+    // Vehicles turned away because their entry already had a full quota waiting.
+    dropped: u64,
+}
 
-    if entry_points.is_empty() || exit_points.is_empty() {
-        return None;
+impl RampMeters {
+    pub fn new(meters: HashMap<String, RampMeterConfig>) -> Self {
+        Self {
+            meters,
+            ..Self::default()
+        }
+    }
+
+    // Keeps a vehicle waiting at `entry_id` (named `entry_name`) until a later tick. At most one
+    // window's quota waits at an entry; a vehicle beyond that is dropped, counted, and false is
+    // returned.
+    pub fn hold_back(&mut self, entry_id: IntersectionId, entry_name: &str) -> bool {
+        let quota = self
+            .meters
+            .get(entry_name)
+            .map_or(u32::MAX, |meter| meter.max_vehicles);
+        let waiting = self.waiting.entry(entry_name.to_string()).or_insert(0);
+        if *waiting >= quota {
+            self.dropped += 1;
+            return false;
+        }
+        *waiting += 1;
+        self.held_back.push(entry_id);
+        true
     }
 
-    let mut rng = rand::rng();
-    let entry = entry_points[rng.random_range(0..entry_points.len())];
-    let exit = exit_points[rng.random_range(0..exit_points.len())];
+    // The vehicles held back so far, oldest first, to be retried at their entries.
+    pub fn take_held_back(&mut self) -> Vec<IntersectionId> {
+        self.waiting.clear();
+        std::mem::take(&mut self.held_back)
+    }
 
-    if entry.id == exit.id {
-        return None;
+    pub fn dropped(&self) -> u64 {
+        self.dropped
     }
 
-    let rand_val: f64 = rng.random_range(0.0..1.0);
-    let vehicle_type = if rand_val < 0.70 {
-        VehicleType::Car
-    } else if rand_val < 0.90 {
-        VehicleType::Truck
-    } else if rand_val < 0.99 {
-        VehicleType::Bus
-    } else {
-        VehicleType::EmergencyVan
-    };
+    // Whether `entry_name` can still release a vehicle in the window containing `elapsed_secs`.
+    pub fn has_quota(&self, entry_name: &str, elapsed_secs: u64) -> bool {
+        let Some(meter) = self.meters.get(entry_name) else {
+            return true;
+        };
+        let window = elapsed_secs / meter.window_secs.max(1);
+        match self.released.get(entry_name) {
+            Some(&(current_window, count)) if current_window == window => {
+                count < meter.max_vehicles
+            }
+            _ => meter.max_vehicles > 0,
+        }
+    }
 
-    let speed = match vehicle_type {
-        VehicleType::Car => rng.random_range(80.0..140.0),
-        VehicleType::Bus => rng.random_range(70.0..100.0),
-        VehicleType::Truck => rng.random_range(60.0..90.0),
-        VehicleType::EmergencyVan => rng.random_range(120.0..180.0),
-    };
+    // Counts one vehicle released at `entry_name` at `elapsed_secs`. Returns false, without
+    // counting it, if the entry has already used up its quota for the current window.
+    pub fn try_release(&mut self, entry_name: &str, elapsed_secs: u64) -> bool {
+        let Some(meter) = self.meters.get(entry_name) else {
+            return true;
+        };
+        let window = elapsed_secs / meter.window_secs.max(1);
+        let (current_window, count) = self
+            .released
+            .entry(entry_name.to_string())
+            .or_insert((window, 0));
+        if *current_window != window {
+            *current_window = window;
+            *count = 0;
+        }
+        if *count >= meter.max_vehicles {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
 
-    let vehicle = Vehicle::new(*next_vehicle_id, vehicle_type, entry.id, exit.id, speed);
-    *next_vehicle_id += 1;
+// Detects when the network has settled into a steady state: over the last `window` ticks the
+// network-average congestion moves by at most `congestion_tolerance` (absolute) and the active
+// vehicle count by at most `vehicle_tolerance` (relative to its mean).
+#[derive(Debug, Clone)]
+pub struct SteadyStateDetector {
+    pub window: usize,
+    pub congestion_tolerance: f64,
+    pub vehicle_tolerance: f64,
+    congestion_history: VecDeque<f64>,
+    vehicle_history: VecDeque<f64>,
+    reached: bool,
+}
+
+impl SteadyStateDetector {
+    pub fn new(window: usize, congestion_tolerance: f64, vehicle_tolerance: f64) -> Self {
+        Self {
+            window,
+            congestion_tolerance,
+            vehicle_tolerance,
+            congestion_history: VecDeque::with_capacity(window),
+            vehicle_history: VecDeque::with_capacity(window),
+            reached: false,
+        }
+    }
+
+    pub fn is_reached(&self) -> bool {
+        self.reached
+    }
+
+    // Records one tick of network metrics. Returns true only on the tick steady state is first reached.
+    pub fn observe(&mut self, average_congestion: f64, active_vehicles: usize) -> bool {
+        if self.reached || self.window == 0 {
+            return false;
+        }
+        if self.congestion_history.len() == self.window {
+            self.congestion_history.pop_front();
+            self.vehicle_history.pop_front();
+        }
+        self.congestion_history.push_back(average_congestion);
+        self.vehicle_history.push_back(active_vehicles as f64);
+        if self.congestion_history.len() < self.window {
+            return false;
+        }
 
-    let entry_id = entry.id;
-    let exit_id = exit.id;
-    drop(intersections_guard);
+        let congestion_spread = spread(&self.congestion_history);
+        let vehicle_mean = self.vehicle_history.iter().sum::<f64>() / self.window as f64;
+        let vehicle_spread = spread(&self.vehicle_history);
+        if congestion_spread <= self.congestion_tolerance
+            && vehicle_spread <= self.vehicle_tolerance * vehicle_mean.max(1.0)
+        {
+            self.reached = true;
+        }
+        self.reached
+    }
+}
+
+impl Default for SteadyStateDetector {
+    fn default() -> Self {
+        Self::new(10, 0.05, 0.10)
+    }
+}
+
+// Difference between the largest and smallest value in the window.
+fn spread(values: &VecDeque<f64>) -> f64 {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    max - min
+}
+
+// Helper function to check if a vehicle is overspeeding: faster than the lane's posted limit.
+// Emergency vans are exempt from posted limits. Only hand-built lanes lack a limit; there a vehicle
+// is overspeeding if its speed is in the upper 10% of its type's spawn range
+// (e.g. Car speed range: 80.0 - 140.0; threshold = 80 + 0.90*(60) = 134.0).
+fn verify_speed_limit(vehicle: &Vehicle, lane: &Lane, speed_ranges: &SpeedRanges) -> bool {
+    match lane.speed_limit {
+        Some(_) if vehicle.vehicle_type == VehicleType::EmergencyVan => false,
+        Some(limit) => vehicle.speed > limit,
+        None => {
+            vehicle.speed
+                >= speed_ranges
+                    .for_type(vehicle.vehicle_type)
+                    .overspeed_threshold()
+        }
+    }
+}
 
-    let filtered_lanes: Vec<Lane> = lanes_guard
-        .clone()
+// Spawn slots for one iteration of the main loop; `None` picks a random entry, `Some` a specific
+// one. With demand profiles each entry receives its arrivals over one configured tick, with
+// fractional arrivals carried over in `demand_carry`; otherwise the rush-hour curve gives the
+// count for the iteration.
+pub fn scheduled_spawns(
+    intersections: &[Intersection],
+    demand_profiles: Option<&DemandProfiles>,
+    demand_carry: &mut HashMap<IntersectionId, f64>,
+    config: &SimulationConfig,
+    elapsed: u64,
+) -> Vec<Option<IntersectionId>> {
+    let Some(profiles) = demand_profiles else {
+        return vec![None; config.rush_hour.spawn_count(elapsed)];
+    };
+    let tick_secs = config.tick_duration().as_secs_f64();
+    intersections
+        .iter()
+        .filter(|i| i.is_entry)
+        .flat_map(|i| {
+            let carry = demand_carry.entry(i.id).or_insert(0.0);
+            let count = profiles.spawns_for_tick(&i.name, elapsed, tick_secs, carry);
+            std::iter::repeat_n(Some(i.id), count)
+        })
+        .collect()
+}
+
+// Spawns a new vehicle and computes its route based on predicted traffic data.
+// All random choices are drawn from the supplied rng so a seeded run is reproducible.
+// Vehicle type weights, speed ranges and the routing occupancy threshold come from `config`; the
+// type is drawn from the mix for `hour` of the simulated day (see SimulationConfig::hour_at).
+pub fn spawn_vehicle(
+    intersections: &Arc<RwLock<Vec<Intersection>>>,
+    lanes: &Arc<LaneStore>,
+    current_traffic_data: &TrafficData,
+    next_vehicle_id: &mut u64,
+    rng: &mut SmallRng,
+    config: &SimulationConfig,
+    hour: u32,
+) -> Option<(Vehicle, Vec<Lane>)> {
+    let routable = routable_lanes(lanes.snapshot(), current_traffic_data, config);
+    let intersections_guard = intersections.read().unwrap();
+    spawn_vehicle_from(
+        &intersections_guard,
+        &routable,
+        next_vehicle_id,
+        rng,
+        config,
+        None,
+        hour,
+    )
+}
+
+// Lanes a newly spawned vehicle may be routed over: no accident and not above the configured
+// occupancy threshold. Computed once per tick and shared by the whole spawn batch.
+pub fn routable_lanes(
+    lanes: Vec<Lane>,
+    current_traffic_data: &TrafficData,
+    config: &SimulationConfig,
+) -> Vec<Lane> {
+    lanes
         .into_iter()
         .filter(|lane| {
             if lane.has_accident {
                 return false;
             }
             if let Some(&occ) = current_traffic_data.lane_occupancy.get(&lane.name) {
-                if occ > 0.75 {
+                if occ > config.route_occupancy_threshold {
                     return false;
                 }
             }
             true
         })
-        .collect();
+        .collect()
+}
+
+// Spawns a vehicle using an already locked intersection list and pre-filtered lanes.
+// `entry_id` of None picks a random entry intersection. The vehicle type is drawn from the mix
+// configured for `hour` of the simulated day.
+pub fn spawn_vehicle_from(
+    intersections: &[Intersection],
+    routable_lanes: &[Lane],
+    next_vehicle_id: &mut u64,
+    rng: &mut SmallRng,
+    config: &SimulationConfig,
+    entry_id: Option<IntersectionId>,
+    hour: u32,
+) -> Option<(Vehicle, Vec<Lane>)> {
+    let entry_id = match entry_id {
+        Some(id) => id,
+        None => random_entry(intersections, rng)?,
+    };
+    let exit_points: Vec<_> = intersections.iter().filter(|i| i.is_exit).collect();
+
+    if exit_points.is_empty() {
+        return None;
+    }
+
+    let exit_id = exit_points[rng.random_range(0..exit_points.len())].id;
+
+    if entry_id == exit_id {
+        return None;
+    }
+
+    let vehicle_type = random_vehicle_type(rng, config.vehicle_mix_at(hour));
+
+    let speed_range = config.speed_ranges.for_type(vehicle_type);
+    let speed = rng.random_range(speed_range.min..speed_range.max);
+
+    let vehicle = Vehicle::new(*next_vehicle_id, vehicle_type, entry_id, exit_id, speed);
+    *next_vehicle_id += 1;
+
+    let route = route_vehicle(intersections, routable_lanes, &vehicle, config)?;
+    Some((vehicle, route))
+}
+
+// A uniformly chosen entry intersection, or None if there are none.
+pub fn random_entry(intersections: &[Intersection], rng: &mut SmallRng) -> Option<IntersectionId> {
+    let entry_points: Vec<_> = intersections.iter().filter(|i| i.is_entry).collect();
+    if entry_points.is_empty() {
+        return None;
+    }
+    Some(entry_points[rng.random_range(0..entry_points.len())].id)
+}
+
+// A request to send an emergency van from `entry` to `exit` right away, outside the random spawns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchRequest {
+    pub entry: IntersectionId,
+    pub exit: IntersectionId,
+}
 
-    let route = generate_shortest_lane_route(&filtered_lanes, entry_id, exit_id)?;
+// Spawns the emergency van for a dispatch request. Returns None (and logs why) if the endpoints
+// aren't an entry and an exit intersection or no route connects them.
+pub fn dispatch_emergency_vehicle(
+    intersections: &[Intersection],
+    routable_lanes: &[Lane],
+    next_vehicle_id: &mut u64,
+    rng: &mut SmallRng,
+    config: &SimulationConfig,
+    request: DispatchRequest,
+) -> Option<(Vehicle, Vec<Lane>)> {
+    let is_entry = intersections
+        .iter()
+        .any(|i| i.id == request.entry && i.is_entry);
+    let is_exit = intersections
+        .iter()
+        .any(|i| i.id == request.exit && i.is_exit);
+    if !is_entry || !is_exit || request.entry == request.exit {
+        println!(
+            "Rejected dispatch from {} to {}: not an entry/exit pair.",
+            request.entry, request.exit
+        );
+        return None;
+    }
+
+    let speed_range = config.speed_ranges.for_type(VehicleType::EmergencyVan);
+    let speed = rng.random_range(speed_range.min..speed_range.max);
+    let vehicle = Vehicle::new(
+        *next_vehicle_id,
+        VehicleType::EmergencyVan,
+        request.entry,
+        request.exit,
+        speed,
+    );
+
+    let Some(route) = route_vehicle(intersections, routable_lanes, &vehicle, config) else {
+        println!(
+            "Rejected dispatch from {} to {}: no route.",
+            request.entry, request.exit
+        );
+        return None;
+    };
+    *next_vehicle_id += 1;
     Some((vehicle, route))
 }
 
+// Routes a new vehicle from its entry to its exit over `routable_lanes`, honouring entry-only
+// intersections and turn restrictions.
+fn route_vehicle(
+    intersections: &[Intersection],
+    routable_lanes: &[Lane],
+    vehicle: &Vehicle,
+    config: &SimulationConfig,
+) -> Option<Vec<Lane>> {
+    let entry_only = entry_only_intersections(intersections);
+    let restrictions = turn_restrictions(intersections);
+    // Every lane ends at another intersection to cross, so for emergency vehicles fewer hops wins
+    // over a slightly shorter route.
+    let penalty = if vehicle.is_emergency() {
+        config.emergency_intersection_penalty_meters
+    } else {
+        0.0
+    };
+    generate_restricted_lane_route_by(
+        routable_lanes,
+        vehicle.entry_point,
+        vehicle.exit_point,
+        &entry_only,
+        &restrictions,
+        |lane| lane.length_meters + penalty,
+    )
+}
+
+// Replaces the vehicle's route with `new_route`, but only if the new route starts at the
+// intersection the vehicle is currently at. Invalid (or empty) routes are logged and rejected,
+// leaving the existing route untouched. Returns true if the route was replaced.
+pub fn assign_route(vehicle: &Vehicle, route: &mut Vec<Lane>, new_route: Vec<Lane>) -> bool {
+    match new_route.first() {
+        Some(first) if first.from == vehicle.current_intersection => {
+            *route = new_route;
+            true
+        }
+        Some(first) => {
+            println!(
+                "Rejected route for vehicle {:?} {}: route starts at {:?} but vehicle is at {:?}.",
+                vehicle.vehicle_type, vehicle.id, first.from, vehicle.current_intersection
+            );
+            false
+        }
+        None => {
+            println!(
+                "Rejected empty route for vehicle {:?} {}.",
+                vehicle.vehicle_type, vehicle.id
+            );
+            false
+        }
+    }
+}
+
+// Waits out the crash clearance time, then removes a crashed vehicle from its lane and the
+// active set and records its accident event. The lane is unblocked once no crashed vehicles remain.
+async fn remove_crashed_vehicle(vehicle: &Vehicle, lane_name: &str, ctx: &JourneyContext) {
+    ctx.event_log.record(SimulationEvent::VehicleCrashed {
+        vehicle_id: vehicle.id,
+        lane: lane_name.to_string(),
+        severity: vehicle.severity,
+    });
+    let crash_wait = ctx.config.crash_severity.clearance_secs(vehicle.severity);
+    println!(
+        "Vehicle {:?} {} crashed on lane {} with severity {} at {:?}. Waiting {:.2} seconds before removal.",
+        vehicle.vehicle_type, vehicle.id, lane_name, vehicle.severity, vehicle.accident_timestamp, crash_wait
+    );
+    pausable_sleep(
+        scale_duration(Duration::from_secs_f64(crash_wait), ctx.time_scale),
+        &ctx.paused,
+    )
+    .await;
+    println!(
+        "Vehicle {:?} {} removed from simulation due to crash.",
+        vehicle.vehicle_type, vehicle.id
+    );
+    if let Some(mut lane) = ctx.lanes.lock_lane(lane_name) {
+        lane.remove_vehicle(vehicle);
+        lane.unblock_cleared_accidents();
+    }
+    {
+        let mut active = ctx.active_ids.lock().unwrap();
+        active.remove(&vehicle.id);
+    }
+    {
+        let mut veh_ev = ctx.vehicle_events.lock().unwrap();
+        veh_ev.push(VehicleData {
+            id: vehicle.id,
+            waiting_time: vehicle.waiting_time,
+            accident_timestamp: vehicle.accident_timestamp,
+            severity: vehicle.severity,
+            current_lane: lane_name.to_string(),
+            collided_with: vehicle.collided_with,
+            fuel_used: vehicle.fuel_used,
+        });
+    }
+}
+
+// Shortest wait before a vehicle at a red light checks the signal again, in simulated seconds.
+// Phase timers only move when the light controller applies a whole second, which happens once per
+// light tick (or once a second for sub-second ticks), so a phase with 0 seconds left may not have
+// changed yet; re-checking immediately would spin. A quarter of that interval keeps the reaction
+// prompt: 0.25s with the default one-second light tick.
+pub fn red_light_recheck_secs(light_tick: Duration) -> f64 {
+    light_tick.as_secs_f64().max(1.0) / 4.0
+}
+
+// Shared state a vehicle journey works against: the network, the light controller, the
+// bookkeeping every journey reports into, and how fast and whether the run is going.
+// All waits are in simulated seconds and are divided by time_scale before sleeping. While `paused`
+// is set the vehicle holds its position and its pending waits are frozen. The current `weather`
+// slows the vehicle down and raises its crash odds on every lane. The vehicle's lane, progress
+// along it and waiting status are kept up to date in `positions`.
+#[derive(Clone)]
+pub struct JourneyContext {
+    pub intersections: Arc<RwLock<Vec<Intersection>>>,
+    pub lanes: Arc<LaneStore>,
+    pub traffic_controller: Arc<RwLock<TrafficLightController>>,
+    pub active_ids: Arc<Mutex<HashSet<u64>>>,
+    pub vehicle_events: Arc<Mutex<Vec<VehicleData>>>,
+    pub time_scale: f64,
+    pub paused: Arc<AtomicBool>,
+    pub config: Arc<SimulationConfig>,
+    pub event_log: EventLog,
+    pub journeys: ActiveJourneys,
+    pub positions: VehiclePositions,
+    pub weather: SharedWeather,
+}
+
+impl JourneyContext {
+    // A context running in real time under clear weather, with empty bookkeeping and nothing
+    // logged. Override the other fields with struct update syntax.
+    pub fn new(
+        intersections: Arc<RwLock<Vec<Intersection>>>,
+        lanes: Arc<LaneStore>,
+        traffic_controller: Arc<RwLock<TrafficLightController>>,
+        config: Arc<SimulationConfig>,
+    ) -> Self {
+        Self {
+            intersections,
+            lanes,
+            traffic_controller,
+            active_ids: Arc::default(),
+            vehicle_events: Arc::default(),
+            time_scale: 1.0,
+            paused: Arc::default(),
+            config,
+            event_log: EventLog::disabled(),
+            journeys: ActiveJourneys::default(),
+            positions: VehiclePositions::default(),
+            weather: SharedWeather::default(),
+        }
+    }
+}
+
 // Simulates a vehicle’s journey as an independent async task.
-// The vehicle pushes its event data into the shared vehicle_events vector when it reaches its destination or crashes.
+// The vehicle pushes its event data into ctx.vehicle_events when it reaches its destination or crashes.
+// The task owns its rng (seeded by the spawner) so crash rolls don't depend on task scheduling order.
 pub async fn simulate_vehicle_journey(
     mut vehicle: Vehicle,
-    mut route: Vec<Lane>,
-    intersections: Arc<Mutex<Vec<Intersection>>>,
-    lanes: Arc<Mutex<Vec<Lane>>>,
-    traffic_controller: Arc<Mutex<TrafficLightController>>,
-    active_ids: Arc<Mutex<HashSet<u64>>>,
-    vehicle_events: Arc<Mutex<Vec<VehicleData>>>,
+    initial_route: Vec<Lane>,
+    mut rng: SmallRng,
+    ctx: JourneyContext,
 ) {
-    let mut rng = SmallRng::seed_from_u64(1);
+    let JourneyContext {
+        intersections,
+        lanes,
+        traffic_controller,
+        active_ids,
+        vehicle_events,
+        time_scale,
+        paused,
+        config,
+        event_log,
+        journeys,
+        positions,
+        weather,
+    } = &ctx;
+    let time_scale = *time_scale;
+    let mut route = Vec::new();
+    if !assign_route(&vehicle, &mut route, initial_route) {
+        active_ids.lock().unwrap().remove(&vehicle.id);
+        return;
+    }
+    track_journey(journeys, &vehicle, &route);
+    // Lane ahead an emergency vehicle has reserved through a light override, if any.
+    let mut reserved_lane: Option<String> = None;
+    // Simulated seconds spent at the current red light, across however many phases it lasts.
+    let mut red_light_wait: f64 = 0.0;
+    // Whether the vehicle already sat out the accident on its current lane. It re-enters this loop
+    // after every red-light wait and must not wait for the same accident again.
+    let mut waited_for_accident = false;
     while let Some(current_lane) = route.first() {
+        while paused.load(Ordering::SeqCst) {
+            sleep(Duration::from_millis(100)).await;
+        }
         let mut add_success = false;
         {
-            let mut lanes_guard = lanes.lock().unwrap();
-            if let Some(lane) = lanes_guard.iter_mut().find(|l| l.name == current_lane.name) {
-                if lane.add_vehicle(&vehicle) {
+            if let Some(mut lane) = lanes.lock_lane(&current_lane.name) {
+                if lane.contains_vehicle(vehicle.id) {
+                    // Still queued here after waiting at the light.
+                    add_success = true;
+                } else if lane.add_vehicle(&vehicle) {
                     vehicle.is_in_lane = true;
                     add_success = true;
+                    event_log.record(SimulationEvent::VehicleMoved {
+                        vehicle_id: vehicle.id,
+                        lane: lane.name.clone(),
+                    });
+                    positions
+                        .lock()
+                        .unwrap()
+                        .insert(vehicle.id, VehicleProgress::entered(&lane.name));
                 } else {
                     println!(
                         "Vehicle {:?} {} could not be added to lane {} (capacity full). Retrying...",
@@ -211,44 +941,40 @@ pub async fn simulate_vehicle_journey(
             }
         }
         if !add_success {
-            sleep(Duration::from_secs_f64(5.0)).await;
+            vehicle.current_speed = 0.0;
+            pausable_sleep(
+                scale_duration(Duration::from_secs_f64(5.0), time_scale),
+                paused,
+            )
+            .await;
             continue;
         }
 
-        let lane_has_accident = {
-            let lanes_guard = lanes.lock().unwrap();
-            lanes_guard
-                .iter()
-                .find(|l| l.name == current_lane.name)
-                .map(|l| l.has_accident)
-                .unwrap_or(false)
-        };
-        if lane_has_accident {
-            let accident_severity = if vehicle.is_accident {
-                vehicle.severity
-            } else {
-                2
-            };
-            let target_wait = accident_severity as f64 * 1.5;
-            if vehicle.waiting_start.is_none() {
-                vehicle.waiting_start = Some(current_timestamp());
-            }
-            let waited = current_timestamp() - vehicle.waiting_start.unwrap();
-            if (waited as f64) < target_wait {
-                let remaining = target_wait - waited as f64;
+        let blocking_severity = lanes
+            .lock_lane(&current_lane.name)
+            .filter(|l| l.has_accident)
+            .map(|l| l.accident_severity);
+        if let Some(accident_severity) = blocking_severity.filter(|_| !waited_for_accident) {
+            waited_for_accident = true;
+            vehicle.current_speed = 0.0;
+            let target_wait = config.crash_severity.clearance_secs(accident_severity);
+            if target_wait > 0.0 {
+                mark_waiting(positions, vehicle.id);
                 println!(
-                    "Vehicle {:?} {} waiting at lane {} due to accident. Waiting {:.2} more seconds.",
-                    vehicle.vehicle_type, vehicle.id, current_lane.name, remaining
+                    "Vehicle {:?} {} waiting at lane {} due to accident. Waiting {:.2} seconds.",
+                    vehicle.vehicle_type, vehicle.id, current_lane.name, target_wait
                 );
-                sleep(Duration::from_secs_f64(remaining)).await;
-                let total_waited = current_timestamp() - vehicle.waiting_start.unwrap();
-                vehicle.waiting_time += total_waited;
-                vehicle.waiting_start = None;
+                pausable_sleep(
+                    scale_duration(Duration::from_secs_f64(target_wait), time_scale),
+                    paused,
+                )
+                .await;
+                vehicle.record_wait(target_wait.round() as u64);
             }
         }
 
         let intersection_opt = {
-            let intersections_guard = intersections.lock().unwrap();
+            let intersections_guard = intersections.read().unwrap();
             intersections_guard
                 .iter()
                 .find(|i| i.id == current_lane.from)
@@ -256,14 +982,20 @@ pub async fn simulate_vehicle_journey(
         };
         if let Some(intersection) = intersection_opt {
             if intersection.control == IntersectionControl::TrafficLight {
-                let can_move = {
-                    let tc = traffic_controller.lock().unwrap();
-                    tc.is_lane_green(intersection.id, &current_lane.name)
+                let signal = {
+                    let tc = traffic_controller.read().unwrap();
+                    tc.lane_signal(intersection.id, &current_lane.name)
+                };
+                // A vehicle already rolling clears a yellow light; a stationary one waits it out.
+                let can_move = match signal {
+                    LightState::Green => true,
+                    LightState::Yellow => vehicle.current_speed > 0.0,
+                    LightState::Red => false,
                 };
                 if !can_move {
                     if vehicle.is_emergency() {
                         {
-                            let mut tc = traffic_controller.lock().unwrap();
+                            let mut tc = traffic_controller.write().unwrap();
                             tc.set_emergency_override_route(
                                 intersection.id,
                                 vec![current_lane.name.clone()],
@@ -273,77 +1005,136 @@ pub async fn simulate_vehicle_journey(
                             "Emergency vehicle {:?} {} triggered override at intersection {:?} on lane {}.",
                             vehicle.vehicle_type, vehicle.id, intersection.id, current_lane.name
                         );
-                    } else {
-                        if vehicle.waiting_start.is_none() {
-                            vehicle.waiting_start = Some(current_timestamp());
+                        // Keep other traffic off the lane ahead so it clears before we get there.
+                        if let Some(next_lane) = route.get(1) {
+                            if reserved_lane.as_deref() != Some(next_lane.name.as_str()) {
+                                release_reserved_lane(lanes, &mut reserved_lane);
+                                if let Some(mut lane) = lanes.lock_lane(&next_lane.name) {
+                                    lane.reserve_for_emergency();
+                                    reserved_lane = Some(next_lane.name.clone());
+                                }
+                            }
                         }
+                    } else {
+                        // Stopped at a red light: the vehicle pulls away from standstill.
+                        vehicle.current_speed = 0.0;
+                        mark_waiting(positions, vehicle.id);
                         let remaining_phase = {
-                            let tc = traffic_controller.lock().unwrap();
+                            let tc = traffic_controller.read().unwrap();
                             if let Some(ctrl) = tc.controllers.get(&intersection.id) {
-                                ctrl.phases[ctrl.current_phase_index]
-                                    .duration
-                                    .saturating_sub(ctrl.elapsed_in_phase)
+                                ctrl.secs_until_change()
                             } else {
                                 1
                             }
                         };
-                        sleep(Duration::from_secs(remaining_phase)).await;
+                        // The pause-aware sleep is exactly the simulated time spent at the light,
+                        // so the wait is summed from the sleeps rather than read off the
+                        // second-resolution wall clock.
+                        let wait_secs = (remaining_phase as f64)
+                            .max(red_light_recheck_secs(config.light_tick_duration()));
+                        pausable_sleep(
+                            scale_duration(Duration::from_secs_f64(wait_secs), time_scale),
+                            paused,
+                        )
+                        .await;
+                        red_light_wait += wait_secs;
                         continue;
                     }
-                } else {
-                    if let Some(start) = vehicle.waiting_start {
-                        let waited = current_timestamp() - start;
-                        vehicle.waiting_time += waited;
-                        vehicle.waiting_start = None;
+                } else if red_light_wait > 0.0 {
+                    vehicle.record_wait(red_light_wait.round() as u64);
+                    if let Some(waited_at) = intersections
+                        .write()
+                        .unwrap()
+                        .iter_mut()
+                        .find(|i| i.id == intersection.id)
+                    {
+                        waited_at.record_wait(red_light_wait);
                     }
+                    if let Some(mut lane) = lanes.lock_lane(&current_lane.name) {
+                        lane.record_wait(red_light_wait);
+                    }
+                    red_light_wait = 0.0;
                 }
             }
         }
 
-        let accident_probability = if verify_speed_limit(&vehicle) {
-            0.15
+        let lane_weather = *weather.read().unwrap();
+        let overspeeding = verify_speed_limit(&vehicle, current_lane, &config.speed_ranges);
+        if let Some(mut lane) = lanes.lock_lane(&current_lane.name) {
+            lane.record_speed(vehicle.speed, overspeeding);
+        }
+        let mut base_probability = if overspeeding {
+            config.overspeed_crash_probability
         } else {
-            0.10
+            config.crash_probability
         };
+        if config.high_risk_lanes.contains(&current_lane.name) {
+            base_probability *= config.high_risk_crash_multiplier;
+        }
+        let accident_probability = (base_probability * lane_weather.crash_factor()).clamp(0.0, 1.0);
         if rng.random_bool(accident_probability) {
             let crashed_timestamp = current_timestamp();
             vehicle.accident_timestamp = Some(crashed_timestamp);
-            let crash_severity = rng.random_range(1..=3);
+            vehicle.is_accident = true;
+            let crash_severity = config.crash_severity.sample(&mut rng);
             vehicle.severity = crash_severity;
-            let crash_wait = crash_severity as f64 * 1.5;
+            // The wreck blocks the lane for the vehicles behind until it is cleared.
+            if let Some(mut lane) = lanes.lock_lane(&current_lane.name) {
+                lane.record_crash(vehicle.id, crashed_timestamp, crash_severity);
+            }
+            forget_journey(journeys, positions, vehicle.id);
+            release_reserved_lane(lanes, &mut reserved_lane);
+            remove_crashed_vehicle(&vehicle, &current_lane.name, &ctx).await;
+            return;
+        }
+
+        // Two-vehicle collision: if the vehicle directly ahead on this lane is slower, the gap is
+        // closing and the two may collide, more likely the fuller the lane is.
+        let collision = lanes.lock_lane(&current_lane.name).and_then(|mut lane| {
+            let partner_id = lane.closing_vehicle_ahead(&vehicle)?;
+            let occupancy = lane.current_vehicle_length / lane.effective_length();
+            let probability =
+                (config.collision_probability * occupancy * lane_weather.crash_factor())
+                    .clamp(0.0, 1.0);
+            if !rng.random_bool(probability) {
+                return None;
+            }
+            let severity = config.crash_severity.sample(&mut rng);
+            lane.record_collision(vehicle.id, partner_id, current_timestamp(), severity);
+            Some((partner_id, severity))
+        });
+        if let Some((partner_id, severity)) = collision {
+            vehicle.accident_timestamp = Some(current_timestamp());
+            vehicle.is_accident = true;
+            vehicle.severity = severity;
+            vehicle.collided_with = Some(partner_id);
             println!(
-                "Vehicle {:?} {} crashed on lane {} with severity {} at {:?}. Waiting {:.2} seconds before removal.",
-                vehicle.vehicle_type, vehicle.id, current_lane.name, crash_severity, vehicle.accident_timestamp, crash_wait
+                "Vehicle {:?} {} collided with vehicle {} on lane {}.",
+                vehicle.vehicle_type, vehicle.id, partner_id, current_lane.name
             );
-            sleep(Duration::from_secs_f64(crash_wait)).await;
+            forget_journey(journeys, positions, vehicle.id);
+            release_reserved_lane(lanes, &mut reserved_lane);
+            remove_crashed_vehicle(&vehicle, &current_lane.name, &ctx).await;
+            return;
+        }
+
+        // On a clear lane a faster vehicle may pass the slower one ahead instead of following it.
+        let passed = lanes
+            .lock_lane(&current_lane.name)
+            .and_then(|mut lane| lane.try_overtake(vehicle.id));
+        if let Some(passed_id) = passed {
             println!(
-                "Vehicle {:?} {} removed from simulation due to crash.",
-                vehicle.vehicle_type, vehicle.id
+                "Vehicle {:?} {} overtook vehicle {} on lane {}.",
+                vehicle.vehicle_type, vehicle.id, passed_id, current_lane.name
             );
-            {
-                let mut lanes_guard = lanes.lock().unwrap();
-                if let Some(lane) = lanes_guard.iter_mut().find(|l| l.name == current_lane.name) {
-                    lane.remove_vehicle(&vehicle);
-                }
-            }
-            {
-                let mut active = active_ids.lock().unwrap();
-                active.remove(&vehicle.id);
-            }
-            {
-                let mut veh_ev = vehicle_events.lock().unwrap();
-                veh_ev.push(VehicleData {
-                    id: vehicle.id,
-                    waiting_time: vehicle.waiting_time,
-                    accident_timestamp: vehicle.accident_timestamp,
-                    severity: vehicle.severity,
-                    current_lane: current_lane.name.to_string(),
-                });
-            }
-            return;
         }
 
-        let travel_time_secs = current_lane.length_meters / vehicle.speed;
+        // Accelerate from the current speed towards top speed along the lane.
+        let travel_time_secs = vehicle.time_to_traverse_in(
+            current_lane.length_meters,
+            vehicle.current_speed,
+            lane_weather,
+        );
         println!(
             "Vehicle {:?} {} traveling lane {} (from {:?} to {:?}) in {:.2} seconds.",
             vehicle.vehicle_type,
@@ -353,20 +1144,58 @@ pub async fn simulate_vehicle_journey(
             current_lane.to,
             travel_time_secs
         );
-        sleep(Duration::from_secs_f64(travel_time_secs)).await;
-        vehicle.waiting_start = None;
-        {
-            let mut lanes_guard = lanes.lock().unwrap();
-            if let Some(lane) = lanes_guard.iter().position(|l| l.name == current_lane.name) {
-                lanes_guard.get_mut(lane).unwrap().remove_vehicle(&vehicle);
-            }
+        let travel_time = scale_duration(Duration::from_secs_f64(travel_time_secs), time_scale);
+        if let Some(progress) = positions.lock().unwrap().get_mut(&vehicle.id) {
+            progress.start_traversal(travel_time);
+        }
+        pausable_sleep(travel_time, paused).await;
+        vehicle.record_distance(current_lane.length_meters);
+
+        // A vehicle behind us may have run into us while we were traversing the lane.
+        let hit_by = lanes.lock_lane(&current_lane.name).and_then(|lane| {
+            lane.vehicle_queue
+                .iter()
+                .find(|v| v.id == vehicle.id)
+                .filter(|v| v.is_accident)
+                .map(|v| (v.accident_timestamp, v.severity, v.collided_with))
+        });
+        if let Some((accident_timestamp, severity, collided_with)) = hit_by {
+            vehicle.accident_timestamp = accident_timestamp;
+            vehicle.is_accident = true;
+            vehicle.severity = severity;
+            vehicle.collided_with = collided_with;
+            forget_journey(journeys, positions, vehicle.id);
+            release_reserved_lane(lanes, &mut reserved_lane);
+            remove_crashed_vehicle(&vehicle, &current_lane.name, &ctx).await;
+            return;
         }
+
+        if let Some(mut lane) = lanes.lock_lane(&current_lane.name) {
+            lane.remove_vehicle(&vehicle);
+        }
+        if reserved_lane.as_deref() == Some(current_lane.name.as_str()) {
+            release_reserved_lane(lanes, &mut reserved_lane);
+        }
+        vehicle.current_speed = vehicle.exit_speed_in(
+            current_lane.length_meters,
+            vehicle.current_speed,
+            lane_weather,
+        );
+        vehicle.current_intersection = current_lane.to;
         route.remove(0);
+        waited_for_accident = false;
+        track_journey(journeys, &vehicle, &route);
     }
+    forget_journey(journeys, positions, vehicle.id);
+    release_reserved_lane(lanes, &mut reserved_lane);
     println!(
         "Vehicle {:?} {} reached destination. Total waiting time: {} seconds.",
         vehicle.vehicle_type, vehicle.id, vehicle.waiting_time
     );
+    event_log.record(SimulationEvent::VehicleArrived {
+        vehicle_id: vehicle.id,
+        waiting_time: vehicle.waiting_time,
+    });
     {
         let mut veh_ev = vehicle_events.lock().unwrap();
         veh_ev.push(VehicleData {
@@ -375,6 +1204,8 @@ pub async fn simulate_vehicle_journey(
             accident_timestamp: vehicle.accident_timestamp,
             severity: vehicle.severity,
             current_lane: "".to_string(),
+            collided_with: None,
+            fuel_used: vehicle.fuel_used,
         });
     }
     {
@@ -383,56 +1214,383 @@ pub async fn simulate_vehicle_journey(
     }
 }
 
+// Records where a vehicle is on its journey so a snapshot can resume it.
+fn track_journey(journeys: &ActiveJourneys, vehicle: &Vehicle, route: &[Lane]) {
+    journeys.lock().unwrap().insert(
+        vehicle.id,
+        ActiveVehicle {
+            vehicle: vehicle.clone(),
+            remaining_route: route.iter().map(|l| l.name.clone()).collect(),
+        },
+    );
+}
+
+// Lifts an emergency vehicle's reservation once it has passed the lane (or left the network).
+fn release_reserved_lane(lanes: &LaneStore, reserved_lane: &mut Option<String>) {
+    if let Some(name) = reserved_lane.take() {
+        if let Some(mut lane) = lanes.lock_lane(&name) {
+            lane.release_emergency_reservation();
+        }
+    }
+}
+
+fn mark_waiting(positions: &VehiclePositions, vehicle_id: u64) {
+    if let Some(progress) = positions.lock().unwrap().get_mut(&vehicle_id) {
+        progress.waiting = true;
+    }
+}
+
+// Drops a vehicle that has arrived or crashed from the journey and position tracking.
+fn forget_journey(journeys: &ActiveJourneys, positions: &VehiclePositions, vehicle_id: u64) {
+    journeys.lock().unwrap().remove(&vehicle_id);
+    positions.lock().unwrap().remove(&vehicle_id);
+}
+
+// Aggregated counts for a finished (bounded) simulation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimulationSummary {
+    pub total_spawned: u64,
+    pub total_arrived: u64,
+    pub total_crashed: u64,
+}
+
+impl SimulationSummary {
+    // Counts the finished vehicles in a batch of vehicle events.
+    fn record_events(&mut self, events: &[VehicleData]) {
+        for event in events {
+            if event.accident_timestamp.is_some() {
+                self.total_crashed += 1;
+            } else {
+                self.total_arrived += 1;
+            }
+        }
+    }
+}
+
+// Arrivals (vehicles that finished without crashing) per minute over `interval_secs`.
+pub fn throughput_per_min(events: &[VehicleData], interval_secs: f64) -> f64 {
+    if interval_secs <= 0.0 {
+        return 0.0;
+    }
+    let arrivals = events
+        .iter()
+        .filter(|e| e.accident_timestamp.is_none())
+        .count();
+    arrivals as f64 * 60.0 / interval_secs
+}
+
+// Run-scoped settings, as opposed to the scenario tuning in SimulationConfig.
+// All randomness (spawns, speeds, crashes) is derived from `seed`, so two runs with the same seed
+// produce the same vehicles and crash events. Every published TrafficUpdate is tagged with
+// `run_id` so consumers can tell runs apart.
+// `time_scale` speeds up (> 1.0) or slows down (< 1.0) every sleep in the simulation and the light
+// controller. Setting `paused` freezes spawning, the traffic lights and all in-flight vehicles
+// until it is cleared.
+// `capacity_modifiers` are applied to the lanes at the start of every iteration based on elapsed
+// time. With `initial_state` the run resumes from a snapshot instead of an empty network.
+// The run stops after `max_ticks` spawn iterations, or never if None.
+// Every active vehicle's position is published to `positions` (see snapshot_positions). With
+// `event_stream` every spawn, move, crash, arrival and light change is also sent on the channel.
+// Requests arriving on `dispatches` spawn an emergency van on the next tick (see DispatchRequest).
+pub struct RunContext {
+    pub seed: u64,
+    pub run_id: String,
+    pub time_scale: f64,
+    pub paused: Arc<AtomicBool>,
+    pub capacity_modifiers: Vec<CapacityModifier>,
+    pub initial_state: Option<SimulationState>,
+    pub max_ticks: Option<u64>,
+    pub positions: VehiclePositions,
+    pub event_stream: Option<Sender<SimulationEvent>>,
+    pub dispatches: Option<Receiver<DispatchRequest>>,
+}
+
+impl RunContext {
+    // An unbounded real-time run from an empty network with nothing attached. Override the
+    // other fields with struct update syntax.
+    pub fn new(seed: u64, run_id: impl Into<String>) -> Self {
+        Self {
+            seed,
+            run_id: run_id.into(),
+            time_scale: 1.0,
+            paused: Arc::default(),
+            capacity_modifiers: Vec::new(),
+            initial_state: None,
+            max_ticks: None,
+            positions: VehiclePositions::default(),
+            event_stream: None,
+            dispatches: None,
+        }
+    }
+}
+
+// Runs the simulation loop, forever unless `run.max_ticks` is set. Spawn mix, speeds, crash
+// probabilities and the rush-hour cycle are taken from `config`.
 pub async fn run_simulation(
-    intersections: Arc<Mutex<Vec<Intersection>>>,
-    lanes: Arc<Mutex<Vec<Lane>>>,
+    intersections: Arc<RwLock<Vec<Intersection>>>,
+    lanes: Arc<LaneStore>,
+    config: SimulationConfig,
+    run: RunContext,
 ) {
+    run_simulation_for(intersections, lanes, config, run).await;
+}
+
+// Runs the simulation loop for at most `run.max_ticks` spawn iterations (forever if None), one
+// every `config.tick_millis` simulated milliseconds.
+// Once the limit is reached no new vehicles are spawned, all in-flight vehicle tasks are awaited
+// and a summary of spawned/arrived/crashed vehicles is returned.
+pub async fn run_simulation_for(
+    intersections: Arc<RwLock<Vec<Intersection>>>,
+    lanes: Arc<LaneStore>,
+    config: SimulationConfig,
+    run: RunContext,
+) -> SimulationSummary {
+    // Connecting may sleep between retries, so it runs off the async workers.
+    let bus = tokio::task::spawn_blocking(|| AmiquipBus::connect(&amqp_url()))
+        .await
+        .unwrap()
+        .expect("RabbitMQ connection");
+    let summary = run_simulation_on(&bus, intersections, lanes, config, run).await;
+    if let Err(err) = bus.close() {
+        println!("Error closing RabbitMQ connection: {}", err);
+    }
+    summary
+}
+
+// run_simulation_for over an already open `bus`, so the loop can run without a broker.
+pub async fn run_simulation_on(
+    bus: &dyn MessageBus,
+    intersections: Arc<RwLock<Vec<Intersection>>>,
+    lanes: Arc<LaneStore>,
+    config: SimulationConfig,
+    run: RunContext,
+) -> SimulationSummary {
+    let RunContext {
+        seed,
+        run_id,
+        time_scale,
+        paused,
+        capacity_modifiers,
+        initial_state,
+        max_ticks,
+        positions,
+        event_stream,
+        dispatches,
+    } = run;
+    let config = Arc::new(config);
     // Record simulation start time.
     let simulation_start = current_timestamp();
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    if let Some(state) = &initial_state {
+        *intersections.write().unwrap() = state.intersections.clone();
+        lanes.replace_lanes(state.restored_lanes());
+    }
 
     // Initialize the traffic light controller.
-    let (iclones, lclones) = {
-        let intersections_guard = intersections.lock().unwrap();
-        let lanes_guard = lanes.lock().unwrap();
-        (intersections_guard.clone(), lanes_guard.clone())
-    };
-    let tc = TrafficLightController::initialize(iclones, &lclones);
-    let traffic_controller = Arc::new(Mutex::new(tc));
+    let iclones = intersections.read().unwrap().clone();
+    let lclones = lanes.snapshot();
+    let mut tc = TrafficLightController::initialize(iclones, &lclones);
+    if let Some(state) = &initial_state {
+        state.restore_controller(&mut tc);
+    }
+    let traffic_controller = Arc::new(RwLock::new(tc));
 
     // Spawn the traffic light update loop as a concurrent task.
-    tokio::spawn(TrafficLightController::run_update_loop(Arc::clone(
-        &traffic_controller,
-    )));
+    let update_loop = tokio::spawn(TrafficLightController::run_update_loop(
+        Arc::clone(&traffic_controller),
+        time_scale,
+        Arc::clone(&paused),
+        config.light_tick_duration(),
+    ));
 
-    let mut next_vehicle_id = 1;
+    let mut next_vehicle_id = initial_state.as_ref().map_or(1, |s| s.next_vehicle_id);
+    let journeys: ActiveJourneys = Arc::new(Mutex::new(HashMap::new()));
     let active_ids: Arc<Mutex<HashSet<u64>>> = Arc::new(Mutex::new(HashSet::new()));
     let vehicle_events: Arc<Mutex<Vec<VehicleData>>> = Arc::new(Mutex::new(vec![]));
+    let mut summary = SimulationSummary::default();
+    // Every finished vehicle of a bounded run, kept for the end-of-run KPIs.
+    let mut run_events: Vec<VehicleData> = Vec::new();
+    let mut vehicle_tasks = Vec::new();
+    let mut tick: u64 = 0;
+    // Wall-clock seconds spent paused, excluded from the simulated elapsed time.
+    let mut paused_secs: u64 = 0;
+    // Simulated second of the previous tick, for per-tick rates.
+    let mut last_tick_elapsed: u64 = 0;
+    let mut steady_state = SteadyStateDetector::default();
+    let mut spawn_meter = SpawnMeter::new(config.spawn_metering.clone());
+    // Vehicles a ramp meter holds back are retried at the same entry on the next tick.
+    let mut ramp_meters = RampMeters::new(config.ramp_meters.clone());
+    // Per-entry demand profiles replace the global rush-hour curve when configured.
+    let demand_profiles =
+        config
+            .demand_profiles_path
+            .as_deref()
+            .and_then(|path| match DemandProfiles::load(path) {
+                Ok(profiles) => Some(profiles),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to load demand profiles from {}: {}. Using rush-hour curve.",
+                        path, e
+                    );
+                    None
+                }
+            });
+    let mut demand_carry: HashMap<IntersectionId, f64> = HashMap::new();
+    let event_log = match config.event_log_path.as_deref() {
+        Some(path) => EventLog::open(path).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to open event log {}: {}. Not recording events.",
+                path, e
+            );
+            EventLog::disabled()
+        }),
+        None => EventLog::disabled(),
+    };
+    let event_log = match event_stream {
+        Some(sender) => event_log.with_stream(sender),
+        None => event_log,
+    };
+    // Phase each traffic light was on at the previous tick, to log light changes.
+    let mut last_phases: HashMap<IntersectionId, usize> = HashMap::new();
+    let weather: SharedWeather = Arc::new(RwLock::new(weather_at(&config.weather_schedule, 0)));
+    let journey_ctx = JourneyContext {
+        active_ids: Arc::clone(&active_ids),
+        vehicle_events: Arc::clone(&vehicle_events),
+        time_scale,
+        paused: Arc::clone(&paused),
+        event_log: event_log.clone(),
+        journeys: Arc::clone(&journeys),
+        positions: Arc::clone(&positions),
+        weather: Arc::clone(&weather),
+        ..JourneyContext::new(
+            Arc::clone(&intersections),
+            Arc::clone(&lanes),
+            Arc::clone(&traffic_controller),
+            Arc::clone(&config),
+        )
+    };
 
-    let mut rabbit_connection = Connection::insecure_open(AMQP_URL).expect("RabbitMQ connection");
-    let publish_channel = rabbit_connection
-        .open_channel(None)
-        .expect("open publish channel");
-    let exchange = Exchange::direct(&publish_channel);
-    publish_channel
-        .queue_declare(QUEUE_TRAFFIC_DATA, QueueDeclareOptions::default())
-        .expect("declare traffic_data queue");
+    // Resume the journeys that were in flight when the snapshot was taken. Each vehicle re-enters
+    // the lane it was on and drives the rest of its route.
+    for active in initial_state.map(|s| s.active_vehicles).unwrap_or_default() {
+        let route: Vec<Lane> = active
+            .remaining_route
+            .iter()
+            .filter_map(|name| lanes.lock_lane(name).map(|lane| lane.clone()))
+            .collect();
+        let mut vehicle = active.vehicle;
+        vehicle.is_in_lane = false;
+        active_ids.lock().unwrap().insert(vehicle.id);
+        vehicle_tasks.push(tokio::spawn(simulate_vehicle_journey(
+            vehicle,
+            route,
+            SmallRng::seed_from_u64(rng.random()),
+            journey_ctx.clone(),
+        )));
+    }
+
+    // Adjustments made by the traffic light controller service, applied to this run's lights.
+    let signal_adjustments = match bus.subscribe(&queue_name(QUEUE_SIGNAL_ADJUSTMENTS)) {
+        Ok(subscription) => Some(subscription),
+        Err(e) => {
+            println!("Not applying light adjustments: {}", e);
+            None
+        }
+    };
 
-    loop {
+    // Publish failures are logged and the run carries on; repeated failures reopen the connection.
+    let mut publisher =
+        PublishRecovery::new().with_max_queue_depth(config.max_traffic_data_backlog);
+    // Vehicle events of traffic updates skipped for back-pressure, sent with the next update.
+    let mut deferred_vehicle_data = DeferredVehicleData::new(config.max_deferred_vehicle_events);
+
+    while max_ticks.is_none_or(|max| tick < max) {
+        // Hold here while paused; the loop resumes with a single tick rather than catching up.
+        if paused.load(Ordering::SeqCst) {
+            let pause_start = current_timestamp();
+            println!("Simulation paused.");
+            while paused.load(Ordering::SeqCst) {
+                sleep(Duration::from_millis(100)).await;
+            }
+            paused_secs += current_timestamp() - pause_start;
+            println!("Simulation resumed.");
+        }
+        tick += 1;
         // Calculate dynamic spawn count based on rush hour simulation.
-        let current_time = current_timestamp();
-        let spawn_count = simulate_rush_hour(simulation_start, current_time);
+        // Elapsed time is measured in simulated seconds so schedules follow the time scale.
+        let elapsed = scaled_elapsed_since(simulation_start + paused_secs, time_scale);
+        let current_weather = weather_at(&config.weather_schedule, elapsed);
+        if current_weather != *weather.read().unwrap() {
+            println!("Weather changed to {:?}.", current_weather);
+            *weather.write().unwrap() = current_weather;
+        }
+        if let Some(subscription) = &signal_adjustments {
+            apply_signal_adjustments(subscription, &traffic_controller, &run_id);
+        }
+        // One spawn slot per vehicle; `None` picks a random entry, `Some` a specific one.
+        let mut spawn_entries = scheduled_spawns(
+            &intersections.read().unwrap(),
+            demand_profiles.as_ref(),
+            &mut demand_carry,
+            &config,
+            elapsed,
+        );
+        // Vehicles waiting at a ramp meter go first. Hold back part of the batch, waiting vehicles
+        // included, while the grid is still congested from the last tick.
+        spawn_entries.splice(0..0, ramp_meters.take_held_back().into_iter().map(Some));
+        let metered = spawn_meter.effective_count(spawn_entries.len());
+        if metered < spawn_entries.len() {
+            println!(
+                "Congestion metering: spawning {} of {} scheduled vehicle(s).",
+                metered,
+                spawn_entries.len()
+            );
+        }
+        spawn_entries.truncate(metered);
         println!(
             "Elapsed time: {} sec - Spawning {} vehicle(s) this iteration.",
-            current_time - simulation_start,
-            spawn_count
+            elapsed,
+            spawn_entries.len()
         );
 
-        // Take snapshots of lanes and intersections.
-        let (lanes_snapshot, intersections_snapshot) = {
-            let lanes_guard = lanes.lock().unwrap();
-            let intersections_guard = intersections.lock().unwrap();
-            (lanes_guard.clone(), intersections_guard.clone())
-        };
+        // Apply any scheduled capacity changes (weather, incidents) for this point in time.
+        if !capacity_modifiers.is_empty() {
+            lanes.for_each_mut(|lane| {
+                apply_capacity_modifiers(std::slice::from_mut(lane), &capacity_modifiers, elapsed)
+            });
+        }
+
+        if let Some(path) = config.snapshot_path.as_deref() {
+            if tick.is_multiple_of(config.snapshot_interval_ticks.max(1)) {
+                let state = SimulationState::capture(
+                    &lanes.snapshot(),
+                    &intersections.read().unwrap(),
+                    &traffic_controller.read().unwrap(),
+                    next_vehicle_id,
+                    &journeys.lock().unwrap(),
+                );
+                if let Err(e) = state.save_snapshot(path) {
+                    eprintln!("Error saving snapshot to {}: {}", path, e);
+                }
+            }
+        }
+        {
+            let tc = traffic_controller.read().unwrap();
+            for (id, ctrl) in &tc.controllers {
+                if last_phases.insert(*id, ctrl.current_phase_index)
+                    != Some(ctrl.current_phase_index)
+                {
+                    event_log.record(SimulationEvent::LightChanged {
+                        intersection: *id,
+                        phase_index: ctrl.current_phase_index,
+                        green_lanes: ctrl
+                            .current_phase()
+                            .map_or_else(|| ctrl.all_lanes.clone(), |p| p.green_lanes.clone()),
+                    });
+                }
+            }
+        }
         // Extract vehicle events and clear the shared vector.
         let vehicle_data_snapshot = {
             let mut veh_ev = vehicle_events.lock().unwrap();
@@ -440,20 +1598,86 @@ pub async fn run_simulation(
             veh_ev.clear();
             data
         };
-        let current_traffic_data = collect_traffic_data(
-            &lanes_snapshot,
-            &intersections_snapshot,
+        summary.record_events(&vehicle_data_snapshot);
+        // Elapsed time has whole-second resolution, so a sub-second tick counts as its own length.
+        let tick_secs = (elapsed.saturating_sub(last_tick_elapsed) as f64)
+            .max(config.tick_duration().as_secs_f64());
+        last_tick_elapsed = elapsed;
+        let throughput = throughput_per_min(&vehicle_data_snapshot, tick_secs);
+        if max_ticks.is_some() {
+            run_events.extend(vehicle_data_snapshot.iter().cloned());
+        }
+        // Read lanes and intersections in place; nothing is cloned beyond the statistics.
+        let current_traffic_data = collect_traffic_data_from_store(
+            &lanes,
+            &intersections.read().unwrap(),
             vehicle_data_snapshot,
+            config.occupancy_metric,
         );
 
-        // Spawn a batch of vehicles concurrently.
-        for _ in 0..spawn_count {
-            if let Some((vehicle, route)) = spawn_vehicle(
-                &intersections,
-                &lanes,
-                &current_traffic_data,
-                &mut next_vehicle_id,
-            ) {
+        // Pick the whole batch under one intersections lock, routing over one shared lane snapshot.
+        let batch = {
+            let routable = routable_lanes(lanes.snapshot(), &current_traffic_data, &config);
+            let intersections_guard = intersections.read().unwrap();
+            let mut batch = Vec::with_capacity(spawn_entries.len());
+            // Dispatched emergency vans go first and bypass spawn and ramp metering.
+            let requests: Vec<DispatchRequest> = dispatches
+                .as_ref()
+                .map(|rx| rx.try_iter().collect())
+                .unwrap_or_default();
+            for request in requests {
+                if let Some((vehicle, route)) = dispatch_emergency_vehicle(
+                    &intersections_guard,
+                    &routable,
+                    &mut next_vehicle_id,
+                    &mut rng,
+                    &config,
+                    request,
+                ) {
+                    println!(
+                        "Dispatched emergency vehicle {} from {} to {}.",
+                        vehicle.id, request.entry, request.exit
+                    );
+                    active_ids.lock().unwrap().insert(vehicle.id);
+                    let vehicle_rng = SmallRng::seed_from_u64(rng.random());
+                    batch.push((vehicle, route, vehicle_rng));
+                }
+            }
+            for spawn_entry in spawn_entries {
+                let Some(entry_id) =
+                    spawn_entry.or_else(|| random_entry(&intersections_guard, &mut rng))
+                else {
+                    continue;
+                };
+                // A metered entry that is out of quota keeps its vehicle waiting at the ramp,
+                // decided before anything is drawn for the vehicle itself.
+                let entry_name = intersections_guard
+                    .iter()
+                    .find(|i| i.id == entry_id)
+                    .map(|i| i.name.as_str())
+                    .unwrap_or_default();
+                if !ramp_meters.has_quota(entry_name, elapsed) {
+                    if !ramp_meters.hold_back(entry_id, entry_name) {
+                        println!(
+                            "Ramp meter at {} has a full queue; dropping a waiting vehicle ({} so far).",
+                            entry_name,
+                            ramp_meters.dropped()
+                        );
+                    }
+                    continue;
+                }
+                let Some((vehicle, route)) = spawn_vehicle_from(
+                    &intersections_guard,
+                    &routable,
+                    &mut next_vehicle_id,
+                    &mut rng,
+                    &config,
+                    Some(entry_id),
+                    config.hour_at(elapsed),
+                ) else {
+                    continue;
+                };
+                ramp_meters.try_release(entry_name, elapsed);
                 {
                     let mut active = active_ids.lock().unwrap();
                     if active.contains(&vehicle.id) {
@@ -461,45 +1685,213 @@ pub async fn run_simulation(
                     }
                     active.insert(vehicle.id);
                 }
-                println!(
-                    "Spawned vehicle {:?} {} from {:?} to {:?}. Route: {:?}",
-                    vehicle.vehicle_type,
-                    vehicle.id,
-                    vehicle.entry_point,
-                    vehicle.exit_point,
-                    route.iter().map(|l| l.name.clone()).collect::<Vec<_>>()
-                );
-                let intersections_clone = Arc::clone(&intersections);
-                let lanes_clone = Arc::clone(&lanes);
-                let tc_clone = Arc::clone(&traffic_controller);
-                let active_ids_clone = Arc::clone(&active_ids);
-                let vehicle_events_clone = Arc::clone(&vehicle_events);
-                tokio::spawn(simulate_vehicle_journey(
-                    vehicle,
-                    route,
-                    intersections_clone,
-                    lanes_clone,
-                    tc_clone,
-                    active_ids_clone,
-                    vehicle_events_clone,
-                ));
+                // Derive a per-vehicle rng from the master rng to keep crash rolls reproducible.
+                let vehicle_rng = SmallRng::seed_from_u64(rng.random());
+                batch.push((vehicle, route, vehicle_rng));
             }
+            batch
+        };
+
+        // Spawn the batch of vehicles concurrently.
+        for (vehicle, route, vehicle_rng) in batch {
+            println!(
+                "Spawned vehicle {:?} {} from {:?} to {:?}. Route: {:?}",
+                vehicle.vehicle_type,
+                vehicle.id,
+                vehicle.entry_point,
+                vehicle.exit_point,
+                route.iter().map(|l| l.name.clone()).collect::<Vec<_>>()
+            );
+            event_log.record(SimulationEvent::VehicleSpawned {
+                vehicle_id: vehicle.id,
+                vehicle_type: format!("{:?}", vehicle.vehicle_type),
+                entry: vehicle.entry_point,
+                exit: vehicle.exit_point,
+                route: route.iter().map(|l| l.name.clone()).collect(),
+            });
+            summary.total_spawned += 1;
+            vehicle_tasks.push(tokio::spawn(simulate_vehicle_journey(
+                vehicle,
+                route,
+                vehicle_rng,
+                journey_ctx.clone(),
+            )));
         }
 
-        let update = TrafficUpdate {
+        // Check whether the network has stabilized and announce it once.
+        let average_congestion = mean_congestion(&current_traffic_data);
+        spawn_meter.observe(average_congestion);
+        let active_vehicles = active_ids.lock().unwrap().len();
+        if steady_state.observe(average_congestion, active_vehicles) {
+            let event = SteadyStateReached {
+                timestamp: current_timestamp(),
+                elapsed_secs: elapsed,
+                average_congestion,
+                active_vehicles,
+                run_id: run_id.clone(),
+            };
+            println!("Steady state reached: {:?}", event);
+            match serde_json::to_vec(&event) {
+                Ok(payload) => {
+                    publisher.publish(bus, &queue_name(QUEUE_SIMULATION_EVENTS), &payload);
+                }
+                Err(err) => {
+                    println!("Error serializing simulation event: {}", err);
+                }
+            }
+        }
+
+        let mut update = TrafficUpdate {
             current_data: current_traffic_data,
             timestamp: current_timestamp(),
+            run_id: run_id.clone(),
+            throughput_per_min: throughput,
         };
-        match serde_json::to_vec(&update) {
-            Ok(payload) => {
-                if let Err(err) = exchange.publish(Publish::new(&payload, QUEUE_TRAFFIC_DATA)) {
-                    println!("Error publishing traffic_data: {}", err);
+        let traffic_queue = queue_name(QUEUE_TRAFFIC_DATA);
+        // The last tick of a bounded run publishes regardless, so nothing deferred is left behind.
+        let last_tick = max_ticks.is_some_and(|max| tick >= max);
+        if !last_tick && publisher.is_backlogged(bus, &traffic_queue) {
+            // The next update supersedes this snapshot, but its crashes and arrivals must still
+            // reach the analyzer.
+            let dropped = deferred_vehicle_data.defer(&mut update.current_data.vehicle_data);
+            if dropped > 0 {
+                println!(
+                    "Dropped the {} oldest deferred vehicle event(s) while '{}' is backlogged ({} so far).",
+                    dropped,
+                    traffic_queue,
+                    deferred_vehicle_data.dropped()
+                );
+            }
+        } else {
+            let deferred = deferred_vehicle_data.len();
+            update
+                .current_data
+                .vehicle_data
+                .splice(0..0, deferred_vehicle_data.take());
+            let sent = match serde_json::to_vec(&update) {
+                Ok(payload) => publisher.publish(bus, &traffic_queue, &payload),
+                Err(err) => {
+                    println!("Error serializing update: {}", err);
+                    false
+                }
+            };
+            if !sent && deferred > 0 {
+                println!(
+                    "{} deferred vehicle event(s) were lost with an unsent traffic update.",
+                    deferred
+                );
+            }
+        }
+        // Drop handles of vehicles that already finished so the list doesn't grow unbounded.
+        vehicle_tasks.retain(|handle| !handle.is_finished());
+        sleep(scale_duration(config.tick_duration(), time_scale)).await;
+    }
+
+    // Bounded run finished: let every in-flight vehicle complete its journey before summarizing.
+    for handle in vehicle_tasks {
+        if let Err(err) = handle.await {
+            println!("Vehicle task failed: {}", err);
+        }
+    }
+    update_loop.abort();
+    let remaining_events = std::mem::take(&mut *vehicle_events.lock().unwrap());
+    summary.record_events(&remaining_events);
+    run_events.extend(remaining_events);
+    println!("Simulation finished after {} ticks: {:?}", tick, summary);
+    println!("Run summary: {:?}", summarize_run(&run_events));
+    let peaks: Vec<String> = peak_queue_lengths(&lanes)
+        .into_iter()
+        .map(|(name, len)| format!("{}: {}", name, len))
+        .collect();
+    println!("Peak queue length per lane: {}", peaks.join(", "));
+    if ramp_meters.dropped() > 0 {
+        println!(
+            "Ramp meters dropped {} vehicle(s) that found a full queue.",
+            ramp_meters.dropped()
+        );
+    }
+    if deferred_vehicle_data.dropped() > 0 {
+        println!(
+            "Back-pressure dropped {} deferred vehicle event(s) before they were published.",
+            deferred_vehicle_data.dropped()
+        );
+    }
+    summary
+}
+
+// Applies the light adjustments waiting on `subscription` to the simulation's own lights.
+// Adjustments tagged with another run are skipped; untagged ones (made ahead of predicted
+// congestion) apply to any run. Returns how many were applied.
+pub fn apply_signal_adjustments(
+    subscription: &Subscription,
+    traffic_controller: &RwLock<TrafficLightController>,
+    run_id: &str,
+) -> usize {
+    let mut applied = 0;
+    while let Some(payload) = subscription.try_next() {
+        subscription.ack();
+        let adjustment = match serde_json::from_slice::<LightAdjustment>(&payload) {
+            Ok(adjustment) => adjustment,
+            Err(e) => {
+                println!("Ignoring malformed light adjustment: {}", e);
+                continue;
+            }
+        };
+        if !adjustment.run_id.is_empty() && adjustment.run_id != run_id {
+            continue;
+        }
+        if traffic_controller
+            .write()
+            .unwrap()
+            .apply_light_adjustment(&adjustment)
+        {
+            applied += 1;
+        }
+    }
+    applied
+}
+
+// Every lane's longest queue over the run, longest first (ties by lane name).
+pub fn peak_queue_lengths(lanes: &LaneStore) -> Vec<(String, usize)> {
+    let mut peaks: Vec<(String, usize)> = lanes
+        .lock_all()
+        .iter()
+        .map(|lane| (lane.name.clone(), lane.max_queue_len))
+        .collect();
+    peaks.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    peaks
+}
+
+// Consumes SimulationControl messages from the admin CLI and toggles the shared pause flag.
+// Blocking; intended to be run with spawn_blocking alongside the simulation.
+pub fn listen_simulation_control(paused: Arc<AtomicBool>) -> AmiquipResult<()> {
+    let mut connection = connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+    let channel = connection.open_channel(None)?;
+    let queue = channel.queue_declare(
+        queue_name(QUEUE_SIMULATION_CONTROL),
+        QueueDeclareOptions::default(),
+    )?;
+    let consumer = queue.consume(ConsumerOptions::default())?;
+    for message in consumer.receiver() {
+        match message {
+            ConsumerMessage::Delivery(delivery) => {
+                if let Ok(control) = serde_json::from_slice::<SimulationControl>(&delivery.body) {
+                    println!(
+                        "[Simulation] Received control command: {:?}",
+                        control.command
+                    );
+                    match control.command {
+                        SimulationCommand::Pause => paused.store(true, Ordering::SeqCst),
+                        SimulationCommand::Resume => paused.store(false, Ordering::SeqCst),
+                    }
                 }
+                consumer.ack(delivery)?;
             }
-            Err(err) => {
-                println!("Error serializing update: {}", err);
+            other => {
+                println!("[Simulation] Control consumer ended: {:?}", other);
+                break;
             }
         }
-        sleep(Duration::from_millis(1000)).await;
     }
+    connection.close()
 }
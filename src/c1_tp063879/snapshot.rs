@@ -0,0 +1,165 @@
+// snapshot.rs
+use crate::c1_tp063879::intersections::{Intersection, IntersectionId};
+use crate::c1_tp063879::lanes::Lane;
+use crate::c1_tp063879::vehicles::Vehicle;
+use crate::c3_tp063987::traffic_light_controller::TrafficLightController;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Serializable stand-in for an in-flight vehicle journey: the vehicle as it is now and the names
+// of the lanes it still has to drive, starting with the lane it is currently on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveVehicle {
+    pub vehicle: Vehicle,
+    pub remaining_route: Vec<String>,
+}
+
+// Journeys in progress, keyed by vehicle id. Each vehicle task keeps its own entry up to date.
+pub type ActiveJourneys = Arc<Mutex<HashMap<u64, ActiveVehicle>>>;
+
+// Where an active vehicle is right now, for live views of the network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VehicleProgress {
+    pub lane: String,
+    // How far along `lane` the vehicle is, from 0.0 (just entered) to 1.0 (at the far end).
+    pub fraction_traversed: f64,
+    // Held at a red light or behind an accident.
+    pub waiting: bool,
+    // Wall-clock start and (time-scaled) length of the current traversal, once it has begun.
+    traversal: Option<(Instant, Duration)>,
+}
+
+impl VehicleProgress {
+    pub fn entered(lane: &str) -> Self {
+        Self {
+            lane: lane.to_string(),
+            fraction_traversed: 0.0,
+            waiting: false,
+            traversal: None,
+        }
+    }
+
+    pub fn start_traversal(&mut self, duration: Duration) {
+        self.waiting = false;
+        self.traversal = Some((Instant::now(), duration));
+    }
+
+    fn refreshed(&self) -> Self {
+        let mut progress = self.clone();
+        if let Some((started, duration)) = self.traversal {
+            progress.fraction_traversed = if duration.is_zero() {
+                1.0
+            } else {
+                (started.elapsed().as_secs_f64() / duration.as_secs_f64()).min(1.0)
+            };
+        }
+        progress
+    }
+}
+
+// Current position of every active vehicle, keyed by vehicle id. Each vehicle task keeps its own
+// entry up to date and removes it when the vehicle arrives or crashes.
+pub type VehiclePositions = Arc<Mutex<HashMap<u64, VehicleProgress>>>;
+
+// Copy of every active vehicle's position, with the fraction traversed brought up to date.
+pub fn snapshot_positions(positions: &VehiclePositions) -> HashMap<u64, VehicleProgress> {
+    positions
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, progress)| (*id, progress.refreshed()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControllerPhaseState {
+    pub intersection_id: IntersectionId,
+    pub current_phase_index: usize,
+    pub elapsed_in_phase: u64,
+    #[serde(default)]
+    pub extra_green_secs: u64,
+}
+
+// Everything needed to resume a run: the network, the light timings and the vehicles on the road.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationState {
+    pub lanes: Vec<Lane>,
+    pub intersections: Vec<Intersection>,
+    pub controller_phases: Vec<ControllerPhaseState>,
+    pub next_vehicle_id: u64,
+    pub active_vehicles: Vec<ActiveVehicle>,
+}
+
+impl SimulationState {
+    pub fn capture(
+        lanes: &[Lane],
+        intersections: &[Intersection],
+        controller: &TrafficLightController,
+        next_vehicle_id: u64,
+        journeys: &HashMap<u64, ActiveVehicle>,
+    ) -> Self {
+        let mut controller_phases: Vec<ControllerPhaseState> = controller
+            .controllers
+            .iter()
+            .map(|(id, ctrl)| ControllerPhaseState {
+                intersection_id: *id,
+                current_phase_index: ctrl.current_phase_index,
+                elapsed_in_phase: ctrl.elapsed_in_phase,
+                extra_green_secs: ctrl.extra_green_secs,
+            })
+            .collect();
+        controller_phases.sort_by_key(|p| (p.intersection_id.0, p.intersection_id.1));
+        let mut active_vehicles: Vec<ActiveVehicle> = journeys.values().cloned().collect();
+        active_vehicles.sort_by_key(|a| a.vehicle.id);
+        Self {
+            lanes: lanes.to_vec(),
+            intersections: intersections.to_vec(),
+            controller_phases,
+            next_vehicle_id,
+            active_vehicles,
+        }
+    }
+
+    // Puts every traffic light back on the phase it was on when the snapshot was taken.
+    pub fn restore_controller(&self, controller: &mut TrafficLightController) {
+        for phase in &self.controller_phases {
+            if let Some(ctrl) = controller.controllers.get_mut(&phase.intersection_id) {
+                if phase.current_phase_index < ctrl.phases.len() {
+                    ctrl.current_phase_index = phase.current_phase_index;
+                    ctrl.elapsed_in_phase = phase.elapsed_in_phase;
+                    ctrl.extra_green_secs = phase.extra_green_secs;
+                }
+            }
+        }
+    }
+
+    // Lanes to resume with. Vehicle queues are emptied because the restored journeys re-enter
+    // their current lane; crashed vehicles still waiting for clearance are dropped.
+    pub fn restored_lanes(&self) -> Vec<Lane> {
+        let mut lanes = self.lanes.clone();
+        for lane in &mut lanes {
+            lane.vehicle_queue.clear();
+            lane.current_vehicle_length = 0.0;
+            lane.has_emergency_vehicle = false;
+            lane.has_accident = false;
+            lane.accident_severity = 0;
+            lane.emergency_reservations = 0;
+        }
+        lanes
+    }
+
+    pub fn save_snapshot(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn load_snapshot(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
@@ -0,0 +1,155 @@
+// stepwise.rs
+use crate::c1_tp063879::intersections::{Intersection, IntersectionControl};
+use crate::c1_tp063879::lanes::Lane;
+use crate::c1_tp063879::simulation::assign_route;
+use crate::c1_tp063879::vehicles::Vehicle;
+use crate::c3_tp063987::traffic_light_controller::{LightState, TrafficLightController};
+use crate::shared_data::VehicleData;
+
+// A vehicle driven by the stepwise runner and the lanes it still has to cover, current lane first.
+#[derive(Debug, Clone)]
+pub struct SteppedVehicle {
+    pub vehicle: Vehicle,
+    pub route: Vec<Lane>,
+}
+
+// Everything the stepwise runner moves, owned in one place so no locks or tasks are involved.
+// Vehicles are only added explicitly and never crash, so a run is fully deterministic.
+pub struct StepwiseState {
+    pub intersections: Vec<Intersection>,
+    pub lanes: Vec<Lane>,
+    pub controller: TrafficLightController,
+    // In-flight vehicles, in the order they were added (and are moved each step).
+    pub vehicles: Vec<SteppedVehicle>,
+    // Vehicles that reached their exit, in arrival order.
+    pub finished: Vec<VehicleData>,
+    pub steps: u64,
+}
+
+impl StepwiseState {
+    pub fn new(intersections: Vec<Intersection>, lanes: Vec<Lane>) -> Self {
+        let controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+        Self {
+            intersections,
+            lanes,
+            controller,
+            vehicles: Vec::new(),
+            finished: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    // Queues a vehicle at its entry; it enters the first lane of `route` on the next step.
+    // Returns false (and keeps nothing) if the route doesn't start where the vehicle is.
+    pub fn add_vehicle(&mut self, vehicle: Vehicle, route: Vec<Lane>) -> bool {
+        let mut assigned = Vec::new();
+        if !assign_route(&vehicle, &mut assigned, route) {
+            return false;
+        }
+        self.vehicles.push(SteppedVehicle {
+            vehicle,
+            route: assigned,
+        });
+        true
+    }
+
+    // Name of the lane the vehicle is on, or None if it is waiting to enter one or has arrived.
+    pub fn vehicle_lane(&self, vehicle_id: u64) -> Option<&str> {
+        self.vehicles
+            .iter()
+            .find(|v| v.vehicle.id == vehicle_id && v.vehicle.is_in_lane)
+            .and_then(|v| v.route.first())
+            .map(|lane| lane.name.as_str())
+    }
+}
+
+// Advances the simulation `steps` times without spawning any tasks. Each step runs one traffic
+// light update, then moves every vehicle in turn by at most one lane: a vehicle on a lane leaves
+// it (arriving if that was its last lane) and then tries to enter the next one. Entering needs a
+// green light and room on the lane, exactly as in simulate_vehicle_journey; emergency vehicles
+// override a red light. A vehicle that can't enter waits one second.
+pub fn run_simulation_stepwise(state: &mut StepwiseState, steps: u64) {
+    for _ in 0..steps {
+        state.steps += 1;
+        state.controller.update_all();
+        let mut arrived = Vec::new();
+        for (index, stepped) in state.vehicles.iter_mut().enumerate() {
+            if stepped.vehicle.is_in_lane {
+                leave_lane(&mut state.lanes, stepped);
+                if stepped.route.is_empty() {
+                    arrived.push(index);
+                    continue;
+                }
+            }
+            if !enter_lane(
+                &state.intersections,
+                &mut state.lanes,
+                &mut state.controller,
+                stepped,
+            ) {
+                stepped.vehicle.current_speed = 0.0;
+                stepped.vehicle.record_wait(1);
+            }
+        }
+        for index in arrived.into_iter().rev() {
+            let stepped = state.vehicles.remove(index);
+            state.finished.push(VehicleData {
+                id: stepped.vehicle.id,
+                waiting_time: stepped.vehicle.waiting_time,
+                accident_timestamp: None,
+                severity: 0,
+                current_lane: "".to_string(),
+                collided_with: None,
+                fuel_used: stepped.vehicle.fuel_used,
+            });
+        }
+    }
+}
+
+fn leave_lane(lanes: &mut [Lane], stepped: &mut SteppedVehicle) {
+    let current = stepped.route.remove(0);
+    if let Some(lane) = lanes.iter_mut().find(|l| l.name == current.name) {
+        lane.remove_vehicle(&stepped.vehicle);
+    }
+    stepped.vehicle.is_in_lane = false;
+    stepped.vehicle.record_distance(current.length_meters);
+    stepped.vehicle.current_intersection = current.to;
+}
+
+// Moves the vehicle onto the first lane of its route if the light and the lane allow it.
+fn enter_lane(
+    intersections: &[Intersection],
+    lanes: &mut [Lane],
+    controller: &mut TrafficLightController,
+    stepped: &mut SteppedVehicle,
+) -> bool {
+    let Some(next) = stepped.route.first() else {
+        return false;
+    };
+    let has_light = intersections
+        .iter()
+        .any(|i| i.id == next.from && i.control == IntersectionControl::TrafficLight);
+    if has_light {
+        // Same rule as the async journey: a vehicle already rolling clears a yellow light.
+        let can_move = match controller.lane_signal(next.from, &next.name) {
+            LightState::Green => true,
+            LightState::Yellow => stepped.vehicle.current_speed > 0.0,
+            LightState::Red => false,
+        };
+        if !can_move {
+            if !stepped.vehicle.is_emergency() {
+                return false;
+            }
+            controller.set_emergency_override_route(next.from, vec![next.name.clone()]);
+        }
+    }
+    let Some(lane) = lanes.iter_mut().find(|l| l.name == next.name) else {
+        return false;
+    };
+    if !lane.add_vehicle(&stepped.vehicle) {
+        return false;
+    }
+    stepped.vehicle.is_in_lane = true;
+    stepped.vehicle.current_speed = stepped.vehicle.speed;
+    true
+}
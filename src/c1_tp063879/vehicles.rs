@@ -1,6 +1,13 @@
 use crate::c1_tp063879::intersections::IntersectionId;
+use crate::c1_tp063879::weather::Weather;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// Fuel model (litres) for a car; other vehicle types scale it by `fuel_multiplier`.
+const IDLE_FUEL_PER_SEC: f64 = 0.0003;
+const FUEL_PER_METER: f64 = 0.00007;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VehicleType {
     Car,
     Bus,
@@ -8,20 +15,82 @@ pub enum VehicleType {
     EmergencyVan,
 }
 
-#[derive(Debug, Clone)]
+// Relative spawn weights per vehicle type; they don't need to sum to 1.0. This is the single
+// vehicle mix used by every spawner. The default is 70% cars, 20% trucks, 9% buses and 1%
+// emergency vans.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VehicleWeights {
+    pub car: f64,
+    pub truck: f64,
+    pub bus: f64,
+    pub emergency_van: f64,
+}
+
+impl Default for VehicleWeights {
+    fn default() -> Self {
+        Self {
+            car: 0.70,
+            truck: 0.20,
+            bus: 0.09,
+            emergency_van: 0.01,
+        }
+    }
+}
+
+impl VehicleWeights {
+    // Maps a uniform roll in [0, 1) onto a vehicle type according to the weights.
+    // All-zero (or negative) weights fall back to a car.
+    pub fn pick(&self, roll: f64) -> VehicleType {
+        let car = self.car.max(0.0);
+        let truck = self.truck.max(0.0);
+        let bus = self.bus.max(0.0);
+        let total = car + truck + bus + self.emergency_van.max(0.0);
+        if total <= 0.0 {
+            return VehicleType::Car;
+        }
+        let value = roll * total;
+        if value < car {
+            VehicleType::Car
+        } else if value < car + truck {
+            VehicleType::Truck
+        } else if value < car + truck + bus {
+            VehicleType::Bus
+        } else {
+            VehicleType::EmergencyVan
+        }
+    }
+}
+
+// Draws a vehicle type according to `weights`.
+pub fn random_vehicle_type<R: Rng + ?Sized>(rng: &mut R, weights: &VehicleWeights) -> VehicleType {
+    weights.pick(rng.random_range(0.0..1.0))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vehicle {
     pub id: u64,
     pub vehicle_type: VehicleType,
     pub entry_point: IntersectionId,
     pub exit_point: IntersectionId,
+    // Intersection the vehicle is currently at (advanced as each lane is completed).
+    pub current_intersection: IntersectionId,
+    // Top speed the vehicle accelerates towards on each lane.
     pub speed: f64,
+    // Rate the vehicle speeds up at (speed units per second) and its speed right now.
+    pub acceleration: f64,
+    pub current_speed: f64,
     pub length: f64,
     pub is_in_lane: bool,
     pub is_accident: bool,
     pub severity: i8,
     pub accident_timestamp: Option<u64>,
+    // Id of the other vehicle involved if the accident was a two-vehicle collision.
+    pub collided_with: Option<u64>,
     pub waiting_time: u64,
     pub waiting_start: Option<u64>,
+    // Litres burned so far, idling while waiting and driving along lanes.
+    pub fuel_used: f64,
 }
 
 impl Vehicle {
@@ -38,23 +107,95 @@ impl Vehicle {
             VehicleType::Truck => 4.0,
             VehicleType::EmergencyVan => 3.0,
         };
+        let acceleration = match vehicle_type {
+            VehicleType::Car => 40.0,
+            VehicleType::Bus => 20.0,
+            VehicleType::Truck => 15.0,
+            VehicleType::EmergencyVan => 60.0,
+        };
 
         Self {
             id,
             vehicle_type,
             entry_point,
             exit_point,
+            current_intersection: entry_point,
             speed,
+            acceleration,
+            current_speed: 0.0,
             length,
             is_in_lane: false,
             is_accident: false,
             severity: 0,
             accident_timestamp: None,
+            collided_with: None,
             waiting_time: 0,
             waiting_start: None,
+            fuel_used: 0.0,
+        }
+    }
+
+    // Time to cover `lane_length` starting at `entry_speed`, accelerating at a constant rate
+    // until top speed is reached and cruising from there on.
+    pub fn time_to_traverse(&self, lane_length: f64, entry_speed: f64) -> f64 {
+        self.time_to_traverse_in(lane_length, entry_speed, Weather::Clear)
+    }
+
+    // Same as time_to_traverse with top speed and acceleration scaled down for the weather.
+    pub fn time_to_traverse_in(&self, lane_length: f64, entry_speed: f64, weather: Weather) -> f64 {
+        let top_speed = self.speed * weather.speed_factor();
+        let acceleration = self.acceleration * weather.speed_factor();
+        let v0 = entry_speed.clamp(0.0, top_speed);
+        if acceleration <= 0.0 || v0 >= top_speed {
+            return lane_length / top_speed;
+        }
+        let accel_distance = (top_speed * top_speed - v0 * v0) / (2.0 * acceleration);
+        if accel_distance >= lane_length {
+            // Top speed isn't reached on this lane: solve L = v0*t + a*t^2/2.
+            ((v0 * v0 + 2.0 * acceleration * lane_length).sqrt() - v0) / acceleration
+        } else {
+            let accel_time = (top_speed - v0) / acceleration;
+            accel_time + (lane_length - accel_distance) / top_speed
         }
     }
 
+    // Speed at the end of a lane of `lane_length` entered at `entry_speed`, capped at top speed.
+    pub fn exit_speed(&self, lane_length: f64, entry_speed: f64) -> f64 {
+        self.exit_speed_in(lane_length, entry_speed, Weather::Clear)
+    }
+
+    pub fn exit_speed_in(&self, lane_length: f64, entry_speed: f64, weather: Weather) -> f64 {
+        let top_speed = self.speed * weather.speed_factor();
+        let acceleration = self.acceleration * weather.speed_factor();
+        let v0 = entry_speed.clamp(0.0, top_speed);
+        if acceleration <= 0.0 {
+            return v0;
+        }
+        (v0 * v0 + 2.0 * acceleration * lane_length)
+            .sqrt()
+            .min(top_speed)
+    }
+
+    pub fn fuel_multiplier(&self) -> f64 {
+        match self.vehicle_type {
+            VehicleType::Car => 1.0,
+            VehicleType::Bus => 3.0,
+            VehicleType::Truck => 3.5,
+            VehicleType::EmergencyVan => 1.5,
+        }
+    }
+
+    // Adds waited seconds to the vehicle's waiting time and burns idle fuel for them.
+    pub fn record_wait(&mut self, secs: u64) {
+        self.waiting_time += secs;
+        self.fuel_used += secs as f64 * IDLE_FUEL_PER_SEC * self.fuel_multiplier();
+    }
+
+    // Burns fuel for driving `meters`.
+    pub fn record_distance(&mut self, meters: f64) {
+        self.fuel_used += meters * FUEL_PER_METER * self.fuel_multiplier();
+    }
+
     // Returns true if the vehicle is an emergency vehicle.
     pub fn is_emergency(&self) -> bool {
         self.vehicle_type == VehicleType::EmergencyVan
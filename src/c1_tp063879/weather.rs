@@ -0,0 +1,55 @@
+// weather.rs
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+// Network-wide weather. Clear leaves speeds and crash odds unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+    Fog,
+}
+
+impl Weather {
+    // Multiplier on vehicles' top speed and acceleration.
+    pub fn speed_factor(&self) -> f64 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 0.85,
+            Weather::Snow => 0.6,
+            Weather::Fog => 0.75,
+        }
+    }
+
+    // Multiplier on the per-lane crash and collision probabilities.
+    pub fn crash_factor(&self) -> f64 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 1.3,
+            Weather::Snow => 1.8,
+            Weather::Fog => 1.5,
+        }
+    }
+}
+
+// From `start_secs` (simulated seconds since the start) the weather becomes `weather`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WeatherChange {
+    pub start_secs: u64,
+    pub weather: Weather,
+}
+
+// Current weather, updated by the simulation loop and read by every vehicle task.
+pub type SharedWeather = Arc<RwLock<Weather>>;
+
+// Weather at `elapsed_secs` under `schedule`: the latest change that has started, Clear before any.
+pub fn weather_at(schedule: &[WeatherChange], elapsed_secs: u64) -> Weather {
+    schedule
+        .iter()
+        .filter(|c| c.start_secs <= elapsed_secs)
+        .max_by_key(|c| c.start_secs)
+        .map(|c| c.weather)
+        .unwrap_or_default()
+}
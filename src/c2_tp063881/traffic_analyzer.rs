@@ -1,16 +1,18 @@
+use crate::amqp::congestion_routing_key;
+use crate::bus::{AmiquipBus, BusResult, MessageBus};
 use crate::global_variables::{
-    AMQP_URL, QUEUE_CONGESTION_ALERTS, QUEUE_TRAFFIC_DATA, QUEUE_TRAFFIC_EVENTS,
+    amqp_url, queue_name, QUEUE_SPEEDING_ALERTS, QUEUE_TRAFFIC_DATA, QUEUE_TRAFFIC_EVENTS,
+    QUEUE_TRAFFIC_PREDICTIONS,
 };
+use crate::health::{Liveness, SharedLiveness};
 use crate::shared_data::{
-    current_timestamp, AccidentInfo, CongestionAlert, TrafficData, TrafficEvent, TrafficUpdate,
-};
-use amiquip::{
-    Connection, ConsumerMessage, ConsumerOptions, Exchange, Publish, QueueDeclareOptions,
-    Result as AmiquipResult,
+    current_timestamp, matches_run_filter, AccidentInfo, CongestionAlert, SpeedingHotspotAlert,
+    TrafficData, TrafficEvent, TrafficUpdate,
 };
 use serde_json;
 use std::sync::{Arc, Mutex}; 
 use std::collections::{HashMap, VecDeque};
+use tokio::sync::watch;
 use tokio::{self, task, time::Duration};
 
 #[derive(Debug, Clone)]
@@ -31,10 +33,7 @@ impl HistoricalData {
 
     pub fn update_occupancy(&mut self, data: &TrafficData) {
         for (int_id, &occ) in &data.intersection_congestion {
-            let deque = self
-                .occupancy_history
-                .entry(int_id.clone())
-                .or_insert_with(VecDeque::new);
+            let deque = self.occupancy_history.entry(int_id.clone()).or_default();
             if deque.len() == self.capacity {
                 deque.pop_front();
             }
@@ -44,10 +43,7 @@ impl HistoricalData {
 
     pub fn update_waiting_time(&mut self, waiting_times: &HashMap<String, f64>) {
         for (int_id, &wt) in waiting_times {
-            let deque = self
-                .waiting_time_history
-                .entry(int_id.clone())
-                .or_insert_with(VecDeque::new);
+            let deque = self.waiting_time_history.entry(int_id.clone()).or_default();
             if deque.len() == self.capacity {
                 deque.pop_front();
             }
@@ -76,45 +72,252 @@ impl HistoricalData {
     }
 }
 
+// Intersections above this congestion level raise an alert, unless configured otherwise.
+pub const ALERT_CONGESTION_THRESHOLD: f64 = 0.50;
+// Predicted lane congestion above which the traffic light controller gives the lane's phase extra
+// green, unless configured otherwise.
+pub const HIGH_LANE_CONGESTION_THRESHOLD: f64 = 0.80;
+
+// Lanes where more than this share of recent vehicles were overspeeding raise a speeding alert,
+// once at least SPEEDING_MIN_SAMPLES vehicles have used them, unless configured otherwise.
+pub const SPEEDING_HOTSPOT_THRESHOLD: f64 = 0.5;
+pub const SPEEDING_MIN_SAMPLES: usize = 5;
+
+// Which congestion figure of an intersection is compared against the alert threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CongestionRule {
+    // Average occupancy over all outgoing lanes.
+    Average,
+    // Average occupancy over the outgoing lanes of the busiest heading, so one jammed approach
+    // alerts even if the others are empty. Updates without directional figures use the average.
+    MaxDirection,
+}
+
+// Levels every analyzer detector compares against, so they can be tuned in one place.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalyzerThresholds {
+    // Intersection congestion above which a congestion alert is raised; also what counts as an
+    // alerting intersection in the congestion index. `congestion_rule` picks the figure compared.
+    pub intersection_congestion: f64,
+    pub congestion_rule: CongestionRule,
+    // Predicted lane congestion above which the controller lengthens that lane's phase. The
+    // controller also extends green ahead of intersections predicted above
+    // `intersection_congestion`.
+    pub lane_congestion: f64,
+    // Share of overspeeding vehicles above which a lane is a speeding hotspot.
+    pub speeding_ratio: f64,
+    // Vehicles a lane needs to have seen before it can be a speeding hotspot.
+    pub speeding_min_samples: usize,
+}
+
+impl Default for AnalyzerThresholds {
+    fn default() -> Self {
+        Self {
+            intersection_congestion: ALERT_CONGESTION_THRESHOLD,
+            congestion_rule: CongestionRule::MaxDirection,
+            lane_congestion: HIGH_LANE_CONGESTION_THRESHOLD,
+            speeding_ratio: SPEEDING_HOTSPOT_THRESHOLD,
+            speeding_min_samples: SPEEDING_MIN_SAMPLES,
+        }
+    }
+}
+
+// Weights of the congestion index components. Each component is in [0, 1] and the weights
+// sum to 1.0, so the index spans 0 (empty network) to 100 (every lane full, every
+// intersection alerting).
+#[derive(Debug, Clone, Copy)]
+pub struct CongestionIndexWeights {
+    // Mean lane occupancy across the network.
+    pub average_occupancy: f64,
+    // Occupancy of the fullest lane, so a single jammed corridor still shows up.
+    pub max_occupancy: f64,
+    // Share of intersections above the congestion alert threshold.
+    pub alert_intersections: f64,
+}
+
+impl Default for CongestionIndexWeights {
+    fn default() -> Self {
+        Self {
+            average_occupancy: 0.5,
+            max_occupancy: 0.3,
+            alert_intersections: 0.2,
+        }
+    }
+}
+
+// Single 0-100 gauge of how congested the whole network is, using the default weights and
+// thresholds.
+pub fn compute_congestion_index(data: &TrafficData) -> f64 {
+    compute_congestion_index_with(
+        data,
+        &CongestionIndexWeights::default(),
+        &AnalyzerThresholds::default(),
+    )
+}
+
+pub fn compute_congestion_index_with(
+    data: &TrafficData,
+    weights: &CongestionIndexWeights,
+    thresholds: &AnalyzerThresholds,
+) -> f64 {
+    let occupancies: Vec<f64> = data
+        .lane_occupancy
+        .values()
+        .map(|o| o.clamp(0.0, 1.0))
+        .collect();
+    let (avg_occupancy, max_occupancy) = if occupancies.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (
+            occupancies.iter().sum::<f64>() / occupancies.len() as f64,
+            occupancies.iter().cloned().fold(0.0, f64::max),
+        )
+    };
+    let alert_share = if data.intersection_congestion.is_empty() {
+        0.0
+    } else {
+        let alerting = congestion_by_rule(data, thresholds.congestion_rule)
+            .filter(|&(_, c)| c > thresholds.intersection_congestion)
+            .count();
+        alerting as f64 / data.intersection_congestion.len() as f64
+    };
+
+    let total_weight =
+        weights.average_occupancy + weights.max_occupancy + weights.alert_intersections;
+    if total_weight <= 0.0 {
+        return 0.0;
+    }
+    let index = (weights.average_occupancy * avg_occupancy
+        + weights.max_occupancy * max_occupancy
+        + weights.alert_intersections * alert_share)
+        / total_weight
+        * 100.0;
+    // round to 2 decimal places
+    (index * 100.0).round() / 100.0
+}
+
+// Each intersection's congestion as judged by `rule`.
+fn congestion_by_rule(
+    data: &TrafficData,
+    rule: CongestionRule,
+) -> impl Iterator<Item = (&String, f64)> {
+    data.intersection_congestion
+        .iter()
+        .map(move |(int_id, &average)| {
+            let congestion = match rule {
+                CongestionRule::Average => average,
+                CongestionRule::MaxDirection => data
+                    .intersection_max_direction_congestion
+                    .get(int_id)
+                    .copied()
+                    .unwrap_or(average),
+            };
+            (int_id, congestion)
+        })
+}
+
 // Analyze congestion from intersection data.
-pub fn analyze_traffic_data(data: &TrafficData) -> Vec<CongestionAlert> {
+pub fn analyze_traffic_data(
+    data: &TrafficData,
+    thresholds: &AnalyzerThresholds,
+) -> Vec<CongestionAlert> {
     let ts = current_timestamp();
     let mut alerts = Vec::new();
-    for (int_id, &cong) in &data.intersection_congestion {
-        if cong > 0.50 {
+    for (int_id, cong) in congestion_by_rule(data, thresholds.congestion_rule) {
+        if cong > thresholds.intersection_congestion {
             alerts.push(CongestionAlert {
                 timestamp: ts,
                 intersection: Some(int_id.clone()),
                 message: format!("Intersection {} is heavily congested ({:.2})", int_id, cong),
                 congestion_perc: cong,
                 recommended_action: "Adjust traffic light timings to avoid congestion.".to_string(),
+                run_id: String::new(),
             });
         }
     }
     alerts
 }
 
-pub fn analyze_traffic_events(update: TrafficUpdate) -> TrafficEvent {
+// Find lanes where speeding is common.
+pub fn analyze_speeding(
+    data: &TrafficData,
+    thresholds: &AnalyzerThresholds,
+) -> Vec<SpeedingHotspotAlert> {
+    let ts = current_timestamp();
+    let mut alerts: Vec<SpeedingHotspotAlert> = data
+        .lane_speeds
+        .iter()
+        .filter(|(_, stats)| {
+            stats.samples >= thresholds.speeding_min_samples
+                && stats.overspeed_ratio > thresholds.speeding_ratio
+        })
+        .map(|(lane, stats)| SpeedingHotspotAlert {
+            timestamp: ts,
+            lane: lane.clone(),
+            overspeed_ratio: stats.overspeed_ratio,
+            speed_p85: stats.speed_p85,
+            samples: stats.samples,
+            run_id: String::new(),
+        })
+        .collect();
+    alerts.sort_by(|a, b| a.lane.cmp(&b.lane));
+    alerts
+}
+
+// Decimal places the published TrafficEvent's averages and totals are rounded to.
+pub const EVENT_DECIMAL_PLACES: u32 = 2;
+
+// Rounds to `decimals` places; None keeps full precision.
+fn round_to(value: f64, decimals: Option<u32>) -> f64 {
+    match decimals {
+        Some(decimals) => {
+            let factor = 10f64.powi(decimals as i32);
+            (value * factor).round() / factor
+        }
+        None => value,
+    }
+}
+
+// Summarizes an update into a TrafficEvent. The average delay and total fuel are rounded to
+// `decimals` places, or left unrounded with None.
+pub fn analyze_traffic_events(
+    update: TrafficUpdate,
+    thresholds: &AnalyzerThresholds,
+    decimals: Option<u32>,
+) -> TrafficEvent {
     let ts = current_timestamp();
+    let congestion_index = compute_congestion_index_with(
+        &update.current_data,
+        &CongestionIndexWeights::default(),
+        thresholds,
+    );
     let mut total_delay = 0;
     let mut count_delay = 0;
     let mut accident_list = Vec::new();
+    let mut total_fuel_used = 0.0;
 
     for v in &update.current_data.vehicle_data {
         total_delay += v.waiting_time;
         count_delay += 1;
+        total_fuel_used += v.fuel_used;
         if let Some(ts) = v.accident_timestamp {
+            // Both vehicles of a collision report it; only the lower id's entry is counted.
+            if v.collided_with.is_some_and(|other| other < v.id) {
+                continue;
+            }
+            let mut involved_vehicle_ids = vec![v.id];
+            involved_vehicle_ids.extend(v.collided_with);
             accident_list.push(AccidentInfo {
                 vehicle_id: v.id,
                 accident_timestamp: ts,
                 severity: v.severity,
                 current_lane: v.current_lane.clone(),
+                involved_vehicle_ids,
             });
         }
     }
     let avg_delay = if count_delay > 0 {
-        // round to 2 decimal places
-        (total_delay as f64 / count_delay as f64 * 100.0).round() / 100.0
+        round_to(total_delay as f64 / count_delay as f64, decimals)
     } else {
         0.0
     };
@@ -124,6 +327,10 @@ pub fn analyze_traffic_events(update: TrafficUpdate) -> TrafficEvent {
         average_vehicle_delay: avg_delay,
         total_accidents: accident_list.len(),
         accident_details: accident_list,
+        congestion_index,
+        total_fuel_used: round_to(total_fuel_used, decimals),
+        throughput_per_min: update.throughput_per_min,
+        run_id: update.run_id,
     }
 }
 
@@ -154,111 +361,181 @@ pub fn predict_future_traffic_weighted(
         intersection_congestion: new_congestion,
         intersection_waiting_time: new_waiting_time,
         vehicle_data: data.vehicle_data.clone(),
+        intersection_max_direction_congestion: data.intersection_max_direction_congestion.clone(),
+        lane_waiting_time: data.lane_waiting_time.clone(),
+        lane_speeds: data.lane_speeds.clone(),
     }
 }
 
-pub async fn start_analyzer_rabbitmq() -> AmiquipResult<()> {
-    task::spawn_blocking(|| -> AmiquipResult<()> {
+// Consumes TrafficUpdates and publishes alerts/events. With a `run_filter`, updates tagged
+// with a different run id are acknowledged and skipped. Setting `shutdown` to true closes the
+// connection and returns Ok.
+pub async fn start_analyzer_rabbitmq(
+    run_filter: Option<String>,
+    thresholds: AnalyzerThresholds,
+    liveness: SharedLiveness,
+    shutdown: watch::Receiver<bool>,
+) -> BusResult<()> {
+    task::spawn_blocking(move || -> BusResult<()> {
         let historical = Arc::new(Mutex::new(HistoricalData::new(10)));
         let latest_data = Arc::new(Mutex::new(None::<TrafficData>));
+        let bus = Arc::new(AmiquipBus::connect(&amqp_url())?);
 
-        // Spawn a separate thread that prints future traffic predictions every 5 seconds
+        // Spawn a separate task that publishes future traffic predictions every 10 seconds
         {
             let historical = Arc::clone(&historical);
             let latest_data = Arc::clone(&latest_data);
+            let bus = Arc::clone(&bus);
+            let shutdown = shutdown.clone();
             tokio::spawn(async move {
-                loop {
+                while !*shutdown.borrow() {
                     tokio::time::sleep(Duration::from_secs(10)).await;
-                    // Lock the historical data to check if we have sufficient samples.
-                    let hist = historical.lock().unwrap();
-                    let sufficient = hist.occupancy_history.values().any(|deque| deque.len() >= hist.capacity);
-            
-                    if sufficient {
-                        if let Some(ref current_data) = *latest_data.lock().unwrap() {
-                            // Use alpha = 0.7 for the weighted prediction
-                            let predicted = predict_future_traffic_weighted(current_data, &hist, 0.7);
-                            println!(
-                                "Future Traffic Prediction: Congestion: {:?}, Waiting Time: {:?}",
-                                predicted.intersection_congestion,
-                                predicted.intersection_waiting_time
-                            );
-                        }
+                    if let Err(e) = publish_prediction(&*bus, &historical, &latest_data) {
+                        eprintln!("[Analyzer] Error publishing prediction: {}", e);
                     }
                 }
             });
-            
         }
 
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
-        let channel = connection.open_channel(None)?;
-        let exchange = Exchange::direct(&channel);
-
-        let traffic_data_queue = channel.queue_declare(QUEUE_TRAFFIC_DATA, QueueDeclareOptions::default())?;
-        let consumer = traffic_data_queue.consume(ConsumerOptions::default())?;
-
-        println!("[Analyzer] Waiting for TrafficUpdate on 'traffic_data'...");
-
-        channel.queue_declare(QUEUE_CONGESTION_ALERTS, QueueDeclareOptions::default())?;
-        channel.queue_declare(QUEUE_TRAFFIC_EVENTS, QueueDeclareOptions::default())?;
-
-        for message in consumer.receiver() {
-            println!("Received message from simulation to flow analyzer");
-
-            match message {
-                ConsumerMessage::Delivery(delivery) => {
-                    if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
-                        if let Ok(update) = serde_json::from_str::<TrafficUpdate>(json_str) {
-                            {
-                                let mut hist = historical.lock().unwrap();
-                                hist.update_occupancy(&update.current_data);
-                                hist.update_waiting_time(&update.current_data.intersection_waiting_time);
-                            }
-
-                            {
-                                let mut ld = latest_data.lock().unwrap();
-                                *ld = Some(update.current_data.clone());
-                            }
-
-                            let alerts = analyze_traffic_data(&update.current_data);
-                            if !alerts.is_empty() {
-                                for alert in &alerts {
-                                    if let Ok(alert_json) = serde_json::to_string(alert) {
-                                        exchange.publish(Publish::new(
-                                            alert_json.as_bytes(),
-                                            QUEUE_CONGESTION_ALERTS,
-                                        ))?;
-                                    }
-                                }
-                                println!(
-                                    "[Analyzer] Published {} congestion alerts to 'congestion_alerts'",
-                                    alerts.len()
-                                );
-                            }
-
-                            let traffic_event = analyze_traffic_events(update);
-                            if let Ok(event_json) = serde_json::to_string(&traffic_event) {
-                                exchange.publish(Publish::new(
-                                    event_json.as_bytes(),
-                                    QUEUE_TRAFFIC_EVENTS,
-                                ))?;
-                                println!(
-                                    "[Analyzer] Published TrafficEvent to 'traffic_events': {:?}",
-                                    traffic_event
-                                );
-                            }
-                        }
-                    }
-                    consumer.ack(delivery)?;
-                }
-                other => {
-                    println!("[Analyzer] Consumer ended: {:?}", other);
-                    break;
-                }
-            }
-        }
-
-        connection.close()
+        run_analyzer(
+            &*bus,
+            &run_filter,
+            &thresholds,
+            &liveness,
+            &shutdown,
+            &historical,
+            &latest_data,
+        )
     })
     .await
     .unwrap()
 }
+
+// Publishes a weighted prediction of the latest TrafficData to the traffic_predictions queue, once
+// the history holds a full window for at least one intersection. Returns whether one was sent.
+pub fn publish_prediction(
+    bus: &dyn MessageBus,
+    historical: &Mutex<HistoricalData>,
+    latest_data: &Mutex<Option<TrafficData>>,
+) -> BusResult<bool> {
+    let predicted = {
+        // Lock the historical data to check if we have sufficient samples.
+        let hist = historical.lock().unwrap();
+        let sufficient = hist
+            .occupancy_history
+            .values()
+            .any(|deque| deque.len() >= hist.capacity);
+        if !sufficient {
+            return Ok(false);
+        }
+        let latest = latest_data.lock().unwrap();
+        let Some(current_data) = latest.as_ref() else {
+            return Ok(false);
+        };
+        // Use alpha = 0.7 for the weighted prediction
+        predict_future_traffic_weighted(current_data, &hist, 0.7)
+    };
+    println!(
+        "Future Traffic Prediction: Congestion: {:?}, Waiting Time: {:?}",
+        predicted.intersection_congestion, predicted.intersection_waiting_time
+    );
+    let json = serde_json::to_string(&predicted)?;
+    bus.publish(&queue_name(QUEUE_TRAFFIC_PREDICTIONS), json.as_bytes())?;
+    Ok(true)
+}
+
+// The analyzer loop over any transport: every TrafficUpdate on the traffic_data queue is
+// analyzed until `shutdown` is set or the subscription ends.
+pub fn run_analyzer(
+    bus: &dyn MessageBus,
+    run_filter: &Option<String>,
+    thresholds: &AnalyzerThresholds,
+    liveness: &Mutex<Liveness>,
+    shutdown: &watch::Receiver<bool>,
+    historical: &Mutex<HistoricalData>,
+    latest_data: &Mutex<Option<TrafficData>>,
+) -> BusResult<()> {
+    let subscription = bus.subscribe(&queue_name(QUEUE_TRAFFIC_DATA))?;
+    println!("[Analyzer] Waiting for TrafficUpdate on 'traffic_data'...");
+    while let Some(payload) = subscription.next_until(shutdown) {
+        println!("Received message from simulation to flow analyzer");
+        liveness.lock().unwrap().record_message();
+        handle_traffic_update(
+            bus,
+            &payload,
+            run_filter,
+            thresholds,
+            historical,
+            latest_data,
+        )?;
+        // Only now is the update safe to drop from the queue; a failure above leaves it for
+        // redelivery.
+        subscription.ack();
+    }
+    Ok(())
+}
+
+// Analyzes one TrafficUpdate payload: records it in the history, publishes a congestion alert
+// per congested intersection and a TrafficEvent. Payloads that aren't a TrafficUpdate are ignored.
+pub fn handle_traffic_update(
+    bus: &dyn MessageBus,
+    payload: &[u8],
+    run_filter: &Option<String>,
+    thresholds: &AnalyzerThresholds,
+    historical: &Mutex<HistoricalData>,
+    latest_data: &Mutex<Option<TrafficData>>,
+) -> BusResult<()> {
+    let Ok(update) = serde_json::from_slice::<TrafficUpdate>(payload) else {
+        return Ok(());
+    };
+    if !matches_run_filter(run_filter, &update.run_id) {
+        println!("[Analyzer] Ignoring update from run '{}'", update.run_id);
+        return Ok(());
+    }
+    {
+        let mut hist = historical.lock().unwrap();
+        hist.update_occupancy(&update.current_data);
+        hist.update_waiting_time(&update.current_data.intersection_waiting_time);
+    }
+    *latest_data.lock().unwrap() = Some(update.current_data.clone());
+
+    let mut alerts = analyze_traffic_data(&update.current_data, thresholds);
+    for alert in alerts.iter_mut() {
+        alert.run_id = update.run_id.clone();
+    }
+    if !alerts.is_empty() {
+        for alert in &alerts {
+            if let Ok(alert_json) = serde_json::to_string(alert) {
+                let routing_key = congestion_routing_key(alert.intersection.as_deref());
+                bus.publish_congestion_alert(&routing_key, alert_json.as_bytes())?;
+            }
+        }
+        println!(
+            "[Analyzer] Published {} congestion alerts to 'congestion_alerts_topic'",
+            alerts.len()
+        );
+    }
+
+    for mut alert in analyze_speeding(&update.current_data, thresholds) {
+        alert.run_id = update.run_id.clone();
+        if let Ok(alert_json) = serde_json::to_string(&alert) {
+            bus.publish(&queue_name(QUEUE_SPEEDING_ALERTS), alert_json.as_bytes())?;
+            println!(
+                "[Analyzer] Speeding hotspot on lane {}: {:.0}% overspeeding, 85th percentile {:.1} km/h",
+                alert.lane,
+                alert.overspeed_ratio * 100.0,
+                alert.speed_p85
+            );
+        }
+    }
+
+    let traffic_event = analyze_traffic_events(update, thresholds, Some(EVENT_DECIMAL_PLACES));
+    if let Ok(event_json) = serde_json::to_string(&traffic_event) {
+        bus.publish(&queue_name(QUEUE_TRAFFIC_EVENTS), event_json.as_bytes())?;
+        println!(
+            "[Analyzer] Published TrafficEvent to 'traffic_events': {:?}",
+            traffic_event
+        );
+    }
+    Ok(())
+}
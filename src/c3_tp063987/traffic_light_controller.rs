@@ -1,20 +1,67 @@
+use crate::amqp::{
+    connect_with_retry, declare_congestion_exchange, next_message, CONNECT_BASE_DELAY,
+    CONNECT_MAX_ATTEMPTS,
+};
 use crate::c1_tp063879::intersections::{Intersection, IntersectionControl, IntersectionId};
-use crate::c1_tp063879::lanes::Lane;
-use crate::global_variables::{AMQP_URL, QUEUE_CONGESTION_ALERTS, QUEUE_LIGHT_ADJUSTMENTS};
-use crate::shared_data::{current_timestamp, CongestionAlert, LightAdjustment};
+use crate::c1_tp063879::lanes::{Lane, LaneAxis};
+use crate::c2_tp063881::traffic_analyzer::AnalyzerThresholds;
+use crate::global_variables::{
+    amqp_url, queue_name, DEFAULT_CONGESTION_BINDING, EXCHANGE_CONGESTION_ALERTS,
+    QUEUE_CONTROLLER_ALERTS, QUEUE_LIGHT_ADJUSTMENTS, QUEUE_SIGNAL_ADJUSTMENTS,
+    QUEUE_TRAFFIC_PREDICTIONS,
+};
+use crate::health::SharedLiveness;
+use crate::shared_data::{
+    current_timestamp, scale_duration, CongestionAlert, LightAdjustment, TrafficData,
+    TrafficLightChanged,
+};
 use amiquip::{
-    Connection, ConsumerMessage, ConsumerOptions, Exchange, Publish, QueueDeclareOptions,
+    ConsumerMessage, ConsumerOptions, Exchange, FieldTable, Publish, QueueDeclareOptions,
     Result as AmiquipResult,
 };
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
 use tokio::task;
 use tokio::time::{sleep, Duration};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrafficLightPhase {
     pub green_lanes: Vec<String>,
     pub duration: u64, // Duration in seconds
+    // The last `yellow_secs` of the phase show yellow on its green lanes (0 = no yellow interval).
+    #[serde(default)]
+    pub yellow_secs: u64,
+}
+
+// Signal shown to a lane at a traffic light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightState {
+    Green,
+    Yellow,
+    Red,
+}
+
+// Timing plan of a single intersection, as exported by TrafficLightController::export_plan.
+// offset_secs is how far into the current phase the controller was at export time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntersectionPlan {
+    pub intersection_id: IntersectionId,
+    pub phases: Vec<TrafficLightPhase>,
+    pub current_phase_index: usize,
+    pub offset_secs: u64,
+}
+
+// All-red interval for pedestrians, inserted after every `every_n_cycles` full phase cycles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PedestrianPhase {
+    pub every_n_cycles: u64,
+    pub duration: u64,
 }
 
 pub struct IntersectionController {
@@ -22,10 +69,30 @@ pub struct IntersectionController {
     pub phases: Vec<TrafficLightPhase>,
     pub current_phase_index: usize,
     pub elapsed_in_phase: u64,
+    // Green added to the current phase by light adjustments. It is dropped when the phase ends,
+    // so the next cycle runs the configured durations again.
+    pub extra_green_secs: u64,
     pub all_lanes: Vec<String>,
     pub emergency_override: Option<Vec<String>>,
+    pub pedestrian_phase: Option<PedestrianPhase>,
+    pub completed_cycles: u64,
+    // Seconds left of an active pedestrian crossing; every lane is red while this is non-zero.
+    pub pedestrian_remaining: u64,
+    // Longest an emergency override may hold the phases before it is force-cleared, how long the
+    // current one has held them, and how many seconds new overrides are refused after a
+    // force-clear so the starved phase gets its green. This is a time-box on the override as a
+    // whole; it does not track how long each individual phase has gone without green.
+    pub max_override_secs: u64,
+    pub override_secs: u64,
+    pub override_cooldown: u64,
+    // Receives a TrafficLightChanged every time the signals switch, if set.
+    pub light_changes: Option<Sender<TrafficLightChanged>>,
 }
 
+// Longest an emergency override can keep the normal phases from cycling, unless configured
+// otherwise.
+pub const DEFAULT_MAX_OVERRIDE_SECS: u64 = 20;
+
 impl IntersectionController {
     pub fn new(
         intersection: Intersection,
@@ -37,26 +104,143 @@ impl IntersectionController {
             phases,
             current_phase_index: 0,
             elapsed_in_phase: 0,
+            extra_green_secs: 0,
             all_lanes,
             emergency_override: None,
+            pedestrian_phase: None,
+            completed_cycles: 0,
+            pedestrian_remaining: 0,
+            max_override_secs: DEFAULT_MAX_OVERRIDE_SECS,
+            override_secs: 0,
+            override_cooldown: 0,
+            light_changes: None,
+        }
+    }
+
+    pub fn with_light_changes(mut self, sender: Sender<TrafficLightChanged>) -> Self {
+        self.light_changes = Some(sender);
+        self
+    }
+
+    pub fn with_pedestrian_phase(mut self, every_n_cycles: u64, duration: u64) -> Self {
+        self.pedestrian_phase = Some(PedestrianPhase {
+            every_n_cycles: every_n_cycles.max(1),
+            duration,
+        });
+        self
+    }
+
+    pub fn is_pedestrian_crossing(&self) -> bool {
+        self.pedestrian_remaining > 0
+    }
+
+    // The phase that is on now. None when the controller has no phases, in which case the
+    // intersection behaves as uncontrolled: every lane is green and nothing cycles.
+    pub fn current_phase(&self) -> Option<&TrafficLightPhase> {
+        self.phases.get(self.current_phase_index)
+    }
+
+    // How long the current phase runs this time: its configured duration plus any green added
+    // by light adjustments. 0 without phases.
+    pub fn current_phase_duration(&self) -> u64 {
+        self.current_phase()
+            .map_or(0, |phase| phase.duration + self.extra_green_secs)
+    }
+
+    // Seconds until the lights next change (end of the current phase or pedestrian crossing).
+    pub fn secs_until_change(&self) -> u64 {
+        if self.is_pedestrian_crossing() {
+            self.pedestrian_remaining
+        } else {
+            self.current_phase_duration()
+                .saturating_sub(self.elapsed_in_phase)
         }
     }
 
     // Increases the elapsed time and cycles the phase if the current phase's duration is reached.
+    // After every configured number of full cycles an all-red pedestrian crossing is held before
+    // the first phase starts again. Every switch is reported on `light_changes`.
+    // An emergency override is time-boxed: once it has held the lights for `max_override_secs` it
+    // is force-cleared and the phase it interrupted restarts with its full duration.
     pub fn update(&mut self) {
         if self.emergency_override.is_some() {
-            // Do not cycle phases during emergency override.
+            // Do not cycle phases during emergency override, unless it has starved them too long.
+            self.override_secs += 1;
+            if self.override_secs < self.max_override_secs {
+                return;
+            }
+            let starved = self
+                .current_phase()
+                .map(|phase| phase.green_lanes.clone())
+                .unwrap_or_default();
+            println!(
+                "WARNING: Intersection {:?} held on emergency override for {} seconds, starving phase {} (green for {:?}); clearing it so normal phases can run",
+                self.intersection.id, self.override_secs, self.current_phase_index, starved
+            );
+            self.elapsed_in_phase = 0;
+            self.clear_emergency_override();
+            self.override_cooldown = self.current_phase().map_or(0, |phase| phase.duration);
+            return;
+        }
+        self.override_cooldown = self.override_cooldown.saturating_sub(1);
+        if self.phases.is_empty() {
+            return;
+        }
+        if self.is_pedestrian_crossing() {
+            self.pedestrian_remaining -= 1;
+            if !self.is_pedestrian_crossing() {
+                self.apply_current_phase();
+                self.notify_light_change();
+            }
             return;
         }
         self.elapsed_in_phase += 1;
-        let current_phase = &self.phases[self.current_phase_index];
-        if self.elapsed_in_phase >= current_phase.duration {
+        if self.elapsed_in_phase >= self.current_phase_duration() {
             self.elapsed_in_phase = 0;
+            self.extra_green_secs = 0;
             self.current_phase_index = (self.current_phase_index + 1) % self.phases.len();
+            if self.current_phase_index == 0 {
+                self.completed_cycles += 1;
+                if let Some(pedestrian) = self.pedestrian_phase {
+                    if pedestrian.duration > 0
+                        && self
+                            .completed_cycles
+                            .is_multiple_of(pedestrian.every_n_cycles)
+                    {
+                        self.pedestrian_remaining = pedestrian.duration;
+                        println!(
+                            "Intersection {:?} pedestrian crossing: all lanes red for {} seconds",
+                            self.intersection.id, pedestrian.duration
+                        );
+                        self.notify_light_change();
+                        return;
+                    }
+                }
+            }
             self.apply_current_phase();
+            self.notify_light_change();
         }
     }
 
+    // Sends the current signal state to `light_changes`. A dropped receiver is ignored.
+    fn notify_light_change(&self) {
+        let Some(sender) = &self.light_changes else {
+            return;
+        };
+        let is_green = if let Some(override_lanes) = &self.emergency_override {
+            !override_lanes.is_empty()
+        } else if self.is_pedestrian_crossing() {
+            false
+        } else {
+            self.current_phase()
+                .is_none_or(|phase| !phase.green_lanes.is_empty())
+        };
+        let _ = sender.send(TrafficLightChanged {
+            intersection_id: self.intersection.id.into(),
+            is_green,
+        });
+    }
+
     // Prints the currently active green and red lanes.
     pub fn apply_current_phase(&self) {
         if let Some(ref override_lanes) = self.emergency_override {
@@ -70,8 +254,8 @@ impl IntersectionController {
                 "Intersection {:?} EMERGENCY OVERRIDE: Green for lanes: {:?} and Red for lanes: {:?}",
                 self.intersection.id, override_lanes, red_lanes
             );
-        } else {
-            let current_green = &self.phases[self.current_phase_index].green_lanes;
+        } else if let Some(phase) = self.current_phase() {
+            let current_green = &phase.green_lanes;
             let red_lanes: Vec<String> = self
                 .all_lanes
                 .iter()
@@ -85,20 +269,26 @@ impl IntersectionController {
                 current_green,
                 red_lanes
             );
+        } else {
+            println!(
+                "Intersection {:?} has no phases: Green for all lanes: {:?}",
+                self.intersection.id, self.all_lanes
+            );
         }
     }
 
-    // Adjust phase durations based on predicted congestion values.
-
+    // Adjust phase durations based on predicted congestion values. A phase with a green lane
+    // predicted above `thresholds.lane_congestion` gets an extra second.
     pub fn adjust_phase_durations_based_on_prediction(
         &mut self,
         predicted_data: &HashMap<String, f64>,
+        thresholds: &AnalyzerThresholds,
     ) {
         for phase in self.phases.iter_mut() {
             let mut extra_seconds = 0;
             for lane in &phase.green_lanes {
                 if let Some(&congestion) = predicted_data.get(lane) {
-                    if congestion > 0.8 {
+                    if congestion > thresholds.lane_congestion {
                         extra_seconds = extra_seconds.max(1); // add extra 1 seconds if congestion is high
                     }
                 }
@@ -114,6 +304,65 @@ impl IntersectionController {
         }
     }
 
+    // Splits `total_cycle` seconds of green between the phases in proportion to each phase's
+    // demand: the mean `occupancy` of its green lanes (lanes missing from the map count as 0).
+    // Every phase keeps at least 1 second and the durations add up to `total_cycle` (as long as
+    // it allows the 1-second minimum). Without any demand the cycle is split evenly.
+    pub fn rebalance_phases(&mut self, occupancy: &HashMap<String, f64>, total_cycle: u64) {
+        let n = self.phases.len();
+        if n == 0 {
+            return;
+        }
+        let demands: Vec<f64> = self
+            .phases
+            .iter()
+            .map(|phase| {
+                if phase.green_lanes.is_empty() {
+                    return 0.0;
+                }
+                let sum: f64 = phase
+                    .green_lanes
+                    .iter()
+                    .map(|lane| occupancy.get(lane).copied().unwrap_or(0.0).max(0.0))
+                    .sum();
+                sum / phase.green_lanes.len() as f64
+            })
+            .collect();
+        let total_demand: f64 = demands.iter().sum();
+        let shares: Vec<f64> = if total_demand > 0.0 {
+            demands.iter().map(|d| d / total_demand).collect()
+        } else {
+            vec![1.0 / n as f64; n]
+        };
+
+        // Hand out the seconds above the 1-second minimums by share, then the rounding leftovers
+        // to the phases with the largest fractional parts.
+        let spare = total_cycle.saturating_sub(n as u64);
+        let exact: Vec<f64> = shares.iter().map(|s| s * spare as f64).collect();
+        let mut durations: Vec<u64> = exact.iter().map(|e| 1 + e.floor() as u64).collect();
+        let assigned: u64 = durations.iter().sum::<u64>() - n as u64;
+        let mut by_remainder: Vec<usize> = (0..n).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor()))
+        });
+        for &i in by_remainder
+            .iter()
+            .take(spare.saturating_sub(assigned) as usize)
+        {
+            durations[i] += 1;
+        }
+
+        for (phase, duration) in self.phases.iter_mut().zip(durations) {
+            if phase.duration != duration {
+                println!(
+                    "Rebalancing phase {:?} at intersection {:?} from {} to {} seconds",
+                    phase.green_lanes, self.intersection.id, phase.duration, duration
+                );
+                phase.duration = duration;
+            }
+        }
+    }
+
     // Admin function to directly set a phase's duration.
     pub fn set_phase_duration(&mut self, phase_index: usize, new_duration: u64) {
         if phase_index < self.phases.len() {
@@ -130,32 +379,83 @@ impl IntersectionController {
         }
     }
 
-    // Sets an emergency override for the intersection.
+    // Keeps the current green on for `add_seconds_green` more seconds, up to `max_green` in
+    // total. Only this run of the phase is lengthened; its configured duration is unchanged.
+    // A phase already running longer than `max_green` is left as it is.
+    pub fn extend_current_phase(&mut self, add_seconds_green: u64, max_green: u64) {
+        let Some(phase) = self.current_phase() else {
+            return;
+        };
+        let base = phase.duration;
+        let current = self.current_phase_duration();
+        let new_duration = (current + add_seconds_green).min(max_green).max(current);
+        println!(
+            "Extending phase {} at intersection {:?} from {} to {} seconds for this cycle",
+            self.current_phase_index, self.intersection.id, current, new_duration
+        );
+        self.extra_green_secs = new_duration - base;
+    }
+
+    // Sets an emergency override for the intersection. Refused while the phases are catching up
+    // after a starving override was force-cleared.
     pub fn set_emergency_override(&mut self, emergency_route: Vec<String>) {
+        if self.override_cooldown > 0 {
+            println!(
+                "Intersection {:?} refusing emergency override for {} more seconds after a forced clear",
+                self.intersection.id, self.override_cooldown
+            );
+            return;
+        }
+        let starting = self.emergency_override.is_none();
+        if starting {
+            self.override_secs = 0;
+        }
         self.emergency_override = Some(emergency_route);
         self.apply_current_phase();
+        if starting {
+            self.notify_light_change();
+        }
     }
 
     // Clears the emergency override.
     pub fn clear_emergency_override(&mut self) {
-        if self.emergency_override.is_some() {
+        let ending = self.emergency_override.is_some();
+        if ending {
             println!(
                 "Clearing emergency override for intersection {:?}",
                 self.intersection.id
             );
         }
         self.emergency_override = None;
+        self.override_secs = 0;
         self.apply_current_phase();
+        if ending {
+            self.notify_light_change();
+        }
     }
 }
 
+// Longest a phase can be stretched to by light adjustments, unless configured otherwise.
+pub const DEFAULT_MAX_GREEN_SECS: u64 = 30;
+// Green added in advance at an intersection predicted to congest (see apply_prediction).
+pub const PREDICTION_EXTENSION_SECS: u32 = 2;
+// How many recently applied adjustments are remembered to drop re-delivered duplicates.
+const RECENT_ADJUSTMENT_CAPACITY: usize = 64;
+
 pub struct TrafficLightController {
     pub controllers: HashMap<IntersectionId, IntersectionController>,
+    // Upper bound on a phase's duration after light adjustments.
+    pub max_green: u64,
+    // (timestamp, intersection) keys of the latest applied adjustments, oldest first.
+    recent_adjustments: VecDeque<(u64, String)>,
+    // Simulated time from light ticks that hasn't yet added up to a whole second of phase time.
+    tick_carry: Duration,
 }
 
 impl TrafficLightController {
     // Creates a controller for each intersection with traffic light control.
-    // Lanes are grouped into phases based on their orientation.
+    // Lanes are grouped into one phase per axis they run along (horizontal, vertical and either
+    // diagonal), so lanes that cross each other never share a green.
     pub fn initialize(intersections: Vec<Intersection>, lanes: &[Lane]) -> Self {
         let mut controllers = HashMap::new();
 
@@ -168,40 +468,25 @@ impl TrafficLightController {
                 if connected_lanes.is_empty() {
                     continue;
                 }
-                // Group lanes by orientation.
-                let horizontal: Vec<String> = connected_lanes
-                    .iter()
-                    .filter(|lane| lane.from.0 == lane.to.0)
-                    .map(|lane| lane.name.clone())
-                    .collect();
-                let vertical: Vec<String> = connected_lanes
-                    .iter()
-                    .filter(|lane| lane.from.1 == lane.to.1)
-                    .map(|lane| lane.name.clone())
-                    .collect();
+                // Group lanes by axis; BTreeMap keeps the phase order stable
+                // (horizontal, vertical, diagonal, anti-diagonal).
+                let mut by_axis: BTreeMap<LaneAxis, Vec<String>> = BTreeMap::new();
+                for lane in &connected_lanes {
+                    by_axis
+                        .entry(lane.axis())
+                        .or_default()
+                        .push(lane.name.clone());
+                }
 
-                let mut phases = Vec::new();
-                if !horizontal.is_empty() && !vertical.is_empty() {
-                    // Two phases: one for horizontal lanes and one for vertical lanes.
-                    phases.push(TrafficLightPhase {
-                        green_lanes: horizontal.clone(),
+                // One phase per axis; a single axis gives a single phase with all lanes.
+                let phases: Vec<TrafficLightPhase> = by_axis
+                    .into_values()
+                    .map(|green_lanes| TrafficLightPhase {
+                        green_lanes,
                         duration: 8, // most of the vehicles take around 1-8 seconds to travel from one intersection to another. vehicles with longer travel time will have to wait in the queue
-                    });
-                    phases.push(TrafficLightPhase {
-                        green_lanes: vertical.clone(),
-                        duration: 8,
-                    });
-                } else {
-                    // Single phase with all connected lanes.
-                    let all: Vec<String> = connected_lanes
-                        .iter()
-                        .map(|lane| lane.name.clone())
-                        .collect();
-                    phases.push(TrafficLightPhase {
-                        green_lanes: all,
-                        duration: 8,
-                    });
-                }
+                        yellow_secs: 0,
+                    })
+                    .collect();
 
                 // All lane names for display purposes.
                 let all_lane_names: Vec<String> = connected_lanes
@@ -213,7 +498,40 @@ impl TrafficLightController {
                 controllers.insert(intersection.id, controller);
             }
         }
-        Self { controllers }
+        Self {
+            controllers,
+            max_green: DEFAULT_MAX_GREEN_SECS,
+            recent_adjustments: VecDeque::with_capacity(RECENT_ADJUSTMENT_CAPACITY),
+            tick_carry: Duration::ZERO,
+        }
+    }
+
+    // Snapshot of every intersection's phases and timing, ordered by intersection id.
+    pub fn plan(&self) -> Vec<IntersectionPlan> {
+        let mut plan: Vec<IntersectionPlan> = self
+            .controllers
+            .iter()
+            .map(|(id, ctrl)| IntersectionPlan {
+                intersection_id: *id,
+                phases: ctrl.phases.clone(),
+                current_phase_index: ctrl.current_phase_index,
+                offset_secs: ctrl.elapsed_in_phase,
+            })
+            .collect();
+        plan.sort_by_key(|p| (p.intersection_id.0, p.intersection_id.1));
+        plan
+    }
+
+    // Writes the current timing plan for all intersections to `path` as JSON.
+    pub fn export_plan(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(&self.plan())?;
+        fs::write(path, json)?;
+        println!(
+            "Exported timing plan for {} intersections to {}",
+            self.controllers.len(),
+            path
+        );
+        Ok(())
     }
 
     // Calls update() on all individual intersection controllers.
@@ -223,17 +541,68 @@ impl TrafficLightController {
         }
     }
 
+    // Advances every light by `tick` of simulated time. Phase timers count whole seconds, so
+    // shorter ticks are carried over until they add up to one: two 500ms ticks make one update.
+    pub fn advance(&mut self, tick: Duration) {
+        self.tick_carry += tick;
+        while self.tick_carry >= Duration::from_secs(1) {
+            self.tick_carry -= Duration::from_secs(1);
+            self.update_all();
+        }
+    }
+
     // Checks if a given lane at an intersection is currently green.
     pub fn is_lane_green(&self, intersection_id: IntersectionId, lane_name: &str) -> bool {
+        self.lane_signal(intersection_id, lane_name) == LightState::Green
+    }
+
+    // Current signal for a lane. A phase's green lanes turn yellow for its last yellow_secs;
+    // an emergency override shows plain green/red.
+    pub fn lane_signal(&self, intersection_id: IntersectionId, lane_name: &str) -> LightState {
         if let Some(ctrl) = self.controllers.get(&intersection_id) {
             if let Some(ref override_lanes) = ctrl.emergency_override {
-                return override_lanes.contains(&lane_name.to_string());
+                return if override_lanes.iter().any(|l| l == lane_name) {
+                    LightState::Green
+                } else {
+                    LightState::Red
+                };
+            }
+            if ctrl.is_pedestrian_crossing() {
+                return LightState::Red;
+            }
+            let Some(current_phase) = ctrl.current_phase() else {
+                return LightState::Green;
+            };
+            if !current_phase.green_lanes.iter().any(|l| l == lane_name) {
+                return LightState::Red;
             }
-            let current_phase = &ctrl.phases[ctrl.current_phase_index];
-            return current_phase.green_lanes.contains(&lane_name.to_string());
+            let remaining = ctrl
+                .current_phase_duration()
+                .saturating_sub(ctrl.elapsed_in_phase);
+            return if remaining <= current_phase.yellow_secs {
+                LightState::Yellow
+            } else {
+                LightState::Green
+            };
         }
         // If intersection is not controlled by a traffic light, default to green.
-        true
+        LightState::Green
+    }
+
+    // Current signal of every lane at every traffic light, as lane_signal reports it. Lanes are
+    // listed in each controller's lane order.
+    pub fn current_states(&self) -> HashMap<IntersectionId, Vec<(String, LightState)>> {
+        self.controllers
+            .iter()
+            .map(|(id, ctrl)| {
+                let states = ctrl
+                    .all_lanes
+                    .iter()
+                    .map(|lane| (lane.clone(), self.lane_signal(*id, lane)))
+                    .collect();
+                (*id, states)
+            })
+            .collect()
     }
 
     // Sets an emergency override for a given intersection.
@@ -266,62 +635,212 @@ impl TrafficLightController {
         }
     }
 
+    // Gives the intersection's current green `add_seconds_green` more seconds. Every light
+    // adjustment, whether received over RabbitMQ or made in-process, goes through here.
+    // Returns false if the intersection has no traffic light.
+    pub fn apply_adjustment(
+        &mut self,
+        intersection_id: IntersectionId,
+        add_seconds_green: u32,
+    ) -> bool {
+        match self.controllers.get_mut(&intersection_id) {
+            Some(ctrl) => {
+                ctrl.extend_current_phase(add_seconds_green as u64, self.max_green);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn with_max_green(mut self, max_green: u64) -> Self {
+        self.max_green = max_green;
+        self
+    }
+
+    // Longest an emergency override may hold any intersection's phases (see
+    // IntersectionController::update).
+    pub fn with_max_override_secs(mut self, max_override_secs: u64) -> Self {
+        for ctrl in self.controllers.values_mut() {
+            ctrl.max_override_secs = max_override_secs;
+        }
+        self
+    }
+
+    // Sends every intersection's signal switches to `sender` (see IntersectionController::update).
+    pub fn with_light_changes(mut self, sender: Sender<TrafficLightChanged>) -> Self {
+        for ctrl in self.controllers.values_mut() {
+            ctrl.light_changes = Some(sender.clone());
+        }
+        self
+    }
+
+    // Applies a LightAdjustment message. Its intersection_id is the canonical (Display) form of
+    // the id, as used in CongestionAlert. A message with the same timestamp and intersection as one applied
+    // recently is a re-delivery and is ignored.
+    pub fn apply_light_adjustment(&mut self, adjustment: &LightAdjustment) -> bool {
+        let key = (adjustment.timestamp, adjustment.intersection_id.clone());
+        if self.recent_adjustments.contains(&key) {
+            println!(
+                "[TrafficController] Ignoring duplicate adjustment for {} at {}",
+                adjustment.intersection_id, adjustment.timestamp
+            );
+            return false;
+        }
+        if self.recent_adjustments.len() == RECENT_ADJUSTMENT_CAPACITY {
+            self.recent_adjustments.pop_front();
+        }
+        self.recent_adjustments.push_back(key);
+        let target = adjustment
+            .intersection_id
+            .parse::<IntersectionId>()
+            .ok()
+            .filter(|id| self.controllers.contains_key(id));
+        match target {
+            Some(id) => self.apply_adjustment(id, adjustment.add_seconds_green),
+            None => {
+                println!(
+                    "[TrafficController] No traffic light at {} to adjust",
+                    adjustment.intersection_id
+                );
+                false
+            }
+        }
+    }
+
+    // Extends the green at the intersection a congestion alert is about. Returns the adjustment
+    // made, or None if nothing changed: the alert names no intersection, the intersection has no
+    // traffic light, or the alert is a re-delivery.
+    pub fn respond_to_alert(&mut self, alert: &CongestionAlert) -> Option<LightAdjustment> {
+        let intersection_id = alert.intersection.clone()?;
+        // TODO: Temporarily, for demonstration, a fixed additional duration.
+        // Keyed by the alert's timestamp so a re-delivered alert is recognised as a duplicate.
+        let adjustment = LightAdjustment {
+            timestamp: alert.timestamp,
+            intersection_id,
+            add_seconds_green: 5,
+            run_id: alert.run_id.clone(),
+        };
+        self.apply_light_adjustment(&adjustment)
+            .then_some(adjustment)
+    }
+
+    // Extends the current green at every intersection predicted to congest past
+    // `thresholds.intersection_congestion` by PREDICTION_EXTENSION_SECS (capped at max_green),
+    // before the congestion shows up. Returns the intersections that were adjusted.
+    pub fn apply_prediction(
+        &mut self,
+        predicted: &TrafficData,
+        thresholds: &AnalyzerThresholds,
+    ) -> Vec<IntersectionId> {
+        let mut adjusted: Vec<IntersectionId> = self
+            .controllers
+            .keys()
+            .copied()
+            .filter(|id| {
+                predicted
+                    .intersection_congestion
+                    .get(&id.to_string())
+                    .is_some_and(|&c| c > thresholds.intersection_congestion)
+            })
+            .collect();
+        adjusted.sort_by_key(|id| (id.0, id.1));
+        for id in &adjusted {
+            self.apply_adjustment(*id, PREDICTION_EXTENSION_SECS);
+        }
+        adjusted
+    }
+
     // Adjusts phase durations for an intersection based on predicted traffic data.
     pub fn adjust_phases_based_on_prediction(
         &mut self,
         intersection_id: IntersectionId,
         predicted_data: &HashMap<String, f64>,
+        thresholds: &AnalyzerThresholds,
     ) {
         if let Some(ctrl) = self.controllers.get_mut(&intersection_id) {
-            ctrl.adjust_phase_durations_based_on_prediction(predicted_data);
+            ctrl.adjust_phase_durations_based_on_prediction(predicted_data, thresholds);
         }
     }
 
     // Runs a dedicated update loop that periodically updates all traffic lights.
     // This function is intended to be spawned as an async task.
-    pub async fn run_update_loop(controller: Arc<Mutex<Self>>) {
+    // Each update advances the lights by `tick` of simulated time (see advance); time_scale
+    // shortens (or stretches) the real interval. While `paused` is set the lights hold their
+    // current phase.
+    pub async fn run_update_loop(
+        controller: Arc<RwLock<Self>>,
+        time_scale: f64,
+        paused: Arc<AtomicBool>,
+        tick: Duration,
+    ) {
         loop {
-            {
-                let mut ctrl = controller.lock().unwrap();
-                ctrl.update_all();
+            // Only the phase update itself needs exclusive access; readers run between ticks.
+            if !paused.load(Ordering::SeqCst) {
+                controller.write().unwrap().advance(tick);
             }
-            sleep(Duration::from_secs(1)).await;
+            sleep(scale_duration(tick, time_scale)).await;
         }
     }
 }
 
-pub async fn start_traffic_controller_rabbitmq() -> AmiquipResult<()> {
-    task::spawn_blocking(|| -> AmiquipResult<()> {
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
+// Consumes congestion alerts, extends the green time of the congested intersection on
+// `controller`, and publishes the adjustment for the monitoring system to log and for the
+// simulation to apply to its own lights (QUEUE_SIGNAL_ADJUSTMENTS).
+// Once `shutdown` is set to true the consumer closes its connection and returns Ok.
+pub async fn start_traffic_controller_rabbitmq(
+    controller: Arc<RwLock<TrafficLightController>>,
+    liveness: SharedLiveness,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
         let channel = connection.open_channel(None)?;
         let exchange = Exchange::direct(&channel);
-        let congestion_alert_queue =
-            channel.queue_declare(QUEUE_CONGESTION_ALERTS, QueueDeclareOptions::default())?;
+        // The controller reacts to every alert, so it binds its own queue to all routing keys.
+        let congestion_exchange = declare_congestion_exchange(&channel)?;
+        let congestion_alert_queue = channel.queue_declare(
+            queue_name(QUEUE_CONTROLLER_ALERTS),
+            QueueDeclareOptions::default(),
+        )?;
+        congestion_alert_queue.bind(
+            &congestion_exchange,
+            DEFAULT_CONGESTION_BINDING,
+            FieldTable::new(),
+        )?;
         let consumer = congestion_alert_queue.consume(ConsumerOptions::default())?;
-        println!("[TrafficController] Waiting for congestion alerts on 'congestion_alerts'...");
+        println!(
+            "[TrafficController] Waiting for congestion alerts on '{}'...",
+            EXCHANGE_CONGESTION_ALERTS
+        );
 
-        channel.queue_declare(QUEUE_LIGHT_ADJUSTMENTS, QueueDeclareOptions::default())?;
+        channel.queue_declare(
+            queue_name(QUEUE_LIGHT_ADJUSTMENTS),
+            QueueDeclareOptions::default(),
+        )?;
+        channel.queue_declare(
+            queue_name(QUEUE_SIGNAL_ADJUSTMENTS),
+            QueueDeclareOptions::default(),
+        )?;
 
-        for message in consumer.receiver() {
+        while let Some(message) = next_message(&consumer, &shutdown) {
             println!("Received message in TrafficController");
             match message {
                 ConsumerMessage::Delivery(delivery) => {
-                    let ts = current_timestamp();
+                    liveness.lock().unwrap().record_message();
                     if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
                         if let Ok(alert) = serde_json::from_str::<CongestionAlert>(json_str) {
                             println!("[TrafficController] Got CongestionAlert: {:?}", alert);
-                            if let Some(int_id) = alert.intersection {
-                                // TODO: Temporarily, for demonstration, publish a fixed additional duration adjustment.
-                                let adjustment = LightAdjustment {
-                                    timestamp: ts,
-                                    intersection_id: int_id.to_string(),
-                                    add_seconds_green: 5,
-                                };
+                            let adjustment = controller.write().unwrap().respond_to_alert(&alert);
+                            if let Some(adjustment) = adjustment {
                                 if let Ok(adj_json) = serde_json::to_string(&adjustment) {
-                                    exchange.publish(Publish::new(
-                                        adj_json.as_bytes(),
-                                        QUEUE_LIGHT_ADJUSTMENTS,
-                                    ))?;
+                                    for queue in [QUEUE_LIGHT_ADJUSTMENTS, QUEUE_SIGNAL_ADJUSTMENTS]
+                                    {
+                                        exchange.publish(Publish::new(
+                                            adj_json.as_bytes(),
+                                            queue_name(queue),
+                                        ))?;
+                                    }
                                     println!(
                                         "[TrafficController] Published LightAdjustment: {:?}",
                                         adjustment
@@ -343,3 +862,75 @@ pub async fn start_traffic_controller_rabbitmq() -> AmiquipResult<()> {
     .await
     .unwrap()
 }
+
+// Consumes the analyzer's traffic predictions, lengthens green time on `controller` at the
+// intersections predicted to congest, and publishes each extension on QUEUE_SIGNAL_ADJUSTMENTS
+// for the simulation to apply to its own lights. `thresholds` are the analyzer's, so the
+// controller acts on the same congestion levels the analyzer alerts on.
+// Once `shutdown` is set to true the listener closes its connection and returns Ok.
+pub async fn listen_traffic_predictions(
+    controller: Arc<RwLock<TrafficLightController>>,
+    thresholds: AnalyzerThresholds,
+    liveness: SharedLiveness,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+        let channel = connection.open_channel(None)?;
+        let exchange = Exchange::direct(&channel);
+        let queue = channel.queue_declare(
+            queue_name(QUEUE_TRAFFIC_PREDICTIONS),
+            QueueDeclareOptions::default(),
+        )?;
+        channel.queue_declare(
+            queue_name(QUEUE_SIGNAL_ADJUSTMENTS),
+            QueueDeclareOptions::default(),
+        )?;
+        let consumer = queue.consume(ConsumerOptions::default())?;
+        println!("[TrafficController] Waiting for traffic predictions on 'traffic_predictions'...");
+
+        while let Some(message) = next_message(&consumer, &shutdown) {
+            match message {
+                ConsumerMessage::Delivery(delivery) => {
+                    liveness.lock().unwrap().record_message();
+                    if let Ok(predicted) = serde_json::from_slice::<TrafficData>(&delivery.body) {
+                        let adjusted = controller
+                            .write()
+                            .unwrap()
+                            .apply_prediction(&predicted, &thresholds);
+                        if !adjusted.is_empty() {
+                            println!(
+                                "[TrafficController] Extended green ahead of predicted congestion at {:?}",
+                                adjusted
+                            );
+                        }
+                        let timestamp = current_timestamp();
+                        for id in adjusted {
+                            let adjustment = LightAdjustment {
+                                timestamp,
+                                intersection_id: id.to_string(),
+                                add_seconds_green: PREDICTION_EXTENSION_SECS,
+                                run_id: String::new(),
+                            };
+                            if let Ok(json) = serde_json::to_string(&adjustment) {
+                                exchange.publish(Publish::new(
+                                    json.as_bytes(),
+                                    queue_name(QUEUE_SIGNAL_ADJUSTMENTS),
+                                ))?;
+                            }
+                        }
+                    }
+                    consumer.ack(delivery)?;
+                }
+                other => {
+                    println!("[TrafficController] Prediction consumer ended: {:?}", other);
+                    break;
+                }
+            }
+        }
+        connection.close()
+    })
+    .await
+    .unwrap()
+}
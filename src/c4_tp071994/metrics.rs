@@ -0,0 +1,91 @@
+// metrics.rs
+use crate::http::{serve_get, HttpResponse};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+// Counters and gauges updated by the monitoring listeners as they process messages.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsState {
+    pub congestion_alerts_total: u64,
+    pub light_adjustments_total: u64,
+    pub traffic_data_total: u64,
+    pub traffic_events_total: u64,
+    pub accidents_total: u64,
+    // Average vehicle delay reported by the most recent traffic event.
+    pub average_delay_seconds: f64,
+}
+
+pub type SharedMetrics = Arc<Mutex<MetricsState>>;
+
+impl MetricsState {
+    // Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let metrics: [(&str, &str, &str, f64); 6] = [
+            (
+                "traffic_congestion_alerts_total",
+                "counter",
+                "Congestion alerts received.",
+                self.congestion_alerts_total as f64,
+            ),
+            (
+                "traffic_light_adjustments_total",
+                "counter",
+                "Light adjustments received.",
+                self.light_adjustments_total as f64,
+            ),
+            (
+                "traffic_data_updates_total",
+                "counter",
+                "Traffic data updates received.",
+                self.traffic_data_total as f64,
+            ),
+            (
+                "traffic_events_total",
+                "counter",
+                "Traffic events received.",
+                self.traffic_events_total as f64,
+            ),
+            (
+                "traffic_accidents_total",
+                "counter",
+                "Accidents reported in traffic events.",
+                self.accidents_total as f64,
+            ),
+            (
+                "traffic_average_delay_seconds",
+                "gauge",
+                "Average vehicle delay from the latest traffic event.",
+                self.average_delay_seconds,
+            ),
+        ];
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+}
+
+// Serves GET /metrics on the given port until the task is dropped. Any other path gets a 404.
+// Only failing to bind the port is returned as an error.
+pub async fn serve_metrics(metrics: SharedMetrics, port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("Serving Prometheus metrics on port {}", port);
+    serve_get(listener, "/metrics", move || HttpResponse {
+        status: "200 OK",
+        content_type: "text/plain; version=0.0.4",
+        body: metrics.lock().unwrap().render(),
+    })
+    .await;
+    Ok(())
+}
+
+// Port for the metrics endpoint from METRICS_PORT; the endpoint is disabled when unset.
+pub fn metrics_port_from_env() -> Option<u16> {
+    std::env::var("METRICS_PORT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+}
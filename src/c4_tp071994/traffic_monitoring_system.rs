@@ -1,22 +1,32 @@
+use crate::amqp::{
+    connect_with_retry, declare_congestion_exchange, next_message, CONNECT_BASE_DELAY,
+    CONNECT_MAX_ATTEMPTS,
+};
+use crate::bus::MessageBus;
+use crate::c1_tp063879::intersections::IntersectionId;
+use crate::c4_tp071994::metrics::SharedMetrics;
 use crate::global_variables::{
-    AMQP_URL, QUEUE_CONGESTION_ALERTS, QUEUE_LIGHT_ADJUSTMENTS, QUEUE_TRAFFIC_DATA,
-    QUEUE_TRAFFIC_EVENTS,
+    amqp_url, queue_name, QUEUE_CONGESTION_ALERTS, QUEUE_DEAD_LETTER, QUEUE_LIGHT_ADJUSTMENTS,
+    QUEUE_SIMULATION_CONTROL, QUEUE_TRAFFIC_DATA, QUEUE_TRAFFIC_EVENTS,
 };
 use crate::shared_data::{
-    current_timestamp, AccidentInfo, CongestionAlert, LightAdjustment, TrafficEvent,
+    current_timestamp, matches_run_filter, AccidentInfo, CongestionAlert, DeadLetter,
+    LightAdjustment, SimulationCommand, SimulationControl, TrafficEvent, TrafficUpdate,
 };
 use amiquip::{
-    Connection, ConsumerMessage, ConsumerOptions, Exchange, Publish, QueueDeclareOptions,
+    ConsumerMessage, ConsumerOptions, Exchange, FieldTable, Publish, QueueDeclareOptions,
     Result as AmiquipResult,
 };
 use plotters::prelude::*;
 use plotters::style::text_anchor::{HPos, Pos, VPos};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io::{stdin, stdout, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TrafficDataRecord {
@@ -24,37 +34,188 @@ pub struct TrafficDataRecord {
     pub raw_data: String,
 }
 
+// Where the monitoring system writes its CSV logs, charts and reports. Each path can be
+// overridden individually; `in_dir` places every file under one base directory.
+#[derive(Debug, Clone)]
+pub struct OutputPaths {
+    pub base_dir: PathBuf,
+    pub congestion_alerts: PathBuf,
+    pub light_adjustments: PathBuf,
+    pub traffic_data: PathBuf,
+    pub traffic_event: PathBuf,
+    pub accident_info: PathBuf,
+    pub intersection_metrics: PathBuf,
+    pub report: PathBuf,
+    pub congestion_heatmap: PathBuf,
+    pub traffic_events_scatterplot: PathBuf,
+    pub congestion_timeline: PathBuf,
+    pub accident_frequency: PathBuf,
+    // CSV logs at or above this size are rotated before the next write (None = never rotate).
+    pub max_csv_bytes: Option<u64>,
+    // Whether readers also include rotated copies (name.1.csv, name.2.csv, ...).
+    pub include_rotated: bool,
+}
+
+impl Default for OutputPaths {
+    // Today's filenames in the current working directory.
+    fn default() -> Self {
+        Self::in_dir(".")
+    }
+}
+
+impl OutputPaths {
+    pub fn in_dir(base_dir: impl Into<PathBuf>) -> Self {
+        let base_dir = base_dir.into();
+        Self {
+            congestion_alerts: base_dir.join("congestion_alerts.csv"),
+            light_adjustments: base_dir.join("light_adjustments.csv"),
+            traffic_data: base_dir.join("traffic_data.csv"),
+            traffic_event: base_dir.join("traffic_event.csv"),
+            accident_info: base_dir.join("accident_info.csv"),
+            intersection_metrics: base_dir.join("intersection_metrics.csv"),
+            report: base_dir.join("report.json"),
+            congestion_heatmap: base_dir.join("congestion_heatmap.png"),
+            traffic_events_scatterplot: base_dir.join("traffic_events_scatterplot.png"),
+            congestion_timeline: base_dir.join("congestion_timeline.png"),
+            accident_frequency: base_dir.join("accident_frequency.png"),
+            base_dir,
+            max_csv_bytes: None,
+            include_rotated: true,
+        }
+    }
+
+    // Uses MONITORING_OUTPUT_DIR as the base directory when set, creating it if needed, and
+    // MONITORING_MAX_CSV_BYTES as the rotation threshold.
+    pub fn from_env() -> Self {
+        let mut paths = match std::env::var("MONITORING_OUTPUT_DIR") {
+            Ok(dir) => {
+                if let Err(e) = fs::create_dir_all(&dir) {
+                    eprintln!("Could not create output directory {}: {}", dir, e);
+                }
+                Self::in_dir(dir)
+            }
+            Err(_) => Self::default(),
+        };
+        paths.max_csv_bytes = std::env::var("MONITORING_MAX_CSV_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok());
+        paths
+    }
+
+    // The files making up a CSV log, oldest first: rotated copies (if included), then the
+    // current file.
+    pub fn csv_log_files(&self, filename: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        if self.include_rotated {
+            files.extend(
+                (1..=rotated_count(filename))
+                    .rev()
+                    .map(|n| rotated_path(filename, n)),
+            );
+        }
+        files.push(filename.to_path_buf());
+        files
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrafficEventSummary {
     pub timestamp: u64,
     pub average_vehicle_delay: f64,
     pub total_accidents: usize,
+    #[serde(default)]
+    pub congestion_index: f64,
+    #[serde(default)]
+    pub total_fuel_used: f64,
+    #[serde(default)]
+    pub throughput_per_min: f64,
+}
+
+// One intersection's state from one traffic_data update, in long format so an intersection's
+// history can be rebuilt by filtering on intersection_id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntersectionMetricsRecord {
+    pub timestamp: u64,
+    pub intersection_id: String,
+    pub congestion: f64,
+    pub waiting_time: f64,
+}
+
+// Flat CSV form of AccidentInfo; involved vehicle ids are joined with ';'.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccidentRecord {
+    pub vehicle_id: u64,
+    pub accident_timestamp: u64,
+    pub severity: i8,
+    pub current_lane: String,
+    #[serde(default)]
+    pub involved_vehicle_ids: String,
+}
+
+impl From<AccidentInfo> for AccidentRecord {
+    fn from(info: AccidentInfo) -> Self {
+        Self {
+            vehicle_id: info.vehicle_id,
+            accident_timestamp: info.accident_timestamp,
+            severity: info.severity,
+            current_lane: info.current_lane,
+            involved_vehicle_ids: info
+                .involved_vehicle_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(";"),
+        }
+    }
 }
 
 // Listens to the "congestion_alerts" queue and logs each incoming record.
-pub async fn listen_congestion_alerts() -> AmiquipResult<()> {
-    tokio::task::spawn_blocking(|| -> AmiquipResult<()> {
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
+// The queue is bound to the congestion topic exchange with `binding` (e.g. "congestion.#" for
+// every intersection, "congestion.row0.*" for row 0 only).
+// Records tagged with a run id other than `run_filter` are skipped (None logs every run).
+// Once `shutdown` is set to true the listener closes its connection and returns Ok.
+pub async fn listen_congestion_alerts(
+    run_filter: Option<String>,
+    binding: String,
+    paths: OutputPaths,
+    metrics: SharedMetrics,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    tokio::task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
         let channel = connection.open_channel(None)?;
-        let _exchange = Exchange::direct(&channel);
-        let queue =
-            channel.queue_declare(QUEUE_CONGESTION_ALERTS, QueueDeclareOptions::default())?;
+        let congestion_exchange = declare_congestion_exchange(&channel)?;
+        let queue = channel.queue_declare(
+            queue_name(QUEUE_CONGESTION_ALERTS),
+            QueueDeclareOptions::default(),
+        )?;
+        queue.bind(&congestion_exchange, binding, FieldTable::new())?;
+        let exchange = Exchange::direct(&channel);
+        channel.queue_declare(
+            queue_name(QUEUE_DEAD_LETTER),
+            QueueDeclareOptions::default(),
+        )?;
         let consumer = queue.consume(ConsumerOptions::default())?;
         // println!("Listening for congestion alerts...");
-        for message in consumer.receiver() {
+        while let Some(message) = next_message(&consumer, &shutdown) {
             match message {
                 ConsumerMessage::Delivery(delivery) => {
-                    let ts = current_timestamp();
                     if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
-                        let record: CongestionAlert =
-                            serde_json::from_str(json_str).unwrap_or(CongestionAlert {
-                                timestamp: ts,
-                                intersection: None,
-                                message: json_str.to_string(),
-                                congestion_perc: 0.0,
-                                recommended_action: "No recomendations.".to_string(),
-                            });
-                        log_congestion_alert(record);
+                        match serde_json::from_str::<CongestionAlert>(json_str) {
+                            Ok(record) => {
+                                if matches_run_filter(&run_filter, &record.run_id) {
+                                    metrics.lock().unwrap().congestion_alerts_total += 1;
+                                    log_congestion_alert(&paths, record);
+                                }
+                            }
+                            Err(e) => publish_dead_letter(
+                                &exchange,
+                                QUEUE_CONGESTION_ALERTS,
+                                json_str,
+                                &e,
+                            )?,
+                        }
                     }
                     consumer.ack(delivery)?;
                 }
@@ -71,27 +232,45 @@ pub async fn listen_congestion_alerts() -> AmiquipResult<()> {
 }
 
 // Listens to the "light_adjustments" queue and logs each incoming record.
-pub async fn listen_light_adjustments() -> AmiquipResult<()> {
-    tokio::task::spawn_blocking(|| -> AmiquipResult<()> {
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
+pub async fn listen_light_adjustments(
+    run_filter: Option<String>,
+    paths: OutputPaths,
+    metrics: SharedMetrics,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    tokio::task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
         let channel = connection.open_channel(None)?;
-        let _exchange = Exchange::direct(&channel);
-        let queue =
-            channel.queue_declare(QUEUE_LIGHT_ADJUSTMENTS, QueueDeclareOptions::default())?;
+        let exchange = Exchange::direct(&channel);
+        let queue = channel.queue_declare(
+            queue_name(QUEUE_LIGHT_ADJUSTMENTS),
+            QueueDeclareOptions::default(),
+        )?;
+        channel.queue_declare(
+            queue_name(QUEUE_DEAD_LETTER),
+            QueueDeclareOptions::default(),
+        )?;
         let consumer = queue.consume(ConsumerOptions::default())?;
         // println!("Listening for light adjustments...");
-        for message in consumer.receiver() {
+        while let Some(message) = next_message(&consumer, &shutdown) {
             match message {
                 ConsumerMessage::Delivery(delivery) => {
-                    let ts = current_timestamp();
                     if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
-                        let record: LightAdjustment =
-                            serde_json::from_str(json_str).unwrap_or(LightAdjustment {
-                                timestamp: ts,
-                                intersection_id: "unknown".to_string(),
-                                add_seconds_green: 0,
-                            });
-                        log_light_adjustment(record);
+                        match serde_json::from_str::<LightAdjustment>(json_str) {
+                            Ok(record) => {
+                                if matches_run_filter(&run_filter, &record.run_id) {
+                                    metrics.lock().unwrap().light_adjustments_total += 1;
+                                    log_light_adjustment(&paths, record);
+                                }
+                            }
+                            Err(e) => publish_dead_letter(
+                                &exchange,
+                                QUEUE_LIGHT_ADJUSTMENTS,
+                                json_str,
+                                &e,
+                            )?,
+                        }
                     }
                     consumer.ack(delivery)?;
                 }
@@ -108,24 +287,46 @@ pub async fn listen_light_adjustments() -> AmiquipResult<()> {
 }
 
 // Listens to the "traffic_data" queue and logs each incoming record.
-pub async fn listen_traffic_data() -> AmiquipResult<()> {
-    tokio::task::spawn_blocking(|| -> AmiquipResult<()> {
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
+pub async fn listen_traffic_data(
+    run_filter: Option<String>,
+    paths: OutputPaths,
+    metrics: SharedMetrics,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    tokio::task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
         let channel = connection.open_channel(None)?;
         let _exchange = Exchange::direct(&channel);
-        let queue = channel.queue_declare(QUEUE_TRAFFIC_DATA, QueueDeclareOptions::default())?;
+        let queue = channel.queue_declare(
+            queue_name(QUEUE_TRAFFIC_DATA),
+            QueueDeclareOptions::default(),
+        )?;
         let consumer = queue.consume(ConsumerOptions::default())?;
         // println!("Listening for traffic data...");
-        for message in consumer.receiver() {
+        while let Some(message) = next_message(&consumer, &shutdown) {
             match message {
                 ConsumerMessage::Delivery(delivery) => {
                     let ts = current_timestamp();
                     if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
-                        let record = TrafficDataRecord {
-                            timestamp: ts,
-                            raw_data: json_str.to_string(),
-                        };
-                        log_traffic_data(record);
+                        // The raw payload is logged as-is; only the run id is extracted for filtering.
+                        let run_id = serde_json::from_str::<serde_json::Value>(json_str)
+                            .ok()
+                            .and_then(|v| {
+                                v.get("run_id").and_then(|r| r.as_str()).map(String::from)
+                            })
+                            .unwrap_or_default();
+                        if matches_run_filter(&run_filter, &run_id) {
+                            metrics.lock().unwrap().traffic_data_total += 1;
+                            let record = TrafficDataRecord {
+                                timestamp: ts,
+                                raw_data: json_str.to_string(),
+                            };
+                            log_traffic_data(&paths, record);
+                            if let Ok(update) = serde_json::from_str::<TrafficUpdate>(json_str) {
+                                log_intersection_metrics(&paths, &update);
+                            }
+                        }
                     }
                     consumer.ack(delivery)?;
                 }
@@ -141,28 +342,47 @@ pub async fn listen_traffic_data() -> AmiquipResult<()> {
     .unwrap()
 }
 
-pub async fn listen_traffic_event() -> AmiquipResult<()> {
-    tokio::task::spawn_blocking(|| -> AmiquipResult<()> {
-        let mut connection = Connection::insecure_open(AMQP_URL)?;
+pub async fn listen_traffic_event(
+    run_filter: Option<String>,
+    paths: OutputPaths,
+    metrics: SharedMetrics,
+    shutdown: watch::Receiver<bool>,
+) -> AmiquipResult<()> {
+    tokio::task::spawn_blocking(move || -> AmiquipResult<()> {
+        let mut connection =
+            connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
         let channel = connection.open_channel(None)?;
-        let _exchange = Exchange::direct(&channel);
-        let queue = channel.queue_declare(QUEUE_TRAFFIC_EVENTS, QueueDeclareOptions::default())?;
+        let exchange = Exchange::direct(&channel);
+        let queue = channel.queue_declare(
+            queue_name(QUEUE_TRAFFIC_EVENTS),
+            QueueDeclareOptions::default(),
+        )?;
+        channel.queue_declare(
+            queue_name(QUEUE_DEAD_LETTER),
+            QueueDeclareOptions::default(),
+        )?;
         let consumer = queue.consume(ConsumerOptions::default())?;
         // println!("Listening for traffic event...");
-        for message in consumer.receiver() {
+        while let Some(message) = next_message(&consumer, &shutdown) {
             match message {
                 ConsumerMessage::Delivery(delivery) => {
-                    let ts = current_timestamp();
                     if let Ok(json_str) = std::str::from_utf8(&delivery.body) {
-                        // Use unwrap_or to fall back to a default TrafficEvent.
-                        let record: TrafficEvent =
-                            serde_json::from_str(json_str).unwrap_or(TrafficEvent {
-                                timestamp: ts,
-                                average_vehicle_delay: 0.0,
-                                total_accidents: 0,
-                                accident_details: Vec::new(),
-                            });
-                        log_traffic_event(record);
+                        match serde_json::from_str::<TrafficEvent>(json_str) {
+                            Ok(record) => {
+                                if matches_run_filter(&run_filter, &record.run_id) {
+                                    {
+                                        let mut m = metrics.lock().unwrap();
+                                        m.traffic_events_total += 1;
+                                        m.accidents_total += record.total_accidents as u64;
+                                        m.average_delay_seconds = record.average_vehicle_delay;
+                                    }
+                                    log_traffic_event(&paths, record);
+                                }
+                            }
+                            Err(e) => {
+                                publish_dead_letter(&exchange, QUEUE_TRAFFIC_EVENTS, json_str, &e)?
+                            }
+                        }
                     }
                     consumer.ack(delivery)?;
                 }
@@ -178,9 +398,136 @@ pub async fn listen_traffic_event() -> AmiquipResult<()> {
     .unwrap()
 }
 
-// Generic helper to log a record to a CSV file.
-fn log_to_csv<T: Serialize>(filename: &str, record: &T) -> Result<(), Box<dyn Error>> {
-    let file_exists = Path::new(filename).exists();
+// Republishes a message that failed to deserialize to the "dead_letter" queue, along with the
+// queue it came from and the parse error.
+fn publish_dead_letter(
+    exchange: &Exchange,
+    source_queue: &str,
+    body: &str,
+    error: &serde_json::Error,
+) -> AmiquipResult<()> {
+    eprintln!(
+        "Failed to parse message from {}: {}. Moving it to {}.",
+        source_queue, error, QUEUE_DEAD_LETTER
+    );
+    let dead_letter = DeadLetter {
+        timestamp: current_timestamp(),
+        source_queue: queue_name(source_queue),
+        body: body.to_string(),
+        error: error.to_string(),
+    };
+    let payload = serde_json::to_string(&dead_letter).unwrap();
+    exchange.publish(Publish::new(
+        payload.as_bytes(),
+        queue_name(QUEUE_DEAD_LETTER),
+    ))
+}
+
+// Prints the messages currently in the "dead_letter" queue. The messages are left unacknowledged
+// so the broker requeues them when the connection closes.
+pub fn show_dead_letters() -> AmiquipResult<()> {
+    let mut connection = connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+    let channel = connection.open_channel(None)?;
+    let queue = channel.queue_declare(
+        queue_name(QUEUE_DEAD_LETTER),
+        QueueDeclareOptions::default(),
+    )?;
+    println!("Dead-Lettered Messages:");
+    let mut count = 0;
+    while let Some(get) = queue.get(false)? {
+        match serde_json::from_slice::<DeadLetter>(&get.delivery.body) {
+            Ok(dead_letter) => println!("{:?}", dead_letter),
+            Err(_) => println!("{}", String::from_utf8_lossy(&get.delivery.body)),
+        }
+        count += 1;
+    }
+    if count == 0 {
+        println!("No dead-lettered messages.");
+    }
+    connection.close()
+}
+
+// Path of the n-th rotated copy of a CSV log, e.g. traffic_data.csv -> traffic_data.1.csv.
+pub fn rotated_path(filename: &Path, n: usize) -> PathBuf {
+    let stem = filename.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = filename
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "csv".to_string());
+    filename.with_file_name(format!("{}.{}.{}", stem, n, ext))
+}
+
+// Number of rotated copies that exist for a CSV log.
+fn rotated_count(filename: &Path) -> usize {
+    let mut count = 0;
+    while rotated_path(filename, count + 1).exists() {
+        count += 1;
+    }
+    count
+}
+
+// Moves a CSV log to name.1.csv, shifting older copies up by one (name.1.csv -> name.2.csv, ...).
+fn rotate_csv(filename: &Path) -> std::io::Result<()> {
+    for n in (1..=rotated_count(filename)).rev() {
+        fs::rename(rotated_path(filename, n), rotated_path(filename, n + 1))?;
+    }
+    fs::rename(filename, rotated_path(filename, 1))
+}
+
+// Opens a CSV reader for each file of a log, oldest first.
+fn open_csv_logs(
+    paths: &OutputPaths,
+    filename: &Path,
+) -> Result<Vec<csv::Reader<File>>, Box<dyn Error>> {
+    let mut readers = Vec::new();
+    for file in paths.csv_log_files(filename) {
+        readers.push(csv::Reader::from_path(file)?);
+    }
+    Ok(readers)
+}
+
+// Like open_csv_logs, but skips files that don't exist yet, so a log nothing has been written to
+// reads as empty rather than as an error.
+fn open_existing_csv_logs(
+    paths: &OutputPaths,
+    filename: &Path,
+) -> Result<Vec<csv::Reader<File>>, Box<dyn Error>> {
+    let mut readers = Vec::new();
+    for file in paths.csv_log_files(filename) {
+        if file.exists() {
+            readers.push(csv::Reader::from_path(file)?);
+        }
+    }
+    Ok(readers)
+}
+
+// Reads every record of a CSV log, across its rotated files when enabled.
+fn read_csv_log<T: DeserializeOwned>(
+    paths: &OutputPaths,
+    filename: &Path,
+) -> Result<Vec<T>, Box<dyn Error>> {
+    let mut records = Vec::new();
+    for mut rdr in open_csv_logs(paths, filename)? {
+        for result in rdr.deserialize() {
+            records.push(result?);
+        }
+    }
+    Ok(records)
+}
+
+// Generic helper to log a record to a CSV file. Once the file reaches `max_bytes` it is rotated
+// and the record starts a fresh file (with a new header).
+fn log_to_csv<T: Serialize>(
+    filename: &Path,
+    record: &T,
+    max_bytes: Option<u64>,
+) -> Result<(), Box<dyn Error>> {
+    if let (Some(max_bytes), Ok(metadata)) = (max_bytes, fs::metadata(filename)) {
+        if metadata.len() >= max_bytes {
+            rotate_csv(filename)?;
+        }
+    }
+    let file_exists = filename.exists();
     let file = OpenOptions::new()
         .append(true)
         .create(true)
@@ -194,96 +541,277 @@ fn log_to_csv<T: Serialize>(filename: &str, record: &T) -> Result<(), Box<dyn Er
 }
 
 // Logging functions for each type of record.
-pub fn log_congestion_alert(record: CongestionAlert) {
-    if let Err(e) = log_to_csv("congestion_alerts.csv", &record) {
+pub fn log_congestion_alert(paths: &OutputPaths, record: CongestionAlert) {
+    if let Err(e) = log_to_csv(&paths.congestion_alerts, &record, paths.max_csv_bytes) {
         eprintln!("Error logging congestion alert: {}", e);
     }
 }
 
-pub fn log_light_adjustment(record: LightAdjustment) {
-    if let Err(e) = log_to_csv("light_adjustments.csv", &record) {
+pub fn log_light_adjustment(paths: &OutputPaths, record: LightAdjustment) {
+    if let Err(e) = log_to_csv(&paths.light_adjustments, &record, paths.max_csv_bytes) {
         eprintln!("Error logging light adjustment: {}", e);
     }
 }
 
-pub fn log_traffic_data(record: TrafficDataRecord) {
-    if let Err(e) = log_to_csv("traffic_data.csv", &record) {
+pub fn log_traffic_data(paths: &OutputPaths, record: TrafficDataRecord) {
+    if let Err(e) = log_to_csv(&paths.traffic_data, &record, paths.max_csv_bytes) {
         eprintln!("Error logging traffic data: {}", e);
     }
 }
 
+// Logs one row per intersection in the update, in intersection order. Intersections missing
+// from the waiting-time map are logged with a waiting time of 0.
+pub fn log_intersection_metrics(paths: &OutputPaths, update: &TrafficUpdate) {
+    let data = &update.current_data;
+    let mut intersections: Vec<&String> = data.intersection_congestion.keys().collect();
+    intersections.sort();
+    for intersection_id in intersections {
+        let record = IntersectionMetricsRecord {
+            timestamp: update.timestamp,
+            intersection_id: intersection_id.clone(),
+            congestion: data.intersection_congestion[intersection_id],
+            waiting_time: data
+                .intersection_waiting_time
+                .get(intersection_id)
+                .copied()
+                .unwrap_or(0.0),
+        };
+        if let Err(e) = log_to_csv(&paths.intersection_metrics, &record, paths.max_csv_bytes) {
+            eprintln!("Error logging intersection metrics: {}", e);
+        }
+    }
+}
+
 // Log the overall TrafficEvent and process its accident_details vector separately.
-pub fn log_traffic_event(record: TrafficEvent) {
+pub fn log_traffic_event(paths: &OutputPaths, record: TrafficEvent) {
     // Create a summary record that omits the accident_details vector.
     let summary = TrafficEventSummary {
         timestamp: record.timestamp,
         average_vehicle_delay: record.average_vehicle_delay,
         total_accidents: record.total_accidents,
+        congestion_index: record.congestion_index,
+        total_fuel_used: record.total_fuel_used,
+        throughput_per_min: record.throughput_per_min,
     };
 
-    if let Err(e) = log_to_csv("traffic_event.csv", &summary) {
+    if let Err(e) = log_to_csv(&paths.traffic_event, &summary, paths.max_csv_bytes) {
         eprintln!("Error logging traffic event summary: {}", e);
     }
 
     // Process each AccidentInfo record in the accident_details vector.
     for accident in record.accident_details {
-        if let Err(e) = log_to_csv("accident_info.csv", &accident) {
+        if let Err(e) = log_to_csv(
+            &paths.accident_info,
+            &AccidentRecord::from(accident),
+            paths.max_csv_bytes,
+        ) {
             eprintln!("Error logging accident info: {}", e);
         }
     }
 }
 
-// Helper: Count records in a CSV file.
-fn count_csv_records(filename: &str) -> Result<usize, Box<dyn Error>> {
-    let file = File::open(filename)?;
-    let mut rdr = csv::Reader::from_reader(file);
-    let count = rdr.deserialize::<serde_json::Value>().count();
+// Accidents per hour above which a lane is considered high-risk, unless configured otherwise.
+pub const HIGH_RISK_ACCIDENTS_PER_HOUR: f64 = 2.0;
+
+// Accident history of one lane.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaneAccidentRisk {
+    pub lane: String,
+    pub accidents: usize,
+    pub accidents_per_hour: f64,
+    pub high_risk: bool,
+}
+
+// Per-lane accident rates over the period `records` cover (at least an hour), sorted from the
+// highest rate down (ties by lane name). Lanes above `threshold_per_hour` are flagged high-risk.
+pub fn lane_accident_risk(
+    records: &[AccidentRecord],
+    threshold_per_hour: f64,
+) -> Vec<LaneAccidentRisk> {
+    let (Some(first), Some(last)) = (
+        records.iter().map(|r| r.accident_timestamp).min(),
+        records.iter().map(|r| r.accident_timestamp).max(),
+    ) else {
+        return Vec::new();
+    };
+    let hours = ((last - first) as f64 / 3600.0).max(1.0);
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for record in records {
+        *counts.entry(record.current_lane.as_str()).or_insert(0) += 1;
+    }
+    let mut risks: Vec<LaneAccidentRisk> = counts
+        .into_iter()
+        .map(|(lane, accidents)| {
+            let accidents_per_hour = accidents as f64 / hours;
+            LaneAccidentRisk {
+                lane: lane.to_string(),
+                accidents,
+                accidents_per_hour,
+                high_risk: accidents_per_hour > threshold_per_hour,
+            }
+        })
+        .collect();
+    risks.sort_by(|a, b| {
+        b.accidents_per_hour
+            .total_cmp(&a.accidents_per_hour)
+            .then_with(|| a.lane.cmp(&b.lane))
+    });
+    risks
+}
+
+// Reads an accident_info.csv written by the monitoring system.
+pub fn load_accident_history(path: &Path) -> Result<Vec<AccidentRecord>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut records = Vec::new();
+    for result in rdr.deserialize() {
+        records.push(result?);
+    }
+    Ok(records)
+}
+
+// Accident rates per lane from the logged accident history, including rotated copies.
+pub fn accident_hotspots(
+    paths: &OutputPaths,
+    threshold_per_hour: f64,
+) -> Result<Vec<LaneAccidentRisk>, Box<dyn Error>> {
+    let records: Vec<AccidentRecord> = read_csv_log(paths, &paths.accident_info)?;
+    Ok(lane_accident_risk(&records, threshold_per_hour))
+}
+
+// Outcome of replay_traffic_data_csv.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    pub published: usize,
+    // Rows that couldn't be read or whose raw_data isn't a TrafficUpdate.
+    pub skipped: usize,
+}
+
+// Re-publishes the TrafficUpdates recorded in a traffic_data.csv to the traffic_data queue, one
+// every `interval`, so the analyzer can be re-run on past traffic without the simulation.
+pub fn replay_traffic_data_csv(
+    path: &Path,
+    bus: &dyn MessageBus,
+    interval: std::time::Duration,
+) -> Result<ReplaySummary, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut summary = ReplaySummary::default();
+    for result in rdr.deserialize::<TrafficDataRecord>() {
+        let update = match result.map_err(|e| e.to_string()).and_then(|record| {
+            serde_json::from_str::<TrafficUpdate>(&record.raw_data).map_err(|e| e.to_string())
+        }) {
+            Ok(update) => update,
+            Err(e) => {
+                summary.skipped += 1;
+                eprintln!("Skipping malformed traffic_data row: {}", e);
+                continue;
+            }
+        };
+        if summary.published > 0 && !interval.is_zero() {
+            std::thread::sleep(interval);
+        }
+        let payload = serde_json::to_vec(&update)?;
+        bus.publish(&queue_name(QUEUE_TRAFFIC_DATA), &payload)
+            .map_err(|e| e as Box<dyn Error>)?;
+        summary.published += 1;
+    }
+    if summary.skipped > 0 {
+        eprintln!(
+            "Warning: skipped {} malformed rows in {}",
+            summary.skipped,
+            path.display()
+        );
+    }
+    Ok(summary)
+}
+
+// Helper: Count records in a CSV file. A missing file counts as zero records.
+fn count_csv_records(paths: &OutputPaths, filename: &Path) -> Result<usize, Box<dyn Error>> {
+    let mut count = 0;
+    for mut rdr in open_existing_csv_logs(paths, filename)? {
+        count += rdr.deserialize::<serde_json::Value>().count();
+    }
     Ok(count)
 }
 
+// Records from "congestion_alerts.csv" inside the time window, oldest file first.
+pub fn read_congestion_alerts(
+    paths: &OutputPaths,
+    window: Option<(u64, u64)>,
+) -> Result<Vec<CongestionAlert>, Box<dyn Error>> {
+    let records: Vec<CongestionAlert> = read_csv_log(paths, &paths.congestion_alerts)?;
+    Ok(records
+        .into_iter()
+        .filter(|record| in_time_window(record.timestamp, window))
+        .collect())
+}
+
 // Reads and displays records from "congestion_alerts.csv".
-pub fn show_congestion_alerts() -> Result<(), Box<dyn Error>> {
-    let file = File::open("congestion_alerts.csv")?;
-    let mut rdr = csv::Reader::from_reader(file);
+pub fn show_congestion_alerts(
+    paths: &OutputPaths,
+    window: Option<(u64, u64)>,
+) -> Result<(), Box<dyn Error>> {
     println!("Congestion Alerts:");
-    for result in rdr.deserialize() {
-        let record: CongestionAlert = result?;
+    let records = read_congestion_alerts(paths, window)?;
+    for record in &records {
         println!("{:?}", record);
     }
+    if records.is_empty() && window.is_some() {
+        println!("No records in the selected time range.");
+    }
     Ok(())
 }
 
 // Reads and displays records from "light_adjustments.csv".
-pub fn show_light_adjustments() -> Result<(), Box<dyn Error>> {
-    let file = File::open("light_adjustments.csv")?;
-    let mut rdr = csv::Reader::from_reader(file);
+pub fn show_light_adjustments(
+    paths: &OutputPaths,
+    window: Option<(u64, u64)>,
+) -> Result<(), Box<dyn Error>> {
     println!("Light Adjustments:");
-    for result in rdr.deserialize() {
-        let record: LightAdjustment = result?;
-        println!("{:?}", record);
+    let mut shown = 0;
+    for mut rdr in open_csv_logs(paths, &paths.light_adjustments)? {
+        for result in rdr.deserialize() {
+            let record: LightAdjustment = result?;
+            if in_time_window(record.timestamp, window) {
+                println!("{:?}", record);
+                shown += 1;
+            }
+        }
+    }
+    if shown == 0 && window.is_some() {
+        println!("No records in the selected time range.");
     }
     Ok(())
 }
 
 // Reads and displays records from "traffic_data.csv".
-pub fn show_traffic_data() -> Result<(), Box<dyn Error>> {
-    let file = File::open("traffic_data.csv")?;
-    let mut rdr = csv::Reader::from_reader(file);
+pub fn show_traffic_data(
+    paths: &OutputPaths,
+    window: Option<(u64, u64)>,
+) -> Result<(), Box<dyn Error>> {
     println!("Traffic Data:");
-    for result in rdr.deserialize() {
-        let record: TrafficDataRecord = result?;
-        println!("{:?}", record);
+    let mut shown = 0;
+    for mut rdr in open_csv_logs(paths, &paths.traffic_data)? {
+        for result in rdr.deserialize() {
+            let record: TrafficDataRecord = result?;
+            if in_time_window(record.timestamp, window) {
+                println!("{:?}", record);
+                shown += 1;
+            }
+        }
+    }
+    if shown == 0 && window.is_some() {
+        println!("No records in the selected time range.");
     }
     Ok(())
 }
 
 // Option 1: Display report summary with data counts.
-pub fn generate_report_summary() -> Result<(), Box<dyn Error>> {
+pub fn generate_report_summary(paths: &OutputPaths) -> Result<(), Box<dyn Error>> {
     println!("Generating Report Summary...");
-    let congestion_count = count_csv_records("congestion_alerts.csv")?;
-    let light_adjustments_count = count_csv_records("light_adjustments.csv")?;
-    let traffic_data_count = count_csv_records("traffic_data.csv")?;
-    let traffic_event_count = count_csv_records("traffic_event.csv")?;
+    let congestion_count = count_csv_records(paths, &paths.congestion_alerts)?;
+    let light_adjustments_count = count_csv_records(paths, &paths.light_adjustments)?;
+    let traffic_data_count = count_csv_records(paths, &paths.traffic_data)?;
+    let traffic_event_count = count_csv_records(paths, &paths.traffic_event)?;
     println!("Report Summary:");
     println!("Congestion Alerts: {} records", congestion_count);
     println!("Light Adjustments: {} records", light_adjustments_count);
@@ -292,33 +820,121 @@ pub fn generate_report_summary() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// Structured form of the report summary, for dashboards and other tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitoringReport {
+    pub congestion_alerts: usize,
+    pub light_adjustments: usize,
+    pub traffic_data: usize,
+    pub traffic_events: usize,
+    pub average_vehicle_delay: f64,
+    pub total_accidents: usize,
+    // Earliest and latest timestamps across all logged records (None if nothing is logged).
+    pub first_timestamp: Option<u64>,
+    pub last_timestamp: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimestampOnly {
+    timestamp: u64,
+}
+
+// Helper: Read the timestamp column of a CSV file. A missing file has no timestamps.
+fn read_csv_timestamps(paths: &OutputPaths, filename: &Path) -> Result<Vec<u64>, Box<dyn Error>> {
+    let mut timestamps = Vec::new();
+    for mut rdr in open_existing_csv_logs(paths, filename)? {
+        timestamps.extend(
+            rdr.deserialize::<TimestampOnly>()
+                .filter_map(Result::ok)
+                .map(|r| r.timestamp),
+        );
+    }
+    Ok(timestamps)
+}
+
+// Assembles record counts, delay and accident totals into a MonitoringReport and returns it
+// serialized as pretty-printed JSON. Logs that haven't been written yet count as empty.
+pub fn generate_report_json(paths: &OutputPaths) -> Result<String, Box<dyn Error>> {
+    let mut events: Vec<TrafficEventSummary> = Vec::new();
+    for mut rdr in open_existing_csv_logs(paths, &paths.traffic_event)? {
+        events.extend(rdr.deserialize().filter_map(Result::ok));
+    }
+    let average_vehicle_delay = if events.is_empty() {
+        0.0
+    } else {
+        let total: f64 = events.iter().map(|e| e.average_vehicle_delay).sum();
+        // round to 2 decimal places
+        (total / events.len() as f64 * 100.0).round() / 100.0
+    };
+    let total_accidents = events.iter().map(|e| e.total_accidents).sum();
+
+    let mut timestamps = Vec::new();
+    for filename in [
+        &paths.congestion_alerts,
+        &paths.light_adjustments,
+        &paths.traffic_data,
+        &paths.traffic_event,
+    ] {
+        timestamps.extend(read_csv_timestamps(paths, filename)?);
+    }
+
+    let report = MonitoringReport {
+        congestion_alerts: count_csv_records(paths, &paths.congestion_alerts)?,
+        light_adjustments: count_csv_records(paths, &paths.light_adjustments)?,
+        traffic_data: count_csv_records(paths, &paths.traffic_data)?,
+        traffic_events: events.len(),
+        average_vehicle_delay,
+        total_accidents,
+        first_timestamp: timestamps.iter().min().copied(),
+        last_timestamp: timestamps.iter().max().copied(),
+    };
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+// Writes the JSON report to "report.json".
+pub fn export_report_json(paths: &OutputPaths) -> Result<(), Box<dyn Error>> {
+    let json = generate_report_json(paths)?;
+    fs::write(&paths.report, json)?;
+    println!("Report written to {}", paths.report.display());
+    Ok(())
+}
+
 // Option 2: Show congestion report heatmap using Plotters.
-pub fn show_congestion_heatmap() -> Result<(), Box<dyn Error>> {
-    let mut rdr = csv::Reader::from_path("congestion_alerts.csv")?;
-    let mut congestion_map: HashMap<(i32, i32), Vec<f64>> = HashMap::new();
+pub fn show_congestion_heatmap(paths: &OutputPaths) -> Result<(), Box<dyn Error>> {
+    let records: Vec<CongestionAlert> = read_csv_log(paths, &paths.congestion_alerts)?;
+    let mut congestion_map: HashMap<(i8, i8), Vec<f64>> = HashMap::new();
 
-    for result in rdr.deserialize() {
-        let record: CongestionAlert = result?;
+    for record in records {
         if let Some(inter_str) = record.intersection {
-            if let Some((x, y)) = parse_intersection(&inter_str) {
+            if let Some((row, col)) = parse_intersection(&inter_str) {
                 let perc = record.congestion_perc;
-                congestion_map.entry((x, y)).or_default().push(perc);
+                congestion_map.entry((row, col)).or_default().push(perc);
             }
         }
     }
 
-    let mut avg_congestion: HashMap<(i32, i32), f64> = HashMap::new();
-    for ((x, y), values) in congestion_map {
+    let mut avg_congestion: HashMap<(i8, i8), f64> = HashMap::new();
+    for ((row, col), values) in congestion_map {
         let avg = values.iter().sum::<f64>() / values.len() as f64;
-        avg_congestion.insert((x, y), avg);
+        avg_congestion.insert((row, col), avg);
     }
 
-    let (grid_rows, grid_cols) = (4, 4);
+    // Size the grid to the largest row/col seen; cells without data show 0 congestion.
+    let grid_rows = avg_congestion
+        .keys()
+        .map(|&(r, _)| r.max(0) as i32 + 1)
+        .max()
+        .unwrap_or(1);
+    let grid_cols = avg_congestion
+        .keys()
+        .map(|&(_, c)| c.max(0) as i32 + 1)
+        .max()
+        .unwrap_or(1);
     let (cell_width, cell_height) = (100, 100);
     let (image_width, image_height) = (grid_cols * cell_width, grid_rows * cell_height);
 
     let backend = BitMapBackend::new(
-        "congestion_heatmap.png",
+        &paths.congestion_heatmap,
         (image_width as u32, image_height as u32),
     );
     let root = backend.into_drawing_area();
@@ -327,7 +943,7 @@ pub fn show_congestion_heatmap() -> Result<(), Box<dyn Error>> {
     for row in 0..grid_rows {
         for col in 0..grid_cols {
             let congestion = avg_congestion
-                .get(&(col as i32, row as i32))
+                .get(&(row as i8, col as i8))
                 .cloned()
                 .unwrap_or(0.0);
             let green_blue = (127.0 * (1.0 - congestion)).round() as u8;
@@ -342,10 +958,10 @@ pub fn show_congestion_heatmap() -> Result<(), Box<dyn Error>> {
 
             root.draw(&Rectangle::new(
                 [(x0, y0), (x0 + cell_width, y0 + cell_height)],
-                &BLACK,
+                BLACK,
             ))?;
 
-            let text = format!("({},{})\n{:.2}", col, row, congestion);
+            let text = format!("({},{})\n{:.2}", row, col, congestion);
             let (text_x, text_y) = (x0 + cell_width / 2, y0 + cell_height / 2);
             root.draw(&Text::new(
                 text,
@@ -358,35 +974,200 @@ pub fn show_congestion_heatmap() -> Result<(), Box<dyn Error>> {
     }
 
     root.present()?;
-    println!("Congestion heatmap saved to congestion_heatmap.png");
+    println!(
+        "Congestion heatmap saved to {}",
+        paths.congestion_heatmap.display()
+    );
     Ok(())
 }
 
-// Helper to parse an intersection string of the form "IntersectionId(x, y)".
-fn parse_intersection(s: &str) -> Option<(i32, i32)> {
-    let s = s.trim();
-    if s.starts_with("IntersectionId(") && s.ends_with(")") {
-        let inner = &s["IntersectionId(".len()..s.len() - 1];
-        let parts: Vec<&str> = inner.split(',').collect();
-        if parts.len() == 2 {
-            if let (Ok(x), Ok(y)) = (
-                parts[0].trim().parse::<i32>(),
-                parts[1].trim().parse::<i32>(),
-            ) {
-                return Some((x, y));
-            }
+// Tallies accidents per lane from an accident_info CSV.
+pub fn tally_accidents_by_lane(filename: &Path) -> Result<HashMap<String, usize>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(filename)?;
+    let mut tally: HashMap<String, usize> = HashMap::new();
+    for result in rdr.deserialize() {
+        let accident: AccidentRecord = result?;
+        *tally.entry(accident.current_lane).or_insert(0) += 1;
+    }
+    Ok(tally)
+}
+
+// Draws accidents per lane from "accident_info.csv" as a bar chart, most dangerous lane first.
+pub fn show_accident_frequency(paths: &OutputPaths) -> Result<(), Box<dyn Error>> {
+    let accident_file = &paths.accident_info;
+    if !accident_file.exists() {
+        println!("No accident records found.");
+        return Ok(());
+    }
+    let mut lanes: Vec<(String, usize)> = tally_accidents_by_lane(accident_file)?
+        .into_iter()
+        .collect();
+    if lanes.is_empty() {
+        println!("No accident records found.");
+        return Ok(());
+    }
+    lanes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let max_count = lanes[0].1;
+
+    let backend = BitMapBackend::new(&paths.accident_frequency, (1000, 600));
+    let root = backend.into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Accidents per Lane", ("sans-serif", 20))
+        .margin(40)
+        .x_label_area_size(60)
+        .y_label_area_size(40)
+        .build_cartesian_2d((0..lanes.len() - 1).into_segmented(), 0..max_count + 1)?;
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(lanes.len())
+        .x_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(i) => lanes.get(*i).map(|l| l.0.clone()).unwrap_or_default(),
+            _ => String::new(),
+        })
+        .x_desc("Lane")
+        .y_desc("Accidents")
+        .draw()?;
+
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(RED.filled())
+            .margin(5)
+            .data(lanes.iter().enumerate().map(|(i, (_, count))| (i, *count))),
+    )?;
+
+    root.present()?;
+    println!(
+        "Accident frequency chart saved to {}",
+        paths.accident_frequency.display()
+    );
+    for (lane, count) in &lanes {
+        println!("{}: {}", lane, count);
+    }
+    Ok(())
+}
+
+// Draws congestion_perc over time for one intersection from "congestion_alerts.csv" as a line
+// chart. `intersection` is either "IntersectionId(x, y)" or just "x,y".
+pub fn show_congestion_timeline(
+    paths: &OutputPaths,
+    intersection: &str,
+) -> Result<(), Box<dyn Error>> {
+    // Accept either the logged form "IntersectionId(0, 2)" or a bare "0,2" typed at the prompt.
+    let target = parse_intersection(intersection);
+    let records: Vec<CongestionAlert> = read_csv_log(paths, &paths.congestion_alerts)?;
+    let mut points: Vec<(u64, f64)> = Vec::new();
+    for record in records {
+        let matches = match (&record.intersection, target) {
+            (Some(inter_str), Some(coords)) => parse_intersection(inter_str) == Some(coords),
+            (Some(inter_str), None) => inter_str.trim() == intersection.trim(),
+            (None, _) => false,
+        };
+        if matches {
+            points.push((record.timestamp, record.congestion_perc));
         }
     }
+
+    if points.is_empty() {
+        println!(
+            "No congestion alerts recorded for intersection {}.",
+            intersection
+        );
+        return Ok(());
+    }
+    points.sort_by_key(|(ts, _)| *ts);
+
+    let min_ts = points.first().unwrap().0;
+    // Pad the x range so a single alert still gets a drawable chart.
+    let max_ts = points.last().unwrap().0.max(min_ts + 1);
+
+    let backend = BitMapBackend::new(&paths.congestion_timeline, (800, 600));
+    let root = backend.into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("Congestion at {} over time", intersection),
+            ("sans-serif", 20),
+        )
+        .margin(40)
+        .x_label_area_size(40)
+        .y_label_area_size(40)
+        .build_cartesian_2d(min_ts..max_ts, 0.0..1.0)?;
+
+    chart.configure_mesh().draw()?;
+    chart.draw_series(LineSeries::new(points.iter().cloned(), &RED))?;
+    chart.draw_series(
+        points
+            .iter()
+            .map(|&(ts, perc)| Circle::new((ts, perc), 3, RED.filled())),
+    )?;
+
+    root.present()?;
+    println!(
+        "Congestion timeline saved to {}",
+        paths.congestion_timeline.display()
+    );
+    Ok(())
+}
+
+// True if `timestamp` falls inside the inclusive (from_ts, to_ts) window; no window keeps all.
+pub fn in_time_window(timestamp: u64, window: Option<(u64, u64)>) -> bool {
+    window.is_none_or(|(from_ts, to_ts)| timestamp >= from_ts && timestamp <= to_ts)
+}
+
+// Asks for an optional "from_ts,to_ts" range. Empty or invalid input means no filtering.
+fn prompt_time_window() -> Option<(u64, u64)> {
+    print!("Enter timestamp range as from,to (leave empty for all records): ");
+    stdout().flush().unwrap();
+    let mut input = String::new();
+    stdin().read_line(&mut input).unwrap();
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let parts: Vec<&str> = input.split(',').collect();
+    if parts.len() == 2 {
+        if let (Ok(from_ts), Ok(to_ts)) = (
+            parts[0].trim().parse::<u64>(),
+            parts[1].trim().parse::<u64>(),
+        ) {
+            return Some((from_ts.min(to_ts), from_ts.max(to_ts)));
+        }
+    }
+    println!("Invalid range, showing all records.");
     None
 }
 
+// Parses an IntersectionId, e.g. "IntersectionId(0, 2)" or "0,2", into (row, col).
+pub fn parse_intersection(s: &str) -> Option<(i8, i8)> {
+    let id: IntersectionId = s.parse().ok()?;
+    Some((id.0, id.1))
+}
+
 // Option 3: Show traffic events data (average waiting time)
-pub fn show_traffic_events() -> Result<(), Box<dyn Error>> {
-    let mut rdr = csv::Reader::from_path("traffic_event.csv")?;
-    let events: Vec<TrafficEventSummary> = rdr.deserialize().filter_map(Result::ok).collect();
+pub fn show_traffic_events(
+    paths: &OutputPaths,
+    window: Option<(u64, u64)>,
+) -> Result<(), Box<dyn Error>> {
+    let mut events: Vec<TrafficEventSummary> = Vec::new();
+    for mut rdr in open_csv_logs(paths, &paths.traffic_event)? {
+        events.extend(
+            rdr.deserialize::<TrafficEventSummary>()
+                .filter_map(Result::ok)
+                .filter(|e| in_time_window(e.timestamp, window)),
+        );
+    }
 
     if events.is_empty() {
-        println!("No traffic event data available.");
+        if window.is_some() {
+            println!("No traffic event data in the selected time range.");
+        } else {
+            println!("No traffic event data available.");
+        }
         return Ok(());
     }
 
@@ -401,7 +1182,7 @@ pub fn show_traffic_events() -> Result<(), Box<dyn Error>> {
         .map(|e| e.average_vehicle_delay)
         .fold(f64::NEG_INFINITY, f64::max);
 
-    let backend = BitMapBackend::new("traffic_events_scatterplot.png", (800, 600));
+    let backend = BitMapBackend::new(&paths.traffic_events_scatterplot, (800, 600));
     let root = backend.into_drawing_area();
     root.fill(&WHITE)?;
 
@@ -420,15 +1201,18 @@ pub fn show_traffic_events() -> Result<(), Box<dyn Error>> {
     )?;
 
     root.present()?;
-    println!("Traffic events scatterplot saved to traffic_events_scatterplot.png");
+    println!(
+        "Traffic events scatterplot saved to {}",
+        paths.traffic_events_scatterplot.display()
+    );
 
-    let accident_file = "accident_info.csv";
-    if Path::new(accident_file).exists() {
+    let accident_file = &paths.accident_info;
+    if accident_file.exists() {
         let mut rdr = csv::Reader::from_path(accident_file)?;
         let mut accident_count = 0;
         println!("Accident details:");
         for result in rdr.deserialize() {
-            let accident: AccidentInfo = result?;
+            let accident: AccidentRecord = result?;
             println!("{:?}", accident);
             accident_count += 1;
         }
@@ -439,11 +1223,14 @@ pub fn show_traffic_events() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub async fn generate_detailed_report() {
+pub async fn generate_detailed_report(paths: &OutputPaths) {
     println!("\nDetailed Report Menu:");
     println!("1. Display report summary with all data counts");
     println!("2. Show congestion data with heatmap");
     println!("3. Show traffic event data with scatterplot");
+    println!("4. Export report summary to {}", paths.report.display());
+    println!("5. Show congestion timeline for an intersection");
+    println!("6. Show accident frequency per lane");
     print!("Enter your choice: ");
     stdout().flush().unwrap();
     let mut input = String::new();
@@ -451,27 +1238,46 @@ pub async fn generate_detailed_report() {
     let choice = input.trim().parse::<u32>().unwrap_or(0);
     match choice {
         1 => {
-            if let Err(e) = generate_report_summary() {
+            if let Err(e) = generate_report_summary(paths) {
                 eprintln!("Error generating report summary: {}", e);
             }
         }
         2 => {
-            if let Err(e) = show_congestion_heatmap() {
+            if let Err(e) = show_congestion_heatmap(paths) {
                 eprintln!("Error generating congestion heatmap: {}", e);
             }
         }
         3 => {
-            if let Err(e) = show_traffic_events() {
+            if let Err(e) = show_traffic_events(paths, prompt_time_window()) {
                 eprintln!("Error displaying traffic events: {}", e);
             }
         }
+        4 => {
+            if let Err(e) = export_report_json(paths) {
+                eprintln!("Error exporting report: {}", e);
+            }
+        }
+        5 => {
+            print!("Enter Intersection ID (x,y): ");
+            stdout().flush().unwrap();
+            let mut id_input = String::new();
+            stdin().read_line(&mut id_input).unwrap();
+            if let Err(e) = show_congestion_timeline(paths, id_input.trim()) {
+                eprintln!("Error generating congestion timeline: {}", e);
+            }
+        }
+        6 => {
+            if let Err(e) = show_accident_frequency(paths) {
+                eprintln!("Error generating accident frequency chart: {}", e);
+            }
+        }
         _ => {
             println!("Invalid choice.");
         }
     }
 }
 
-pub async fn run_cli() {
+pub async fn run_cli(paths: OutputPaths) {
     loop {
         println!("\nTraffic Monitoring System Admin CLI");
         println!("1. Display Congestion Alerts");
@@ -479,7 +1285,10 @@ pub async fn run_cli() {
         println!("3. Display Traffic Data");
         println!("4. Manually Adjust Traffic Light Phase Duration");
         println!("5. Generate Detailed Report");
-        println!("6. Exit");
+        println!("6. Pause Simulation");
+        println!("7. Resume Simulation");
+        println!("8. Show Dead-Lettered Messages");
+        println!("9. Exit");
         print!("Enter your choice: ");
         stdout().flush().unwrap();
         let mut input = String::new();
@@ -487,17 +1296,17 @@ pub async fn run_cli() {
         let choice = input.trim().parse::<u32>().unwrap_or(0);
         match choice {
             1 => {
-                if let Err(e) = show_congestion_alerts() {
+                if let Err(e) = show_congestion_alerts(&paths, prompt_time_window()) {
                     eprintln!("Error displaying congestion alerts: {}", e);
                 }
             }
             2 => {
-                if let Err(e) = show_light_adjustments() {
+                if let Err(e) = show_light_adjustments(&paths, prompt_time_window()) {
                     eprintln!("Error displaying light adjustments: {}", e);
                 }
             }
             3 => {
-                if let Err(e) = show_traffic_data() {
+                if let Err(e) = show_traffic_data(&paths, prompt_time_window()) {
                     eprintln!("Error displaying traffic data: {}", e);
                 }
             }
@@ -506,8 +1315,13 @@ pub async fn run_cli() {
                 stdout().flush().unwrap();
                 let mut id_input = String::new();
                 stdin().read_line(&mut id_input).unwrap();
-                let trimmed_id = id_input.trim();
-                let intersection_id = format!("IntersectionId({})", trimmed_id);
+                let intersection_id = match id_input.parse::<IntersectionId>() {
+                    Ok(id) => id.to_string(),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        continue;
+                    }
+                };
                 print!("Enter new phase duration (seconds): ");
                 stdout().flush().unwrap();
                 let mut dur_input = String::new();
@@ -522,9 +1336,22 @@ pub async fn run_cli() {
                 }
             }
             5 => {
-                generate_detailed_report().await;
+                generate_detailed_report(&paths).await;
             }
-            6 => {
+            6 => match send_simulation_command(SimulationCommand::Pause) {
+                Ok(_) => println!("Pause command sent to simulation."),
+                Err(e) => eprintln!("Error sending pause command: {}", e),
+            },
+            7 => match send_simulation_command(SimulationCommand::Resume) {
+                Ok(_) => println!("Resume command sent to simulation."),
+                Err(e) => eprintln!("Error sending resume command: {}", e),
+            },
+            8 => {
+                if let Err(e) = show_dead_letters() {
+                    eprintln!("Error displaying dead-lettered messages: {}", e);
+                }
+            }
+            9 => {
                 println!("Exiting CLI.");
                 break;
             }
@@ -537,15 +1364,40 @@ pub async fn run_cli() {
 
 // Publishes a manual traffic light phase adjustment to the "light_adjustments" queue.
 pub fn adjust_traffic_light_phase(intersection_id: String, new_duration: u32) -> AmiquipResult<()> {
-    let mut connection = Connection::insecure_open(AMQP_URL)?;
+    let mut connection = connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
     let channel = connection.open_channel(None)?;
     let exchange = Exchange::direct(&channel);
     let adjustment = LightAdjustment {
         timestamp: current_timestamp(),
         intersection_id,
         add_seconds_green: new_duration,
+        run_id: String::new(),
     };
     let payload = serde_json::to_string(&adjustment).unwrap();
-    exchange.publish(Publish::new(payload.as_bytes(), QUEUE_LIGHT_ADJUSTMENTS))?;
+    exchange.publish(Publish::new(
+        payload.as_bytes(),
+        queue_name(QUEUE_LIGHT_ADJUSTMENTS),
+    ))?;
+    connection.close()
+}
+
+// Publishes a pause/resume command to the "simulation_control" queue.
+pub fn send_simulation_command(command: SimulationCommand) -> AmiquipResult<()> {
+    let mut connection = connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)?;
+    let channel = connection.open_channel(None)?;
+    let exchange = Exchange::direct(&channel);
+    channel.queue_declare(
+        queue_name(QUEUE_SIMULATION_CONTROL),
+        QueueDeclareOptions::default(),
+    )?;
+    let control = SimulationControl {
+        timestamp: current_timestamp(),
+        command,
+    };
+    let payload = serde_json::to_string(&control).unwrap();
+    exchange.publish(Publish::new(
+        payload.as_bytes(),
+        queue_name(QUEUE_SIMULATION_CONTROL),
+    ))?;
     connection.close()
 }
@@ -6,3 +6,44 @@ pub const QUEUE_TRAFFIC_DATA: &str = "traffic_data";
 pub const QUEUE_CONGESTION_ALERTS: &str = "congestion_alerts";
 pub const QUEUE_TRAFFIC_EVENTS: &str = "traffic_events";
 pub const QUEUE_LIGHT_ADJUSTMENTS: &str = "light_adjustments";
+pub const QUEUE_SIMULATION_EVENTS: &str = "simulation_events";
+pub const QUEUE_SIMULATION_CONTROL: &str = "simulation_control";
+pub const QUEUE_SPEEDING_ALERTS: &str = "speeding_alerts";
+// Analyzer's predicted TrafficData, consumed by the traffic light controller.
+pub const QUEUE_TRAFFIC_PREDICTIONS: &str = "traffic_predictions";
+// Light adjustments the controller made, for the simulation to apply to its own traffic lights.
+// The monitoring system logs the same adjustments from QUEUE_LIGHT_ADJUSTMENTS.
+pub const QUEUE_SIGNAL_ADJUSTMENTS: &str = "signal_adjustments";
+// Traffic light controller's own queue for congestion alerts.
+pub const QUEUE_CONTROLLER_ALERTS: &str = "controller_congestion_alerts";
+// Messages that failed to deserialize, republished with the parse error for debugging.
+pub const QUEUE_DEAD_LETTER: &str = "dead_letter";
+
+// Topic exchange congestion alerts are published on, with routing keys like congestion.row0.col2.
+pub const EXCHANGE_CONGESTION_ALERTS: &str = "congestion_alerts_topic";
+pub const DEFAULT_CONGESTION_BINDING: &str = "congestion.#";
+
+// Broker URL, overridable with the AMQP_URL environment variable.
+pub fn amqp_url() -> String {
+    std::env::var("AMQP_URL").unwrap_or_else(|_| AMQP_URL.to_string())
+}
+
+// Environment variable that overrides a queue: QUEUE_ followed by the default queue name in upper
+// case. This matches the constant's name except for QUEUE_CONTROLLER_ALERTS, whose queue is
+// controller_congestion_alerts and so is overridden with QUEUE_CONTROLLER_CONGESTION_ALERTS.
+pub fn queue_env_var(default: &str) -> String {
+    format!("QUEUE_{}", default.to_uppercase())
+}
+
+// Queue name for one of the QUEUE_* defaults above, overridable with queue_env_var(default),
+// e.g. QUEUE_TRAFFIC_DATA=traffic_data_staging.
+pub fn queue_name(default: &str) -> String {
+    std::env::var(queue_env_var(default)).unwrap_or_else(|_| default.to_string())
+}
+
+// Binding pattern the monitoring system subscribes to congestion alerts with, overridable with
+// CONGESTION_ALERT_BINDING (e.g. congestion.row0.* for a single corridor).
+pub fn congestion_binding() -> String {
+    std::env::var("CONGESTION_ALERT_BINDING")
+        .unwrap_or_else(|_| DEFAULT_CONGESTION_BINDING.to_string())
+}
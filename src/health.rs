@@ -0,0 +1,91 @@
+// health.rs
+use crate::http::{serve_get, HttpResponse};
+use crate::shared_data::current_timestamp;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+// How long a service may go without processing a message before it reports stale, unless
+// HEALTH_STALE_SECS says otherwise.
+pub const DEFAULT_HEALTH_STALE_SECS: u64 = 60;
+
+// When a long-running service last did useful work. A service that has just started counts as
+// fresh, so it isn't reported stale before the first message could arrive.
+#[derive(Debug, Clone)]
+pub struct Liveness {
+    pub started_at: u64,
+    pub last_message_at: Option<u64>,
+}
+
+impl Default for Liveness {
+    fn default() -> Self {
+        Self {
+            started_at: current_timestamp(),
+            last_message_at: None,
+        }
+    }
+}
+
+impl Liveness {
+    pub fn record_message(&mut self) {
+        self.last_message_at = Some(current_timestamp());
+    }
+
+    // Whether a message was processed (or the service started) within `stale_secs` of `now`.
+    pub fn is_fresh_at(&self, now: u64, stale_secs: u64) -> bool {
+        let last = self.last_message_at.unwrap_or(self.started_at);
+        now.saturating_sub(last) <= stale_secs
+    }
+
+    pub fn is_fresh(&self, stale_secs: u64) -> bool {
+        self.is_fresh_at(current_timestamp(), stale_secs)
+    }
+}
+
+pub type SharedLiveness = Arc<Mutex<Liveness>>;
+
+// Serves GET /health on the given port until the task is dropped: 200 OK while the service is
+// fresh, 503 once it has gone `stale_secs` without a message. Any other path gets a 404.
+// Only failing to bind the port is returned as an error.
+pub async fn serve_health(
+    liveness: SharedLiveness,
+    port: u16,
+    stale_secs: u64,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("Serving health check on port {}", port);
+    serve_get(listener, "/health", move || {
+        let (status, body) = if liveness.lock().unwrap().is_fresh(stale_secs) {
+            ("200 OK", "OK")
+        } else {
+            ("503 Service Unavailable", "STALE")
+        };
+        HttpResponse {
+            status,
+            content_type: "text/plain",
+            body: body.to_string(),
+        }
+    })
+    .await;
+    Ok(())
+}
+
+// Starts the health endpoint in the background if HEALTH_PORT is set, using HEALTH_STALE_SECS
+// (default DEFAULT_HEALTH_STALE_SECS) as the staleness window.
+pub fn spawn_health_from_env(liveness: &SharedLiveness) {
+    let Some(port) = std::env::var("HEALTH_PORT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+    else {
+        return;
+    };
+    let stale_secs = std::env::var("HEALTH_STALE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_HEALTH_STALE_SECS);
+    let liveness = Arc::clone(liveness);
+    tokio::spawn(async move {
+        if let Err(e) = serve_health(liveness, port, stale_secs).await {
+            eprintln!("Error in health server: {}", e);
+        }
+    });
+}
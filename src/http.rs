@@ -0,0 +1,78 @@
+// http.rs
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// How long to back off after a failed accept (e.g. out of file descriptors) before retrying.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// A plain HTTP response: status line without the version (e.g. "200 OK"), content type and body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn to_http(&self) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+const NOT_FOUND: &str = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+// Whether the request line is a GET for exactly `path`. A query string is ignored, but a longer
+// path sharing the prefix (e.g. /healthz for /health) is not a match.
+fn is_get_for(request: &str, path: &str) -> bool {
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (Some("GET"), Some(target)) = (request_line.next(), request_line.next()) else {
+        return false;
+    };
+    target.split('?').next() == Some(path)
+}
+
+// Answers GET `path` on the listener with `respond()` until the task is dropped. Any other path
+// gets a 404. A failed accept is logged and the server keeps listening.
+pub async fn serve_get<F>(listener: TcpListener, path: &'static str, respond: F)
+where
+    F: Fn() -> HttpResponse + Send + Sync + 'static,
+{
+    let respond = Arc::new(respond);
+    loop {
+        let mut socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                eprintln!("Error accepting connection for {}: {}", path, e);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let respond = Arc::clone(&respond);
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = match socket.read(&mut buf).await {
+                Ok(n) => n,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let response = if is_get_for(&request, path) {
+                respond().to_http()
+            } else {
+                NOT_FOUND.to_string()
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
@@ -1,6 +1,10 @@
+pub mod amqp;
+pub mod bus;
 pub mod c1_tp063879;
 pub mod c2_tp063881;
 pub mod c3_tp063987;
 pub mod c4_tp071994;
 pub mod global_variables;
+pub mod health;
+pub mod http;
 pub mod shared_data;
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    time::{SystemTime, UNIX_EPOCH},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 // shared structs
@@ -12,7 +13,23 @@ pub struct VehicleData {
     pub accident_timestamp: Option<u64>,
     pub severity: i8,
     pub current_lane: String,
+    // Other vehicle involved when the accident was a two-vehicle collision.
+    #[serde(default)]
+    pub collided_with: Option<u64>,
+    // Litres of fuel burned over the journey.
+    #[serde(default)]
+    pub fuel_used: f64,
 }
+// Speeds of the recent vehicles on one lane.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LaneSpeedStats {
+    pub samples: usize,
+    // 85th-percentile top speed (km/h).
+    pub speed_p85: f64,
+    // Share of the sampled vehicles that were overspeeding.
+    pub overspeed_ratio: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrafficData {
     pub lane_occupancy: HashMap<String, f64>,
@@ -20,12 +37,29 @@ pub struct TrafficData {
     pub intersection_congestion: HashMap<String, f64>,
     pub intersection_waiting_time: HashMap<String, f64>,
     pub vehicle_data: Vec<VehicleData>,
+    // Per intersection, the average occupancy of its outgoing lanes in the busiest heading, so a
+    // jammed approach isn't diluted by empty ones the way intersection_congestion is.
+    #[serde(default)]
+    pub intersection_max_direction_congestion: HashMap<String, f64>,
+    // Average red-light wait per lane. An intersection's waiting time is the same waits pooled
+    // over its outgoing lanes.
+    #[serde(default)]
+    pub lane_waiting_time: HashMap<String, f64>,
+    // Per-lane speed statistics; lanes no vehicle has entered yet are left out.
+    #[serde(default)]
+    pub lane_speeds: HashMap<String, LaneSpeedStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrafficUpdate {
     pub current_data: TrafficData,
     pub timestamp: u64,
+    // Identifies the simulation run that produced the message (empty for untagged messages).
+    #[serde(default)]
+    pub run_id: String,
+    // Vehicles that reached their destination per simulated minute over the last tick.
+    #[serde(default)]
+    pub throughput_per_min: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +68,9 @@ pub struct AccidentInfo {
     pub accident_timestamp: u64,
     pub severity: i8,
     pub current_lane: String,
+    // All vehicles involved in the accident, including vehicle_id (two for a collision).
+    #[serde(default)]
+    pub involved_vehicle_ids: Vec<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +79,17 @@ pub struct TrafficEvent {
     pub average_vehicle_delay: f64,
     pub total_accidents: usize,
     pub accident_details: Vec<AccidentInfo>,
+    // Network congestion gauge from 0 (empty) to 100 (saturated).
+    #[serde(default)]
+    pub congestion_index: f64,
+    // Fuel burned by the vehicles reported in this update (litres).
+    #[serde(default)]
+    pub total_fuel_used: f64,
+    // Network throughput carried over from the TrafficUpdate (arrivals per minute).
+    #[serde(default)]
+    pub throughput_per_min: f64,
+    #[serde(default)]
+    pub run_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +99,20 @@ pub struct CongestionAlert {
     pub message: String,
     pub congestion_perc: f64,
     pub recommended_action: String,
+    #[serde(default)]
+    pub run_id: String,
+}
+
+// Raised for a lane where a large share of recent vehicles were overspeeding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedingHotspotAlert {
+    pub timestamp: u64,
+    pub lane: String,
+    pub overspeed_ratio: f64,
+    pub speed_p85: f64,
+    pub samples: usize,
+    #[serde(default)]
+    pub run_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +120,50 @@ pub struct LightAdjustment {
     pub timestamp: u64,
     pub intersection_id: String,
     pub add_seconds_green: u32,
+    #[serde(default)]
+    pub run_id: String,
+}
+
+// A traffic light switching: the next phase starting, or an emergency override or pedestrian
+// crossing starting or ending. `intersection_id` is the IntersectionId as a u32 (see its From
+// impl); `is_green` is whether any lane at the intersection is green now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficLightChanged {
+    pub intersection_id: u32,
+    pub is_green: bool,
+}
+
+// Published once per run when network congestion and active vehicle count have stabilized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SteadyStateReached {
+    pub timestamp: u64,
+    pub elapsed_secs: u64,
+    pub average_congestion: f64,
+    pub active_vehicles: usize,
+    #[serde(default)]
+    pub run_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulationCommand {
+    Pause,
+    Resume,
+}
+
+// Control message sent from the admin CLI to the running simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationControl {
+    pub timestamp: u64,
+    pub command: SimulationCommand,
+}
+
+// A message a consumer couldn't deserialize, kept with the error instead of being dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub timestamp: u64,
+    pub source_queue: String,
+    pub body: String,
+    pub error: String,
 }
 
 // shared functions
@@ -67,3 +173,55 @@ pub fn current_timestamp() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+// Converts a simulated duration into the wall-clock duration to sleep for.
+// A time_scale of 2.0 runs twice as fast, 0.5 runs in slow motion; non-positive values are treated as 1.0.
+pub fn scale_duration(duration: Duration, time_scale: f64) -> Duration {
+    if time_scale > 0.0 {
+        duration.div_f64(time_scale)
+    } else {
+        duration
+    }
+}
+
+// Sleeps for `duration` in small slices, freezing the remaining time while `paused` is set.
+// Resuming continues the sleep where it stopped instead of returning immediately.
+pub async fn pausable_sleep(duration: Duration, paused: &AtomicBool) {
+    let slice = Duration::from_millis(100);
+    let mut remaining = duration;
+    while !remaining.is_zero() {
+        if paused.load(Ordering::SeqCst) {
+            tokio::time::sleep(slice).await;
+            continue;
+        }
+        let step = remaining.min(slice);
+        tokio::time::sleep(step).await;
+        remaining -= step;
+    }
+}
+
+// Simulated seconds elapsed since the wall-clock timestamp `start` under the given time_scale.
+pub fn scaled_elapsed_since(start: u64, time_scale: f64) -> u64 {
+    let wall_elapsed = current_timestamp().saturating_sub(start) as f64;
+    if time_scale > 0.0 {
+        (wall_elapsed * time_scale).round() as u64
+    } else {
+        wall_elapsed as u64
+    }
+}
+
+// Returns true if a message tagged with `run_id` should be processed by a consumer
+// configured with `run_filter`. No filter accepts every run.
+pub fn matches_run_filter(run_filter: &Option<String>, run_id: &str) -> bool {
+    match run_filter {
+        Some(filter) => filter == run_id,
+        None => true,
+    }
+}
+
+// Reads the optional run id filter for consumers from the RUN_ID_FILTER environment variable.
+pub fn run_id_filter_from_env() -> Option<String> {
+    std::env::var("RUN_ID_FILTER")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+}
@@ -0,0 +1,59 @@
+// Accidents are tallied per lane from the accident_info log, and lanes with a high accident rate
+// are flagged high-risk.
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    lane_accident_risk, load_accident_history, tally_accidents_by_lane,
+    HIGH_RISK_ACCIDENTS_PER_HOUR,
+};
+use std::collections::HashMap;
+
+const FIXTURE: &str = r#"vehicle_id,accident_timestamp,severity,current_lane,involved_vehicle_ids
+4,1000,2,"(0,0) -> (0,1)",4
+9,1010,5,"(1,1) -> (1,2)",9;12
+15,1020,1,"(0,0) -> (0,1)",15
+21,1030,3,"(0,0) -> (0,1)",21
+30,1040,4,"(2,3) -> (3,3)",30
+"#;
+
+#[test]
+fn tally_matches_the_fixture() {
+    let path = std::env::temp_dir().join(format!("rts_accidents_{}.csv", std::process::id()));
+    std::fs::write(&path, FIXTURE).unwrap();
+    let tally = tally_accidents_by_lane(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let expected: HashMap<String, usize> = [
+        ("(0,0) -> (0,1)", 3),
+        ("(1,1) -> (1,2)", 1),
+        ("(2,3) -> (3,3)", 1),
+    ]
+    .into_iter()
+    .map(|(lane, count)| (lane.to_string(), count))
+    .collect();
+    assert_eq!(tally, expected);
+}
+
+#[test]
+fn a_lane_with_many_accidents_is_flagged_high_risk() {
+    let path = std::env::temp_dir().join(format!("rts_accident_risk_{}.csv", std::process::id()));
+    std::fs::write(&path, FIXTURE).unwrap();
+    let records = load_accident_history(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The fixture spans under an hour, so counts are per hour.
+    let risks = lane_accident_risk(&records, HIGH_RISK_ACCIDENTS_PER_HOUR);
+    assert_eq!(risks.len(), 3);
+    assert_eq!(risks[0].lane, "(0,0) -> (0,1)");
+    assert_eq!(risks[0].accidents, 3);
+    assert_eq!(risks[0].accidents_per_hour, 3.0);
+    assert!(risks[0].high_risk);
+    for clean in &risks[1..] {
+        assert_eq!(clean.accidents, 1);
+        assert!(!clean.high_risk, "{} flagged", clean.lane);
+    }
+
+    // Spread over three hours, the same accidents fall below the threshold.
+    let mut spread = records.clone();
+    spread.last_mut().unwrap().accident_timestamp = 1_000 + 3 * 3_600;
+    let risks = lane_accident_risk(&spread, HIGH_RISK_ACCIDENTS_PER_HOUR);
+    assert!(risks.iter().all(|risk| !risk.high_risk));
+}
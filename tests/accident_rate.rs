@@ -0,0 +1,77 @@
+// Each vehicle rolls for a crash once per lane, at the configured probability.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionControl};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+
+const TIME_SCALE: f64 = 1_000.0;
+const CRASH_PROBABILITY: f64 = 0.05;
+const JOURNEYS: u64 = 2_000;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn single_lane_journeys_crash_at_the_configured_rate() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let lane = lanes
+        .iter()
+        .find(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control != IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving an uncontrolled intersection")
+        .clone();
+    let controller = Arc::new(RwLock::new(TrafficLightController::initialize(
+        intersections.clone(),
+        &lanes,
+    )));
+    let config = Arc::new(SimulationConfig {
+        crash_probability: CRASH_PROBABILITY,
+        overspeed_crash_probability: CRASH_PROBABILITY,
+        collision_probability: 0.0,
+        ..SimulationConfig::default()
+    });
+    let vehicle_events = Arc::new(Mutex::new(Vec::new()));
+
+    // One seeded vehicle per journey, each on an empty copy of the network.
+    let journeys: Vec<_> = (0..JOURNEYS)
+        .map(|id| {
+            let ctx = JourneyContext {
+                active_ids: Arc::new(Mutex::new(HashSet::from([id]))),
+                vehicle_events: Arc::clone(&vehicle_events),
+                time_scale: TIME_SCALE,
+                ..JourneyContext::new(
+                    Arc::new(RwLock::new(intersections.clone())),
+                    Arc::new(LaneStore::new(lanes.clone())),
+                    Arc::clone(&controller),
+                    Arc::clone(&config),
+                )
+            };
+            tokio::spawn(simulate_vehicle_journey(
+                Vehicle::new(id, VehicleType::Car, lane.from, lane.to, 40.0),
+                vec![lane.clone()],
+                SmallRng::seed_from_u64(id),
+                ctx,
+            ))
+        })
+        .collect();
+    for journey in journeys {
+        journey.await.unwrap();
+    }
+
+    let events = vehicle_events.lock().unwrap();
+    assert_eq!(events.len() as u64, JOURNEYS);
+    let crashes = events
+        .iter()
+        .filter(|e| e.accident_timestamp.is_some())
+        .count();
+    let rate = crashes as f64 / JOURNEYS as f64;
+    // Within three standard deviations of 5%; a second roll per lane would give about 9.75%.
+    assert!((0.035..=0.065).contains(&rate), "crash rate {}", rate);
+}
@@ -0,0 +1,256 @@
+// The analyzer's handling of TrafficUpdates, run against the in-memory bus.
+use rts_assignment::amqp::congestion_routing_key;
+use rts_assignment::bus::{InMemoryBus, MessageBus};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::collect_traffic_data;
+use rts_assignment::c2_tp063881::traffic_analyzer::{
+    analyze_traffic_data, compute_congestion_index, compute_congestion_index_with,
+    handle_traffic_update, predict_future_traffic_weighted, publish_prediction, run_analyzer,
+    AnalyzerThresholds, CongestionIndexWeights, HistoricalData,
+};
+use rts_assignment::global_variables::{
+    queue_name, QUEUE_TRAFFIC_DATA, QUEUE_TRAFFIC_EVENTS, QUEUE_TRAFFIC_PREDICTIONS,
+};
+use rts_assignment::health::Liveness;
+use rts_assignment::shared_data::{CongestionAlert, TrafficData, TrafficUpdate};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+// One intersection with its only outgoing lane nearly full.
+fn congested_data() -> TrafficData {
+    TrafficData {
+        lane_occupancy: HashMap::from([("(0, 0) -> (0, 1)".to_string(), 0.9)]),
+        accident_lanes: HashSet::new(),
+        intersection_congestion: HashMap::from([("(0, 0)".to_string(), 0.9)]),
+        intersection_waiting_time: HashMap::from([("(0, 0)".to_string(), 4.0)]),
+        vehicle_data: Vec::new(),
+        intersection_max_direction_congestion: HashMap::new(),
+        lane_waiting_time: HashMap::new(),
+        lane_speeds: HashMap::new(),
+    }
+}
+
+// Every lane at `occupancy`, with each intersection's congestion matching it.
+fn uniform_data(occupancy: f64) -> TrafficData {
+    let lanes = ["(0, 0) -> (0, 1)", "(0, 1) -> (1, 1)", "(1, 1) -> (1, 2)"];
+    let intersections = ["(0, 0)", "(0, 1)", "(1, 1)"];
+    TrafficData {
+        lane_occupancy: lanes.iter().map(|l| (l.to_string(), occupancy)).collect(),
+        intersection_congestion: intersections
+            .iter()
+            .map(|i| (i.to_string(), occupancy))
+            .collect(),
+        ..congested_data()
+    }
+}
+
+fn update_payload(run_id: &str, data: TrafficData) -> Vec<u8> {
+    let update = TrafficUpdate {
+        current_data: data,
+        timestamp: 1_000,
+        run_id: run_id.to_string(),
+        throughput_per_min: 0.0,
+    };
+    serde_json::to_vec(&update).unwrap()
+}
+
+// Feeds one payload to a fresh analyzer, returning the bus and the data it kept as latest.
+fn handle(payload: &[u8], run_filter: Option<&str>) -> (InMemoryBus, Option<TrafficData>) {
+    let bus = InMemoryBus::new();
+    let historical = Mutex::new(HistoricalData::new(10));
+    let latest_data = Mutex::new(None);
+    handle_traffic_update(
+        &bus,
+        payload,
+        &run_filter.map(str::to_string),
+        &AnalyzerThresholds::default(),
+        &historical,
+        &latest_data,
+    )
+    .unwrap();
+    (bus, latest_data.into_inner().unwrap())
+}
+
+#[test]
+fn run_filter_ignores_updates_from_other_runs() {
+    let (bus, latest) = handle(&update_payload("B", congested_data()), Some("A"));
+    assert!(bus.published().is_empty());
+    assert!(latest.is_none());
+
+    let (bus, latest) = handle(&update_payload("A", congested_data()), Some("A"));
+    assert_eq!(bus.published_to(&queue_name(QUEUE_TRAFFIC_EVENTS)).len(), 1);
+    assert!(latest.is_some());
+}
+
+// Congestion alerts the analyzer published on `bus`, in order.
+fn published_alerts(bus: &InMemoryBus) -> Vec<(String, CongestionAlert)> {
+    bus.published()
+        .into_iter()
+        .filter(|(key, _)| key.starts_with("congestion."))
+        .map(|(key, payload)| (key, serde_json::from_slice(&payload).unwrap()))
+        .collect()
+}
+
+#[test]
+fn a_congested_update_publishes_one_alert_per_congested_intersection() {
+    let (bus, _) = handle(&update_payload("A", congested_data()), None);
+    let alerts = published_alerts(&bus);
+    assert_eq!(alerts.len(), 1);
+    let (routing_key, alert) = &alerts[0];
+    assert_eq!(alert.intersection.as_deref(), Some("(0, 0)"));
+    assert_eq!(
+        *routing_key,
+        congestion_routing_key(alert.intersection.as_deref())
+    );
+    assert_eq!(alert.run_id, "A");
+    assert!(alert.congestion_perc > 0.0);
+
+    let (bus, _) = handle(&update_payload("A", uniform_data(0.1)), None);
+    assert!(published_alerts(&bus).is_empty());
+    assert_eq!(bus.published_to(&queue_name(QUEUE_TRAFFIC_EVENTS)).len(), 1);
+}
+
+#[test]
+fn congestion_index_spans_empty_to_saturated() {
+    let empty = TrafficData {
+        lane_occupancy: HashMap::new(),
+        intersection_congestion: HashMap::new(),
+        ..congested_data()
+    };
+    assert_eq!(compute_congestion_index(&empty), 0.0);
+    assert_eq!(compute_congestion_index(&uniform_data(0.0)), 0.0);
+    assert_eq!(compute_congestion_index(&uniform_data(1.0)), 100.0);
+
+    let partial = compute_congestion_index(&uniform_data(0.3));
+    assert!(partial > 0.0 && partial < 100.0, "{}", partial);
+}
+
+#[test]
+fn signalling_shutdown_stops_the_analyzer_promptly() {
+    let bus = InMemoryBus::new();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let liveness = Mutex::new(Liveness::default());
+    let historical = Mutex::new(HistoricalData::new(10));
+    let latest_data = Mutex::new(None);
+
+    thread::scope(|scope| {
+        let analyzer = scope.spawn(|| {
+            run_analyzer(
+                &bus,
+                &None,
+                &AnalyzerThresholds::default(),
+                &liveness,
+                &shutdown_rx,
+                &historical,
+                &latest_data,
+            )
+        });
+
+        // Updates published before the analyzer subscribes are dropped, so keep sending until
+        // one has been handled.
+        let events = queue_name(QUEUE_TRAFFIC_EVENTS);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while bus.published_to(&events).is_empty() {
+            assert!(
+                Instant::now() < deadline,
+                "analyzer never handled an update"
+            );
+            bus.publish(
+                &queue_name(QUEUE_TRAFFIC_DATA),
+                &update_payload("", congested_data()),
+            )
+            .unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let signalled = Instant::now();
+        shutdown_tx.send(true).unwrap();
+        analyzer.join().unwrap().unwrap();
+        assert!(signalled.elapsed() < Duration::from_secs(1));
+    });
+    assert!(liveness.lock().unwrap().last_message_at.is_some());
+}
+
+#[test]
+fn the_analyzer_reads_what_the_simulation_publishes() {
+    let published = TrafficUpdate {
+        current_data: collect_traffic_data(&create_lanes(), &create_intersections(), vec![]),
+        timestamp: 1_000,
+        run_id: "sim".to_string(),
+        throughput_per_min: 0.0,
+    };
+    let json = serde_json::to_vec(&published).unwrap();
+    let received: TrafficUpdate = serde_json::from_slice(&json).unwrap();
+    let data = received.current_data;
+    assert_eq!(
+        data.intersection_congestion.len(),
+        create_intersections().len()
+    );
+
+    let mut historical = HistoricalData::new(3);
+    historical.update_occupancy(&data);
+    historical.update_waiting_time(&data.intersection_waiting_time);
+    let predicted = predict_future_traffic_weighted(&data, &historical, 0.7);
+    let mut keys: Vec<&String> = predicted.intersection_congestion.keys().collect();
+    let mut expected: Vec<&String> = data.intersection_congestion.keys().collect();
+    keys.sort();
+    expected.sort();
+    assert_eq!(keys, expected);
+    // An empty network predicts empty and raises no alerts.
+    assert!(predicted
+        .intersection_congestion
+        .values()
+        .all(|&c| c == 0.0));
+    assert!(analyze_traffic_data(&data, &AnalyzerThresholds::default()).is_empty());
+}
+
+#[test]
+fn a_prediction_is_published_once_the_history_is_full() {
+    let bus = InMemoryBus::new();
+    let historical = Mutex::new(HistoricalData::new(3));
+    let latest_data = Mutex::new(None);
+    let predictions = queue_name(QUEUE_TRAFFIC_PREDICTIONS);
+
+    // Nothing is predicted from fewer than a full window of updates.
+    for _ in 0..3 {
+        assert!(!publish_prediction(&bus, &historical, &latest_data).unwrap());
+        assert!(bus.published_to(&predictions).is_empty());
+        handle_traffic_update(
+            &bus,
+            &update_payload("A", congested_data()),
+            &None,
+            &AnalyzerThresholds::default(),
+            &historical,
+            &latest_data,
+        )
+        .unwrap();
+    }
+
+    assert!(publish_prediction(&bus, &historical, &latest_data).unwrap());
+    let published = bus.published_to(&predictions);
+    assert_eq!(published.len(), 1);
+    let predicted: TrafficData = serde_json::from_slice(&published[0]).unwrap();
+    assert!(predicted.intersection_congestion.contains_key("(0, 0)"));
+}
+
+#[test]
+fn a_higher_threshold_suppresses_alerts_the_default_raises() {
+    let data = uniform_data(0.85);
+    let defaults = AnalyzerThresholds::default();
+    assert_eq!(analyze_traffic_data(&data, &defaults).len(), 3);
+
+    let raised = AnalyzerThresholds {
+        intersection_congestion: 0.9,
+        ..defaults
+    };
+    assert!(analyze_traffic_data(&data, &raised).is_empty());
+    // The congestion index counts alerting intersections against the same threshold.
+    assert!(
+        compute_congestion_index_with(&data, &CongestionIndexWeights::default(), &raised)
+            < compute_congestion_index(&data)
+    );
+}
@@ -0,0 +1,170 @@
+// The publisher sheds traffic updates while the queue is deeper than its limit.
+use rts_assignment::bus::{
+    BusResult, InMemoryBus, MessageBus, PublishRecovery, Subscription, DEPTH_SAMPLE_INTERVAL,
+};
+use rts_assignment::c1_tp063879::config::{CrashSeverityConfig, RushHourConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{run_simulation_on, DeferredVehicleData, RunContext};
+use rts_assignment::global_variables::{queue_name, QUEUE_TRAFFIC_DATA};
+use rts_assignment::shared_data::{TrafficUpdate, VehicleData};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const QUEUE: &str = "traffic_data";
+
+// Publishes `count` updates through `publisher`, skipping backlogged ones. Returns how many were
+// sent.
+fn publish_updates(publisher: &mut PublishRecovery, bus: &InMemoryBus, count: usize) -> usize {
+    (0..count)
+        .filter(|i| {
+            !publisher.is_backlogged(bus, QUEUE)
+                && publisher.publish(bus, QUEUE, format!("update {}", i).as_bytes())
+        })
+        .count()
+}
+
+#[test]
+fn updates_are_dropped_once_the_queue_exceeds_the_limit() {
+    // Nobody consumes the queue, so every published update adds to its depth.
+    let bus = InMemoryBus::new();
+    let mut publisher = PublishRecovery::new()
+        .with_max_queue_depth(Some(3))
+        .with_depth_sample_interval(1);
+
+    // Depths 0 through 3 are within the limit; from depth 4 on everything is shed.
+    assert_eq!(publish_updates(&mut publisher, &bus, 10), 4);
+    assert_eq!(bus.queue_depth(QUEUE).unwrap(), Some(4));
+    assert_eq!(bus.published_to(QUEUE).len(), 4);
+
+    // Once the consumer catches up publishing resumes.
+    bus.set_queue_depth(QUEUE, 0);
+    assert_eq!(publish_updates(&mut publisher, &bus, 2), 2);
+    assert_eq!(bus.published_to(QUEUE).len(), 6);
+}
+
+#[test]
+fn without_a_limit_every_update_is_published() {
+    let bus = InMemoryBus::new();
+    let mut publisher = PublishRecovery::new();
+
+    assert_eq!(publish_updates(&mut publisher, &bus, 10), 10);
+    assert_eq!(bus.queue_depth(QUEUE).unwrap(), Some(10));
+}
+
+// A queue that always reports the same depth and counts how often it was asked.
+struct FixedDepthBus {
+    depth: AtomicU32,
+    reads: AtomicU32,
+}
+
+impl MessageBus for FixedDepthBus {
+    fn publish(&self, _queue: &str, _payload: &[u8]) -> BusResult<()> {
+        Ok(())
+    }
+
+    fn subscribe(&self, _queue: &str) -> BusResult<Subscription> {
+        Err("not supported".into())
+    }
+
+    fn queue_depth(&self, _queue: &str) -> BusResult<Option<u32>> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        Ok(Some(self.depth.load(Ordering::SeqCst)))
+    }
+}
+
+#[test]
+fn the_depth_is_only_read_every_sample_interval() {
+    let bus = FixedDepthBus {
+        depth: AtomicU32::new(10),
+        reads: AtomicU32::new(0),
+    };
+    let mut publisher = PublishRecovery::new().with_max_queue_depth(Some(3));
+
+    // Every call in the interval goes by the first reading.
+    for _ in 0..DEPTH_SAMPLE_INTERVAL {
+        assert!(publisher.is_backlogged(&bus, QUEUE));
+    }
+    assert_eq!(bus.reads.load(Ordering::SeqCst), 1);
+
+    // The queue drains, but that is only noticed at the next reading.
+    bus.depth.store(0, Ordering::SeqCst);
+    assert!(!publisher.is_backlogged(&bus, QUEUE));
+    assert_eq!(bus.reads.load(Ordering::SeqCst), 2);
+}
+
+fn crash_event(id: u64) -> VehicleData {
+    VehicleData {
+        id,
+        waiting_time: 0,
+        accident_timestamp: Some(id),
+        severity: 1,
+        current_lane: "(0,0) -> (0,1)".to_string(),
+        collided_with: None,
+        fuel_used: 0.0,
+    }
+}
+
+#[test]
+fn deferred_events_beyond_the_cap_drop_the_oldest() {
+    let mut deferred = DeferredVehicleData::new(3);
+    let mut first_tick: Vec<VehicleData> = (1..=2).map(crash_event).collect();
+    assert_eq!(deferred.defer(&mut first_tick), 0);
+    assert!(first_tick.is_empty());
+
+    let mut second_tick: Vec<VehicleData> = (3..=5).map(crash_event).collect();
+    assert_eq!(deferred.defer(&mut second_tick), 2);
+    assert_eq!(deferred.dropped(), 2);
+
+    let ids: Vec<u64> = deferred.take().iter().map(|event| event.id).collect();
+    assert_eq!(ids, [3, 4, 5]);
+    assert!(deferred.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_bounded_run_that_ends_backlogged_still_sends_its_deferred_events() {
+    let bus = InMemoryBus::new();
+    let queue = queue_name(QUEUE_TRAFFIC_DATA);
+    // The analyzer never catches up, so every tick but the last is skipped.
+    bus.set_queue_depth(&queue, 1_000);
+    // Every vehicle crashes on its first lane and is cleared at once, so each tick has events.
+    let config = SimulationConfig {
+        rush_hour: RushHourConfig::new(40, 2, 2),
+        crash_probability: 1.0,
+        overspeed_crash_probability: 1.0,
+        crash_severity: CrashSeverityConfig {
+            clearance_secs_per_severity: 0.0,
+            ..CrashSeverityConfig::default()
+        },
+        max_traffic_data_backlog: Some(10),
+        ..SimulationConfig::default()
+    };
+    let run = run_simulation_on(
+        &bus,
+        Arc::new(RwLock::new(create_intersections())),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        RunContext {
+            time_scale: 50.0,
+            max_ticks: Some(20),
+            ..RunContext::new(5, "backlogged")
+        },
+    );
+    let summary = tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("bounded run finished");
+    assert!(summary.total_spawned > 0);
+
+    let updates: Vec<TrafficUpdate> = bus
+        .published_to(&queue)
+        .iter()
+        .map(|payload| serde_json::from_slice(payload).unwrap())
+        .collect();
+    assert_eq!(updates.len(), 1, "only the last tick should publish");
+    let crashes = &updates[0].current_data.vehicle_data;
+    assert!(!crashes.is_empty(), "the deferred crashes were lost");
+    assert!(crashes
+        .iter()
+        .all(|event| event.accident_timestamp.is_some()));
+}
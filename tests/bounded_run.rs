@@ -0,0 +1,223 @@
+// Bounded simulation runs over the in-memory bus: they end on their own and account for every
+// vehicle they spawned.
+use rts_assignment::bus::InMemoryBus;
+use rts_assignment::c1_tp063879::config::{RushHourConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::event_log::SimulationEvent;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{run_simulation_on, RunContext, SimulationSummary};
+use rts_assignment::global_variables::{queue_name, QUEUE_TRAFFIC_DATA};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const TIME_SCALE: f64 = 50.0;
+
+// Runs `ticks` spawn iterations on `bus` with `spawns` vehicles per tick, streaming the run's
+// events to `event_stream` if given.
+async fn bounded_run(
+    bus: &InMemoryBus,
+    ticks: u64,
+    spawns: usize,
+    time_scale: f64,
+    paused: Arc<AtomicBool>,
+    event_stream: Option<Sender<SimulationEvent>>,
+) -> SimulationSummary {
+    let config = SimulationConfig {
+        rush_hour: RushHourConfig::new(40, spawns, spawns),
+        ..SimulationConfig::default()
+    };
+    let run = RunContext {
+        time_scale,
+        paused,
+        max_ticks: Some(ticks),
+        event_stream,
+        ..RunContext::new(11, "bounded")
+    };
+    run_simulation_on(
+        bus,
+        Arc::new(RwLock::new(create_intersections())),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        run,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_bounded_run_terminates_with_consistent_counts() {
+    let bus = InMemoryBus::new();
+    let run = bounded_run(
+        &bus,
+        4,
+        2,
+        TIME_SCALE,
+        Arc::new(AtomicBool::new(false)),
+        None,
+    );
+    let summary = tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("the bounded run should finish");
+
+    assert!(summary.total_spawned > 0);
+    // Every spawned vehicle was awaited, so each one either arrived or crashed.
+    assert_eq!(
+        summary.total_arrived + summary.total_crashed,
+        summary.total_spawned,
+        "{:?}",
+        summary
+    );
+    // One traffic update per tick.
+    assert_eq!(bus.published_to(&queue_name(QUEUE_TRAFFIC_DATA)).len(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn doubling_the_time_scale_halves_the_run_time() {
+    let timed = |time_scale| async move {
+        let start = Instant::now();
+        bounded_run(
+            &InMemoryBus::new(),
+            10,
+            0,
+            time_scale,
+            Arc::new(AtomicBool::new(false)),
+            None,
+        )
+        .await;
+        start.elapsed()
+    };
+    let normal = timed(10.0).await;
+    let double = timed(20.0).await;
+    let ratio = double.as_secs_f64() / normal.as_secs_f64();
+    assert!(
+        (0.35..=0.65).contains(&ratio),
+        "10 ticks took {:?} at 10x but {:?} at 20x",
+        normal,
+        double
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn nothing_ticks_while_paused_and_resuming_does_not_catch_up() {
+    let bus = Arc::new(InMemoryBus::new());
+    let paused = Arc::new(AtomicBool::new(true));
+    let run = {
+        let (bus, paused) = (Arc::clone(&bus), Arc::clone(&paused));
+        tokio::spawn(async move { bounded_run(&bus, 3, 1, TIME_SCALE, paused, None).await })
+    };
+    let traffic_queue = queue_name(QUEUE_TRAFFIC_DATA);
+
+    // Held long enough for many ticks, yet nothing is spawned or published.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(bus.published_to(&traffic_queue).is_empty());
+    assert!(!run.is_finished());
+
+    paused.store(false, Ordering::SeqCst);
+    let summary = tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("the run should finish once resumed")
+        .unwrap();
+    // The pause isn't made up for with a burst: still exactly one update and one batch per tick.
+    assert_eq!(bus.published_to(&traffic_queue).len(), 3);
+    assert!(summary.total_spawned <= 3, "{:?}", summary);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn each_vehicle_streams_its_spawn_before_its_moves_in_route_order() {
+    let bus = InMemoryBus::new();
+    let (sender, receiver) = mpsc::channel();
+    let run = bounded_run(
+        &bus,
+        3,
+        2,
+        TIME_SCALE,
+        Arc::new(AtomicBool::new(false)),
+        Some(sender),
+    );
+    let summary = tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("the bounded run should finish");
+    let events: Vec<SimulationEvent> = receiver.try_iter().collect();
+
+    // Per vehicle: its route as spawned, and the lanes it was seen moving onto.
+    let mut routes: HashMap<u64, Vec<String>> = HashMap::new();
+    let mut moves: HashMap<u64, Vec<String>> = HashMap::new();
+    for event in &events {
+        match event {
+            SimulationEvent::VehicleSpawned {
+                vehicle_id, route, ..
+            } => {
+                assert!(
+                    !moves.contains_key(vehicle_id),
+                    "vehicle {} moved before it spawned",
+                    vehicle_id
+                );
+                routes.insert(*vehicle_id, route.clone());
+            }
+            SimulationEvent::VehicleMoved { vehicle_id, lane } => {
+                assert!(
+                    routes.contains_key(vehicle_id),
+                    "vehicle {} moved before it spawned",
+                    vehicle_id
+                );
+                moves.entry(*vehicle_id).or_default().push(lane.clone());
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(routes.len() as u64, summary.total_spawned);
+    assert!(!moves.is_empty());
+    for (vehicle_id, lanes) in &moves {
+        let route = &routes[vehicle_id];
+        assert!(
+            route.starts_with(lanes),
+            "vehicle {} moved {:?} along {:?}",
+            vehicle_id,
+            lanes,
+            route
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn vehicles_on_a_congested_grid_keep_the_route_they_spawned_with() {
+    let bus = InMemoryBus::new();
+    let (sender, receiver) = mpsc::channel();
+    // Enough vehicles per tick that lanes fill up over consecutive ticks.
+    let run = bounded_run(
+        &bus,
+        3,
+        12,
+        TIME_SCALE,
+        Arc::new(AtomicBool::new(false)),
+        Some(sender),
+    );
+    tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("the bounded run should finish");
+
+    let mut routes: HashMap<u64, Vec<String>> = HashMap::new();
+    let mut moves: HashMap<u64, Vec<String>> = HashMap::new();
+    let mut arrived = Vec::new();
+    for event in receiver.try_iter() {
+        match event {
+            SimulationEvent::VehicleSpawned {
+                vehicle_id, route, ..
+            } => {
+                routes.insert(vehicle_id, route);
+            }
+            SimulationEvent::VehicleMoved { vehicle_id, lane } => {
+                moves.entry(vehicle_id).or_default().push(lane);
+            }
+            SimulationEvent::VehicleArrived { vehicle_id, .. } => arrived.push(vehicle_id),
+            _ => {}
+        }
+    }
+    // Routes are fixed at spawn, so no vehicle is ever rerouted, let alone twice in a cooldown.
+    assert!(!arrived.is_empty());
+    for vehicle_id in arrived {
+        assert_eq!(moves[&vehicle_id], routes[&vehicle_id]);
+    }
+}
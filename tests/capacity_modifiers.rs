@@ -0,0 +1,56 @@
+// A scheduled capacity modifier shrinks a lane while it is active and restores it afterwards.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::lanes::{apply_capacity_modifiers, CapacityModifier, Lane};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+
+// Number of cars the lane admits at `elapsed` seconds, starting from empty.
+fn cars_admitted(lane: &Lane, modifiers: &[CapacityModifier], elapsed: u64) -> usize {
+    let mut lanes = vec![lane.clone()];
+    apply_capacity_modifiers(&mut lanes, modifiers, elapsed);
+    let lane = &mut lanes[0];
+    let (from, to) = (lane.from, lane.to);
+    (1..)
+        .map(|id| Vehicle::new(id, VehicleType::Car, from, to, 60.0))
+        .take_while(|car| lane.add_vehicle(car))
+        .count()
+}
+
+#[test]
+fn a_lane_admits_fewer_vehicles_during_the_window_and_recovers() {
+    let lane = Lane::new(
+        "(0,0) -> (0,1)".to_string(),
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        100.0,
+    )
+    .unwrap();
+    let storm = [CapacityModifier::new(10, 20, 0.5, vec![lane.name.clone()])];
+
+    let before = cars_admitted(&lane, &storm, 5);
+    let during = cars_admitted(&lane, &storm, 15);
+    let after = cars_admitted(&lane, &storm, 30);
+    assert!(before > 0);
+    assert!(during < before, "{} during vs {} before", during, before);
+    assert_eq!(after, before);
+}
+
+#[test]
+fn a_modifier_for_other_lanes_leaves_this_one_alone() {
+    let lane = Lane::new(
+        "(0,0) -> (0,1)".to_string(),
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        100.0,
+    )
+    .unwrap();
+    let elsewhere = [CapacityModifier::new(
+        0,
+        60,
+        0.5,
+        vec!["(1,0) -> (1,1)".to_string()],
+    )];
+    assert_eq!(
+        cars_admitted(&lane, &elsewhere, 10),
+        cars_admitted(&lane, &[], 10)
+    );
+}
@@ -0,0 +1,128 @@
+// The parallel collect_traffic_data gives exactly what the serial version does, and per-lane
+// figures add up to the per-intersection ones.
+use rts_assignment::c1_tp063879::grid::GridBuilder;
+use rts_assignment::c1_tp063879::intersections::{
+    Intersection, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::Lane;
+use rts_assignment::c1_tp063879::simulation::{collect_traffic_data, collect_traffic_data_serial};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::shared_data::TrafficData;
+
+// A rows x cols grid with a lane to the right and below every intersection, vehicles on every
+// third lane and red-light waits recorded throughout.
+fn loaded_grid(rows: i8, cols: i8) -> (Vec<Intersection>, Vec<Lane>) {
+    let mut intersections = Vec::new();
+    let mut lanes = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let intersection = Intersection::new(
+                format!("I{}_{}", row, col),
+                row,
+                col,
+                col == 0,
+                col == cols - 1,
+                IntersectionControl::TrafficLight,
+            );
+            let from = intersection.id;
+            intersections.push(intersection);
+            for to in [IntersectionId(row, col + 1), IntersectionId(row + 1, col)] {
+                if to.0 < rows && to.1 < cols {
+                    lanes.push(Lane::new(format!("{}->{}", from, to), from, to, 200.0).unwrap());
+                }
+            }
+        }
+    }
+    let mut next_id = 0;
+    for (i, lane) in lanes.iter_mut().enumerate().filter(|(i, _)| i % 3 == 0) {
+        for _ in 0..(i % 7 + 1) {
+            let vehicle = Vehicle::new(next_id, VehicleType::Car, lane.from, lane.to, 50.0);
+            lane.add_vehicle(&vehicle);
+            lane.record_wait((next_id % 11) as f64 * 0.7);
+            next_id += 1;
+        }
+    }
+    for (i, intersection) in intersections.iter_mut().enumerate() {
+        intersection.record_wait((i % 5) as f64 * 1.3);
+    }
+    (intersections, lanes)
+}
+
+#[test]
+fn parallel_and_serial_collection_agree() {
+    let (intersections, lanes) = loaded_grid(10, 20);
+    let parallel = collect_traffic_data(&lanes, &intersections, vec![]);
+    let serial = collect_traffic_data_serial(&lanes, &intersections, vec![]);
+
+    assert_eq!(parallel.intersection_congestion.len(), 200);
+    assert_eq!(
+        parallel.intersection_congestion,
+        serial.intersection_congestion
+    );
+    assert_eq!(
+        parallel.intersection_waiting_time,
+        serial.intersection_waiting_time
+    );
+    assert_eq!(
+        parallel.intersection_max_direction_congestion,
+        serial.intersection_max_direction_congestion
+    );
+    assert_eq!(parallel.lane_occupancy, serial.lane_occupancy);
+    assert_eq!(parallel.lane_waiting_time, serial.lane_waiting_time);
+    assert_eq!(parallel.accident_lanes, serial.accident_lanes);
+    // The grid isn't empty, so the comparison covers real figures.
+    assert!(parallel.intersection_congestion.values().any(|&c| c > 0.0));
+    assert!(parallel
+        .intersection_waiting_time
+        .values()
+        .any(|&w| w > 0.0));
+}
+
+#[test]
+fn per_lane_waits_pool_into_the_intersection_average() {
+    let (mut intersections, mut lanes) = GridBuilder::rectangular(2, 2).build().unwrap();
+    // Red-light waits at (0, 0), recorded on the lane taken and the intersection as a journey does.
+    let corner = IntersectionId(0, 0);
+    let waits: [(IntersectionId, f64); 3] = [
+        (IntersectionId(0, 1), 4.0),
+        (IntersectionId(0, 1), 6.0),
+        (IntersectionId(1, 0), 11.0),
+    ];
+    for (to, secs) in waits {
+        let lane = lanes
+            .iter_mut()
+            .find(|l| l.from == corner && l.to == to)
+            .unwrap();
+        lane.record_wait(secs);
+        let intersection = intersections.iter_mut().find(|i| i.id == corner).unwrap();
+        intersection.record_wait(secs);
+    }
+
+    let data = collect_traffic_data(&lanes, &intersections, vec![]);
+    let outgoing: Vec<&Lane> = lanes.iter().filter(|l| l.from == corner).collect();
+    assert_eq!(outgoing.len(), 2);
+    assert_eq!(data.lane_waiting_time[&outgoing[0].name], 5.0);
+    assert_eq!(data.lane_waiting_time[&outgoing[1].name], 11.0);
+    // Pooled over every wait on the outgoing lanes, weighted by how many each lane saw.
+    let pooled: f64 = outgoing
+        .iter()
+        .map(|l| data.lane_waiting_time[&l.name] * l.wait_count as f64)
+        .sum::<f64>()
+        / outgoing.iter().map(|l| l.wait_count as f64).sum::<f64>();
+    assert_eq!(data.intersection_waiting_time[&corner.to_string()], pooled);
+    assert_eq!(pooled, 7.0);
+}
+
+#[test]
+fn traffic_data_without_lane_waits_still_deserializes() {
+    let json = r#"{
+        "lane_occupancy": {},
+        "accident_lanes": [],
+        "intersection_congestion": {},
+        "intersection_waiting_time": {"(0, 0)": 7.0},
+        "vehicle_data": []
+    }"#;
+    let data: TrafficData = serde_json::from_str(json).unwrap();
+    assert!(data.lane_waiting_time.is_empty());
+    assert_eq!(data.intersection_waiting_time["(0, 0)"], 7.0);
+}
@@ -0,0 +1,71 @@
+// Two vehicles closing on one short lane collide as one accident that involves both of them.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::lanes::Lane;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c2_tp063881::traffic_analyzer::{analyze_traffic_events, AnalyzerThresholds};
+use rts_assignment::shared_data::{TrafficData, TrafficUpdate, VehicleData};
+use std::collections::{HashMap, HashSet};
+
+fn vehicle_data(vehicle: &Vehicle, lane: &str) -> VehicleData {
+    VehicleData {
+        id: vehicle.id,
+        waiting_time: vehicle.waiting_time,
+        accident_timestamp: vehicle.accident_timestamp,
+        severity: vehicle.severity,
+        current_lane: lane.to_string(),
+        collided_with: vehicle.collided_with,
+        fuel_used: vehicle.fuel_used,
+    }
+}
+
+#[test]
+fn closing_vehicles_on_a_short_lane_collide_as_one_accident() {
+    let (from, to) = (IntersectionId(0, 0), IntersectionId(0, 1));
+    let mut lane = Lane::new("short".to_string(), from, to, 20.0).unwrap();
+    let slow = Vehicle::new(1, VehicleType::Car, from, to, 40.0);
+    let fast = Vehicle::new(2, VehicleType::Car, from, to, 120.0);
+    assert!(lane.add_vehicle(&slow));
+    assert!(lane.add_vehicle(&fast));
+
+    // The faster vehicle behind is closing on the slower one ahead.
+    assert_eq!(lane.closing_vehicle_ahead(&fast), Some(slow.id));
+    assert_eq!(lane.closing_vehicle_ahead(&slow), None);
+
+    lane.record_collision(fast.id, slow.id, 1_000, 3);
+    assert!(lane.has_accident);
+    assert_eq!(lane.accident_severity, 3);
+    let crashed: Vec<&Vehicle> = lane.vehicle_queue.iter().collect();
+    assert_eq!(crashed.len(), 2);
+    for v in &crashed {
+        assert!(v.is_accident);
+        assert_eq!(v.accident_timestamp, Some(1_000));
+    }
+    assert_eq!(crashed[0].collided_with, Some(fast.id));
+    assert_eq!(crashed[1].collided_with, Some(slow.id));
+
+    // Each vehicle reports the crash, but the analyzer counts the pair once.
+    let update = TrafficUpdate {
+        current_data: TrafficData {
+            lane_occupancy: HashMap::new(),
+            accident_lanes: HashSet::from([lane.name.clone()]),
+            intersection_congestion: HashMap::new(),
+            intersection_waiting_time: HashMap::new(),
+            vehicle_data: crashed
+                .iter()
+                .map(|v| vehicle_data(v, &lane.name))
+                .collect(),
+            intersection_max_direction_congestion: HashMap::new(),
+            lane_waiting_time: HashMap::new(),
+            lane_speeds: HashMap::new(),
+        },
+        timestamp: 1_000,
+        run_id: String::new(),
+        throughput_per_min: 0.0,
+    };
+    let event = analyze_traffic_events(update, &AnalyzerThresholds::default(), None);
+    assert_eq!(event.total_accidents, 1);
+    let accident = &event.accident_details[0];
+    assert_eq!(accident.vehicle_id, slow.id);
+    assert_eq!(accident.involved_vehicle_ids, vec![slow.id, fast.id]);
+    assert_eq!(accident.current_lane, "short");
+}
@@ -0,0 +1,91 @@
+// Simulation parameters load from TOML, fall back to the defaults and drive spawn_vehicle.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::{ConfigError, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::spawn_vehicle_from;
+use rts_assignment::c1_tp063879::vehicles::VehicleType;
+
+const SAMPLE_CONFIG: &str = r#"
+crash_probability = 0.05
+
+[vehicle_mix]
+car = 0.0
+truck = 1.0
+bus = 0.0
+emergency_van = 0.0
+
+[speed_ranges.truck]
+min = 55.0
+max = 65.0
+"#;
+
+#[test]
+fn a_sample_config_overrides_only_the_fields_it_sets() {
+    let config = SimulationConfig::from_toml_str(SAMPLE_CONFIG).expect("valid config");
+    let defaults = SimulationConfig::default();
+    assert_eq!(config.crash_probability, 0.05);
+    assert_eq!(config.vehicle_mix.truck, 1.0);
+    assert_eq!(config.speed_ranges.truck.min, 55.0);
+    // Everything left out keeps today's literals.
+    assert_eq!(
+        config.overspeed_crash_probability,
+        defaults.overspeed_crash_probability
+    );
+    assert_eq!(config.speed_ranges.car.max, defaults.speed_ranges.car.max);
+    assert_eq!(config.tick_millis, defaults.tick_millis);
+}
+
+#[test]
+fn spawn_vehicle_draws_types_and_speeds_from_the_config() {
+    let config = SimulationConfig::from_toml_str(SAMPLE_CONFIG).expect("valid config");
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut next_vehicle_id = 1;
+    let mut spawned = 0;
+    for _ in 0..200 {
+        let Some((vehicle, _route)) = spawn_vehicle_from(
+            &intersections,
+            &lanes,
+            &mut next_vehicle_id,
+            &mut rng,
+            &config,
+            None,
+            12,
+        ) else {
+            continue;
+        };
+        spawned += 1;
+        assert_eq!(vehicle.vehicle_type, VehicleType::Truck);
+        assert!((55.0..65.0).contains(&vehicle.speed), "{}", vehicle.speed);
+    }
+    assert!(spawned > 100, "only {} of 200 spawns succeeded", spawned);
+}
+
+#[test]
+fn configs_the_simulation_cannot_sample_from_are_rejected() {
+    let empty_range = "[speed_ranges.car]\nmin = 100.0\nmax = 100.0\n";
+    assert!(SimulationConfig::from_toml_str(empty_range).is_err());
+
+    let zero_mix = "[vehicle_mix]\ncar = 0.0\ntruck = 0.0\nbus = 0.0\nemergency_van = 0.0\n";
+    assert!(SimulationConfig::from_toml_str(zero_mix).is_err());
+
+    let zero_period = r#"
+[[vehicle_mix_schedule]]
+start_hour = 7
+end_hour = 9
+mix = { car = 0.0, truck = 0.0, bus = 0.0, emergency_van = 0.0 }
+"#;
+    let config: SimulationConfig = toml::from_str(zero_period).unwrap();
+    assert_eq!(
+        config.validate(),
+        Err(ConfigError::ZeroVehicleMix { period: Some(0) })
+    );
+
+    let backwards_severity = "[crash_severity]\nmin_severity = 3\nmax_severity = 1\n";
+    assert!(SimulationConfig::from_toml_str(backwards_severity).is_err());
+
+    assert!(SimulationConfig::default().validate().is_ok());
+}
@@ -0,0 +1,121 @@
+// Congestion alerts are routed by intersection, so a monitor can bind to a single corridor.
+// The binding test is ignored by default; run it with `cargo test -- --ignored` and AMQP_URL
+// pointing at a reachable RabbitMQ instance.
+use amiquip::{QueueDeclareOptions, QueueDeleteOptions};
+use rts_assignment::amqp::{congestion_routing_key, connect_with_retry, CONNECT_BASE_DELAY};
+use rts_assignment::bus::{AmiquipBus, MessageBus};
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c4_tp071994::metrics::MetricsState;
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    listen_congestion_alerts, OutputPaths,
+};
+use rts_assignment::global_variables::{
+    amqp_url, queue_env_var, queue_name, QUEUE_CONGESTION_ALERTS, QUEUE_DEAD_LETTER,
+};
+use rts_assignment::shared_data::{current_timestamp, CongestionAlert};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+#[test]
+fn routing_keys_encode_the_intersection() {
+    let id = IntersectionId(0, 2).to_string();
+    assert_eq!(congestion_routing_key(Some(&id)), "congestion.row0.col2");
+    assert_eq!(congestion_routing_key(Some("3, 1")), "congestion.row3.col1");
+    assert_eq!(
+        congestion_routing_key(Some("Intersection 02")),
+        "congestion.unknown"
+    );
+    assert_eq!(congestion_routing_key(None), "congestion.unknown");
+}
+
+fn alert(intersection: IntersectionId) -> Vec<u8> {
+    let alert = CongestionAlert {
+        timestamp: current_timestamp(),
+        intersection: Some(intersection.to_string()),
+        message: format!("{} is congested", intersection),
+        congestion_perc: 0.9,
+        recommended_action: "Adjust traffic light timings to avoid congestion.".to_string(),
+        run_id: String::new(),
+    };
+    serde_json::to_vec(&alert).unwrap()
+}
+
+fn delete_queues() {
+    let Ok(mut connection) = connect_with_retry(&amqp_url(), 1, CONNECT_BASE_DELAY) else {
+        return;
+    };
+    if let Ok(channel) = connection.open_channel(None) {
+        for default in [QUEUE_CONGESTION_ALERTS, QUEUE_DEAD_LETTER] {
+            if let Ok(queue) =
+                channel.queue_declare(queue_name(default), QueueDeclareOptions::default())
+            {
+                let _ = queue.delete(QueueDeleteOptions::default());
+            }
+        }
+    }
+    let _ = connection.close();
+}
+
+fn csv_records(path: &std::path::Path) -> usize {
+    csv::Reader::from_path(path)
+        .map(|mut rdr| rdr.records().filter(|r| r.is_ok()).count())
+        .unwrap_or(0)
+}
+
+#[ignore = "needs a RabbitMQ broker at AMQP_URL"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_row_binding_only_receives_that_rows_alerts() {
+    let bus = AmiquipBus::connect(&amqp_url()).expect("broker at AMQP_URL");
+    let suffix = format!("{}_{}", std::process::id(), current_timestamp());
+    for default in [QUEUE_CONGESTION_ALERTS, QUEUE_DEAD_LETTER] {
+        std::env::set_var(queue_env_var(default), format!("{}_{}", default, suffix));
+    }
+    let output_dir = std::env::temp_dir().join(format!("rts_routing_{}", suffix));
+    std::fs::create_dir_all(&output_dir).expect("create output dir");
+    let paths = OutputPaths::in_dir(&output_dir);
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let metrics = Arc::new(Mutex::new(MetricsState::default()));
+    let listener = tokio::spawn(listen_congestion_alerts(
+        None,
+        "congestion.row0.*".to_string(),
+        paths.clone(),
+        Arc::clone(&metrics),
+        shutdown_rx,
+    ));
+    // Let the listener bind its queue before anything is published.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    for intersection in [
+        IntersectionId(0, 1),
+        IntersectionId(1, 1),
+        IntersectionId(0, 2),
+        IntersectionId(2, 0),
+    ] {
+        let key = congestion_routing_key(Some(&intersection.to_string()));
+        bus.publish_congestion_alert(&key, &alert(intersection))
+            .expect("publish alert");
+    }
+
+    let mut received = 0;
+    for _ in 0..20 {
+        received = csv_records(&paths.congestion_alerts);
+        if received >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    // Allow any wrongly routed alert to arrive too.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    received = received.max(csv_records(&paths.congestion_alerts));
+
+    let _ = shutdown_tx.send(true);
+    let _ = tokio::time::timeout(Duration::from_secs(5), listener).await;
+    let _ = bus.close();
+    delete_queues();
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    assert_eq!(received, 2);
+    assert_eq!(metrics.lock().unwrap().congestion_alerts_total, 2);
+}
@@ -0,0 +1,188 @@
+// Crash severities follow the configured range and skew, and accident-lane blocking follows the
+// configured clearance time of the crash that blocks the lane.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::{CrashSeverityConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, Intersection, IntersectionControl,
+};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, Lane, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const TIME_SCALE: f64 = 50.0;
+
+// Seconds a car waits on entering a lane blocked by an accident, with the given clearance time
+// per severity point.
+async fn wait_behind_accident(clearance_secs_per_severity: f64) -> u64 {
+    journey_waiting_time(true, clearance_secs_per_severity).await
+}
+
+// A lane leaving an intersection without lights, so the only wait on it is an accident's.
+fn uncontrolled_lane(intersections: &[Intersection], lanes: &[Lane]) -> Lane {
+    lanes
+        .iter()
+        .find(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control != IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving an uncontrolled intersection")
+        .clone()
+}
+
+// Journey context over the default network with `config`.
+fn journey_context(lanes: Vec<Lane>, config: SimulationConfig) -> JourneyContext {
+    let intersections = create_intersections();
+    let controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+    JourneyContext {
+        time_scale: TIME_SCALE,
+        ..JourneyContext::new(
+            Arc::new(RwLock::new(intersections)),
+            Arc::new(LaneStore::new(lanes)),
+            Arc::new(RwLock::new(controller)),
+            Arc::new(config),
+        )
+    }
+}
+
+// Waiting time a car reports after driving one lane, blocked by a severity 2 accident or not.
+async fn journey_waiting_time(blocked: bool, clearance_secs_per_severity: f64) -> u64 {
+    let lanes = create_lanes();
+    let lane = uncontrolled_lane(&create_intersections(), &lanes);
+    let config = SimulationConfig {
+        crash_probability: 0.0,
+        overspeed_crash_probability: 0.0,
+        collision_probability: 0.0,
+        crash_severity: CrashSeverityConfig {
+            clearance_secs_per_severity,
+            ..CrashSeverityConfig::default()
+        },
+        ..SimulationConfig::default()
+    };
+    let ctx = journey_context(lanes, config);
+    if blocked {
+        ctx.lanes
+            .lock_lane(&lane.name)
+            .unwrap()
+            .block_for_accident(2);
+    }
+    ctx.active_ids.lock().unwrap().insert(1);
+    let vehicle = Vehicle::new(1, VehicleType::Car, lane.from, lane.to, 40.0);
+    let vehicle_events = Arc::clone(&ctx.vehicle_events);
+    simulate_vehicle_journey(vehicle, vec![lane], SmallRng::seed_from_u64(1), ctx).await;
+    let events = vehicle_events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    events[0].waiting_time
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_higher_clearance_multiplier_blocks_the_lane_proportionally_longer() {
+    let short = wait_behind_accident(1.5).await;
+    let long = wait_behind_accident(6.0).await;
+    // Two severity points of clearance each.
+    assert_eq!(short, 3);
+    assert_eq!(long, 12);
+    assert_eq!(long, 4 * short);
+}
+
+#[test]
+fn clearance_time_scales_with_severity() {
+    let config = CrashSeverityConfig {
+        clearance_secs_per_severity: 2.5,
+        ..CrashSeverityConfig::default()
+    };
+    assert_eq!(config.clearance_secs(1), 2.5);
+    assert_eq!(config.clearance_secs(4), 10.0);
+}
+
+#[test]
+fn severities_stay_in_range_and_skew_towards_minor() {
+    let config = CrashSeverityConfig {
+        min_severity: 1,
+        max_severity: 5,
+        minor_skew: 3.0,
+        ..CrashSeverityConfig::default()
+    };
+    let mut rng = SmallRng::seed_from_u64(5);
+    let mut counts = [0usize; 5];
+    for _ in 0..10_000 {
+        let severity = config.sample(&mut rng);
+        assert!((1..=5).contains(&severity));
+        counts[(severity - 1) as usize] += 1;
+    }
+    // Each step up is rarer than the one below.
+    assert!(counts.windows(2).all(|w| w[0] > w[1]), "{:?}", counts);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_vehicle_on_an_accident_lane_reports_the_clearance_wait() {
+    let clearance = CrashSeverityConfig::default();
+    let blocked = journey_waiting_time(true, clearance.clearance_secs_per_severity).await;
+    let clear = journey_waiting_time(false, clearance.clearance_secs_per_severity).await;
+    assert_eq!(blocked as f64, clearance.clearance_secs(2).round());
+    assert_eq!(clear, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn a_car_behind_a_real_crash_waits_for_that_crashs_clearance() {
+    let lanes = create_lanes();
+    let lane = uncontrolled_lane(&create_intersections(), &lanes);
+    // Only an overspeeding vehicle crashes, always with severity 3.
+    let config = SimulationConfig {
+        crash_probability: 0.0,
+        overspeed_crash_probability: 1.0,
+        collision_probability: 0.0,
+        crash_severity: CrashSeverityConfig {
+            min_severity: 3,
+            max_severity: 3,
+            clearance_secs_per_severity: 5.0,
+            ..CrashSeverityConfig::default()
+        },
+        ..SimulationConfig::default()
+    };
+    let clearance = config.crash_severity.clearance_secs(3);
+    let ctx = journey_context(lanes, config);
+    ctx.active_ids.lock().unwrap().extend([1, 2]);
+    let speeder = Vehicle::new(1, VehicleType::Car, lane.from, lane.to, 500.0);
+    let crash = tokio::spawn(simulate_vehicle_journey(
+        speeder,
+        vec![lane.clone()],
+        SmallRng::seed_from_u64(1),
+        ctx.clone(),
+    ));
+
+    // The single-vehicle crash blocks the lane with its own severity.
+    while !ctx.lanes.lock_lane(&lane.name).unwrap().has_accident {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert_eq!(
+        ctx.lanes.lock_lane(&lane.name).unwrap().accident_severity,
+        3
+    );
+
+    let follower = Vehicle::new(2, VehicleType::Car, lane.from, lane.to, 40.0);
+    let vehicle_events = Arc::clone(&ctx.vehicle_events);
+    let lanes = Arc::clone(&ctx.lanes);
+    simulate_vehicle_journey(
+        follower,
+        vec![lane.clone()],
+        SmallRng::seed_from_u64(2),
+        ctx,
+    )
+    .await;
+    crash.await.unwrap();
+
+    let events = vehicle_events.lock().unwrap();
+    let follower = events.iter().find(|e| e.id == 2).expect("follower arrived");
+    assert_eq!(follower.waiting_time as f64, clearance);
+    let crashed = events.iter().find(|e| e.id == 1).expect("crash recorded");
+    assert_eq!(crashed.severity, 3);
+    // Once the wreck is removed the lane is clear again.
+    let lane = lanes.lock_lane(&lane.name).unwrap();
+    assert!(!lane.has_accident);
+    assert_eq!(lane.accident_severity, 0);
+}
@@ -0,0 +1,78 @@
+// A payload a listener can't parse is moved to the dead_letter queue with its parse error.
+// Ignored by default; run it with `cargo test -- --ignored` and AMQP_URL pointing at a
+// reachable RabbitMQ instance.
+use amiquip::{QueueDeclareOptions, QueueDeleteOptions};
+use rts_assignment::amqp::{connect_with_retry, CONNECT_BASE_DELAY, CONNECT_MAX_ATTEMPTS};
+use rts_assignment::bus::{AmiquipBus, MessageBus};
+use rts_assignment::c4_tp071994::metrics::MetricsState;
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{listen_traffic_event, OutputPaths};
+use rts_assignment::global_variables::{
+    amqp_url, queue_env_var, queue_name, QUEUE_DEAD_LETTER, QUEUE_TRAFFIC_EVENTS,
+};
+use rts_assignment::shared_data::{current_timestamp, DeadLetter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+const MALFORMED: &str = "{\"timestamp\": 12, \"total_vehicles\": ";
+
+#[ignore = "needs a RabbitMQ broker at AMQP_URL"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn malformed_json_lands_in_the_dead_letter_queue() {
+    let bus = AmiquipBus::connect(&amqp_url()).expect("broker at AMQP_URL");
+    let suffix = format!("{}_{}", std::process::id(), current_timestamp());
+    for default in [QUEUE_TRAFFIC_EVENTS, QUEUE_DEAD_LETTER] {
+        std::env::set_var(queue_env_var(default), format!("{}_{}", default, suffix));
+    }
+    let output_dir = std::env::temp_dir().join(format!("rts_dead_letter_{}", suffix));
+    std::fs::create_dir_all(&output_dir).expect("create output dir");
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let metrics = Arc::new(Mutex::new(MetricsState::default()));
+    let listener = tokio::spawn(listen_traffic_event(
+        None,
+        OutputPaths::in_dir(&output_dir),
+        Arc::clone(&metrics),
+        shutdown_rx,
+    ));
+    bus.publish(&queue_name(QUEUE_TRAFFIC_EVENTS), MALFORMED.as_bytes())
+        .expect("publish malformed event");
+
+    let mut connection =
+        connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY).unwrap();
+    let channel = connection.open_channel(None).unwrap();
+    let dead_letters = channel
+        .queue_declare(
+            queue_name(QUEUE_DEAD_LETTER),
+            QueueDeclareOptions::default(),
+        )
+        .unwrap();
+    let mut dead_letter = None;
+    for _ in 0..40 {
+        if let Some(get) = dead_letters.get(true).unwrap() {
+            dead_letter = Some(serde_json::from_slice::<DeadLetter>(&get.delivery.body).unwrap());
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    let _ = shutdown_tx.send(true);
+    let _ = tokio::time::timeout(Duration::from_secs(5), listener).await;
+    for default in [QUEUE_TRAFFIC_EVENTS, QUEUE_DEAD_LETTER] {
+        if let Ok(queue) =
+            channel.queue_declare(queue_name(default), QueueDeclareOptions::default())
+        {
+            let _ = queue.delete(QueueDeleteOptions::default());
+        }
+    }
+    let _ = connection.close();
+    let _ = bus.close();
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    let dead_letter = dead_letter.expect("nothing reached the dead_letter queue");
+    assert_eq!(dead_letter.source_queue, queue_name(QUEUE_TRAFFIC_EVENTS));
+    assert_eq!(dead_letter.body, MALFORMED);
+    assert!(!dead_letter.error.is_empty());
+    // The malformed payload isn't counted as an event.
+    assert_eq!(metrics.lock().unwrap().traffic_events_total, 0);
+}
@@ -0,0 +1,45 @@
+// A demand profile loaded from CSV drives spawns from its own entry, spikes included.
+use rts_assignment::c1_tp063879::config::{DemandProfiles, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionId};
+use rts_assignment::c1_tp063879::simulation::scheduled_spawns;
+use std::collections::HashMap;
+use std::fs;
+
+const PROFILE_CSV: &str = "entry_id, minute, vehicles_per_minute
+Intersection 00, 0, 6
+Intersection 00, 5, 30
+Intersection 00, 6, 6
+Intersection 02, 0, 6
+";
+
+// Vehicles scheduled from `entry` during simulated minute `minute`, ticking once a second
+// from the start of the run.
+fn spawns_in_minute(profiles: &DemandProfiles, entry: IntersectionId, minute: u64) -> usize {
+    let intersections = create_intersections();
+    let config = SimulationConfig::default();
+    let mut carry = HashMap::new();
+    let mut spawned = 0;
+    for elapsed in 0..(minute + 1) * 60 {
+        let spawns = scheduled_spawns(&intersections, Some(profiles), &mut carry, &config, elapsed);
+        if elapsed / 60 == minute {
+            spawned += spawns.iter().filter(|s| **s == Some(entry)).count();
+        }
+    }
+    spawned
+}
+
+#[test]
+fn a_spike_at_minute_five_raises_spawns_from_that_entry() {
+    let path = std::env::temp_dir().join(format!("rts_demand_{}.csv", std::process::id()));
+    fs::write(&path, PROFILE_CSV).unwrap();
+    let profiles = DemandProfiles::load(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let spiking = IntersectionId(0, 0);
+    let steady = IntersectionId(0, 2);
+    assert_eq!(spawns_in_minute(&profiles, spiking, 4), 6);
+    assert_eq!(spawns_in_minute(&profiles, spiking, 5), 30);
+    assert_eq!(spawns_in_minute(&profiles, spiking, 6), 6);
+    // The other entry keeps its own rate through the spike.
+    assert_eq!(spawns_in_minute(&profiles, steady, 5), 6);
+}
@@ -0,0 +1,59 @@
+// One jammed approach alerts under the max-direction rule even when the average stays low.
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::collect_traffic_data;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c2_tp063881::traffic_analyzer::{
+    analyze_traffic_data, AnalyzerThresholds, CongestionRule,
+};
+use std::collections::HashSet;
+
+#[test]
+fn one_jammed_approach_alerts_on_max_direction_but_not_average() {
+    let intersections = create_intersections();
+    let mut lanes = create_lanes();
+    // An intersection with at least three outgoing lanes, each heading a different way.
+    let busy = intersections
+        .iter()
+        .find(|i| {
+            let headings: HashSet<_> = lanes
+                .iter()
+                .filter(|l| l.from == i.id)
+                .map(|l| l.heading())
+                .collect();
+            let outgoing = lanes.iter().filter(|l| l.from == i.id).count();
+            outgoing >= 3 && headings.len() == outgoing
+        })
+        .expect("an intersection with three or more headings");
+
+    // Fill one outgoing lane to capacity and leave the others empty.
+    let jammed = lanes.iter_mut().find(|l| l.from == busy.id).unwrap();
+    let mut id = 1;
+    while jammed.add_vehicle(&Vehicle::new(
+        id,
+        VehicleType::Car,
+        busy.id,
+        jammed.to,
+        40.0,
+    )) {
+        id += 1;
+    }
+    assert!(jammed.occupancy() > 0.9);
+
+    let data = collect_traffic_data(&lanes, &intersections, vec![]);
+    let key = busy.id.to_string();
+    assert!(data.intersection_congestion[&key] < 0.5);
+    assert!(data.intersection_max_direction_congestion[&key] > 0.9);
+
+    let alerted = |rule| {
+        let thresholds = AnalyzerThresholds {
+            congestion_rule: rule,
+            ..AnalyzerThresholds::default()
+        };
+        analyze_traffic_data(&data, &thresholds)
+            .iter()
+            .any(|alert| alert.intersection.as_deref() == Some(key.as_str()))
+    };
+    assert!(alerted(CongestionRule::MaxDirection));
+    assert!(!alerted(CongestionRule::Average));
+}
@@ -0,0 +1,222 @@
+// Dispatch requests spawn an emergency van routed from the requested entry to the exit.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::bus::InMemoryBus;
+use rts_assignment::c1_tp063879::config::{RushHourConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::event_log::SimulationEvent;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::intersections::{
+    Intersection, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, Lane, LaneStore};
+use rts_assignment::c1_tp063879::route_generation::generate_shortest_lane_route;
+use rts_assignment::c1_tp063879::simulation::{
+    dispatch_emergency_vehicle, run_simulation_on, DispatchRequest, RunContext,
+};
+use rts_assignment::c1_tp063879::vehicles::VehicleType;
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+
+// A three-intersection line: entry (0,0) -> (0,1) -> exit (0,2), plus an exit (1,0) that no lane
+// reaches.
+fn line_network() -> (Vec<Intersection>, Vec<Lane>) {
+    let intersections = vec![
+        Intersection::new(
+            "Entry".to_string(),
+            0,
+            0,
+            true,
+            false,
+            IntersectionControl::Normal,
+        ),
+        Intersection::new(
+            "Middle".to_string(),
+            0,
+            1,
+            false,
+            false,
+            IntersectionControl::Normal,
+        ),
+        Intersection::new(
+            "Exit".to_string(),
+            0,
+            2,
+            false,
+            true,
+            IntersectionControl::Normal,
+        ),
+        Intersection::new(
+            "Island".to_string(),
+            1,
+            0,
+            false,
+            true,
+            IntersectionControl::Normal,
+        ),
+    ];
+    let lanes = vec![
+        Lane::new(
+            "(0,0) -> (0,1)".to_string(),
+            IntersectionId(0, 0),
+            IntersectionId(0, 1),
+            100.0,
+        )
+        .unwrap(),
+        Lane::new(
+            "(0,1) -> (0,2)".to_string(),
+            IntersectionId(0, 1),
+            IntersectionId(0, 2),
+            100.0,
+        )
+        .unwrap(),
+    ];
+    (intersections, lanes)
+}
+
+#[test]
+fn a_dispatch_spawns_an_emergency_van_along_the_route() {
+    let (intersections, lanes) = line_network();
+    let config = SimulationConfig::default();
+    let mut rng = SmallRng::seed_from_u64(1);
+    let mut next_vehicle_id = 10;
+    let request = DispatchRequest {
+        entry: IntersectionId(0, 0),
+        exit: IntersectionId(0, 2),
+    };
+
+    let (vehicle, route) = dispatch_emergency_vehicle(
+        &intersections,
+        &lanes,
+        &mut next_vehicle_id,
+        &mut rng,
+        &config,
+        request,
+    )
+    .expect("a dispatched van");
+    assert_eq!(vehicle.id, 10);
+    assert_eq!(next_vehicle_id, 11);
+    assert_eq!(vehicle.vehicle_type, VehicleType::EmergencyVan);
+    assert_eq!(vehicle.entry_point, request.entry);
+    assert_eq!(vehicle.exit_point, request.exit);
+    let range = config.speed_ranges.emergency_van;
+    assert!((range.min..range.max).contains(&vehicle.speed));
+    let names: Vec<&str> = route.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["(0,0) -> (0,1)", "(0,1) -> (0,2)"]);
+}
+
+#[test]
+fn an_emergency_van_takes_fewer_hops_over_a_shorter_route() {
+    let (intersections, mut lanes) = line_network();
+    // A direct bypass that is longer than going through (0,1), but skips an intersection.
+    lanes.push(
+        Lane::new(
+            "(0,0) -> (0,2)".to_string(),
+            IntersectionId(0, 0),
+            IntersectionId(0, 2),
+            300.0,
+        )
+        .unwrap(),
+    );
+    let config = SimulationConfig::default();
+    assert!(config.emergency_intersection_penalty_meters > 100.0);
+    let (entry, exit) = (IntersectionId(0, 0), IntersectionId(0, 2));
+
+    let normal = generate_shortest_lane_route(&lanes, entry, exit, &HashSet::new()).unwrap();
+    let names: Vec<&str> = normal.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["(0,0) -> (0,1)", "(0,1) -> (0,2)"]);
+
+    let (_, route) = dispatch_emergency_vehicle(
+        &intersections,
+        &lanes,
+        &mut 0,
+        &mut SmallRng::seed_from_u64(1),
+        &config,
+        DispatchRequest { entry, exit },
+    )
+    .unwrap();
+    let names: Vec<&str> = route.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["(0,0) -> (0,2)"]);
+    let length = |route: &[Lane]| route.iter().map(|l| l.length_meters).sum::<f64>();
+    assert!(length(&route) > length(&normal));
+}
+
+#[test]
+fn invalid_or_unroutable_dispatches_are_rejected() {
+    let (intersections, lanes) = line_network();
+    let config = SimulationConfig::default();
+    let mut rng = SmallRng::seed_from_u64(1);
+    let mut next_vehicle_id = 10;
+    let rejected = [
+        // Not an entry.
+        (IntersectionId(0, 1), IntersectionId(0, 2)),
+        // Not an exit.
+        (IntersectionId(0, 0), IntersectionId(0, 1)),
+        // Unknown intersection.
+        (IntersectionId(5, 5), IntersectionId(0, 2)),
+        // A valid pair no lane connects.
+        (IntersectionId(0, 0), IntersectionId(1, 0)),
+    ];
+    for (entry, exit) in rejected {
+        let dispatched = dispatch_emergency_vehicle(
+            &intersections,
+            &lanes,
+            &mut next_vehicle_id,
+            &mut rng,
+            &config,
+            DispatchRequest { entry, exit },
+        );
+        assert!(dispatched.is_none(), "dispatch from {} to {}", entry, exit);
+    }
+    // Rejected dispatches don't use up vehicle ids.
+    assert_eq!(next_vehicle_id, 10);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_dispatch_request_spawns_an_emergency_vehicle_on_the_next_tick() {
+    let intersections = create_intersections();
+    let entry = intersections.iter().find(|i| i.is_entry).unwrap().id;
+    let exit = intersections
+        .iter()
+        .find(|i| i.is_exit && i.id != entry)
+        .unwrap()
+        .id;
+    let (dispatch_tx, dispatch_rx) = mpsc::channel();
+    dispatch_tx.send(DispatchRequest { entry, exit }).unwrap();
+    let (event_tx, event_rx) = mpsc::channel();
+
+    // No random spawns, so the only vehicle is the dispatched one.
+    let config = SimulationConfig {
+        rush_hour: RushHourConfig::new(40, 0, 0),
+        ..SimulationConfig::default()
+    };
+    let summary = run_simulation_on(
+        &InMemoryBus::new(),
+        Arc::new(RwLock::new(intersections)),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        RunContext {
+            time_scale: 50.0,
+            max_ticks: Some(1),
+            event_stream: Some(event_tx),
+            dispatches: Some(dispatch_rx),
+            ..RunContext::new(7, "dispatch")
+        },
+    )
+    .await;
+
+    assert_eq!(summary.total_spawned, 1);
+    let spawned: Vec<_> = event_rx
+        .try_iter()
+        .filter_map(|event| match event {
+            SimulationEvent::VehicleSpawned {
+                vehicle_type,
+                entry,
+                exit,
+                ..
+            } => Some((vehicle_type, entry, exit)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(spawned, vec![("EmergencyVan".to_string(), entry, exit)]);
+}
@@ -0,0 +1,52 @@
+// Recorded simulation events replay from the JSON-lines log in the order they were written.
+use rts_assignment::c1_tp063879::event_log::{replay_from_log, EventLog, SimulationEvent};
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+
+fn short_run() -> Vec<SimulationEvent> {
+    vec![
+        SimulationEvent::VehicleSpawned {
+            vehicle_id: 1,
+            vehicle_type: "Car".to_string(),
+            entry: IntersectionId(0, 0),
+            exit: IntersectionId(0, 2),
+            route: vec!["(0,0) -> (0,1)".to_string(), "(0,1) -> (0,2)".to_string()],
+        },
+        SimulationEvent::LightChanged {
+            intersection: IntersectionId(0, 1),
+            phase_index: 1,
+            green_lanes: vec!["(0,1) -> (0,2)".to_string()],
+        },
+        SimulationEvent::VehicleMoved {
+            vehicle_id: 1,
+            lane: "(0,1) -> (0,2)".to_string(),
+        },
+        SimulationEvent::VehicleCrashed {
+            vehicle_id: 1,
+            lane: "(0,1) -> (0,2)".to_string(),
+            severity: 2,
+        },
+        SimulationEvent::VehicleArrived {
+            vehicle_id: 2,
+            waiting_time: 6,
+        },
+    ]
+}
+
+#[test]
+fn replay_emits_the_recorded_sequence() {
+    let path = std::env::temp_dir().join(format!("rts_events_{}.jsonl", std::process::id()));
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+    {
+        let log = EventLog::open(path).unwrap();
+        for event in short_run() {
+            log.record(event);
+        }
+    }
+
+    let mut replayed = Vec::new();
+    let count = replay_from_log(path, |logged| replayed.push(logged.event.clone())).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(count, 5);
+    assert_eq!(replayed, short_run());
+}
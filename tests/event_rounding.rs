@@ -0,0 +1,66 @@
+// TrafficEvent figures are rounded to the requested precision, or kept exact without one.
+use rts_assignment::c2_tp063881::traffic_analyzer::{
+    analyze_traffic_events, AnalyzerThresholds, EVENT_DECIMAL_PLACES,
+};
+use rts_assignment::shared_data::{TrafficData, TrafficUpdate, VehicleData};
+use std::collections::{HashMap, HashSet};
+
+fn vehicle(id: u64, waiting_time: u64, accident_timestamp: Option<u64>) -> VehicleData {
+    VehicleData {
+        id,
+        waiting_time,
+        accident_timestamp,
+        severity: 2,
+        current_lane: "(0, 0) -> (0, 1)".to_string(),
+        collided_with: None,
+        fuel_used: 0.1234,
+    }
+}
+
+// Delays of 1, 2 and 2 seconds average to 5/3; the last vehicle has crashed.
+fn update() -> TrafficUpdate {
+    TrafficUpdate {
+        current_data: TrafficData {
+            lane_occupancy: HashMap::new(),
+            accident_lanes: HashSet::new(),
+            intersection_congestion: HashMap::new(),
+            intersection_waiting_time: HashMap::new(),
+            vehicle_data: vec![
+                vehicle(1, 1, None),
+                vehicle(2, 2, None),
+                vehicle(3, 2, Some(900)),
+            ],
+            intersection_max_direction_congestion: HashMap::new(),
+            lane_waiting_time: HashMap::new(),
+            lane_speeds: HashMap::new(),
+        },
+        timestamp: 1_000,
+        run_id: String::new(),
+        throughput_per_min: 0.0,
+    }
+}
+
+#[test]
+fn rounding_only_changes_the_delay_and_fuel_figures() {
+    let thresholds = AnalyzerThresholds::default();
+    let rounded = analyze_traffic_events(update(), &thresholds, Some(EVENT_DECIMAL_PLACES));
+    let exact = analyze_traffic_events(update(), &thresholds, None);
+
+    assert_eq!(rounded.average_vehicle_delay, 1.67);
+    assert_eq!(exact.average_vehicle_delay, 5.0 / 3.0);
+    assert_eq!(rounded.total_fuel_used, 0.37);
+    assert_eq!(exact.total_fuel_used, 0.1234 + 0.1234 + 0.1234);
+    assert_eq!(
+        analyze_traffic_events(update(), &thresholds, Some(0)).average_vehicle_delay,
+        2.0
+    );
+
+    for event in [&rounded, &exact] {
+        assert_eq!(event.total_accidents, 1);
+        assert_eq!(event.accident_details.len(), 1);
+        let accident = &event.accident_details[0];
+        assert_eq!(accident.vehicle_id, 3);
+        assert_eq!(accident.accident_timestamp, 900);
+        assert_eq!(accident.involved_vehicle_ids, [3]);
+    }
+}
@@ -0,0 +1,43 @@
+// Lane lengths taken from intersection geometry are the same in both directions.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::lanes::{apply_geometric_lengths, create_lanes, geometric_length};
+use std::collections::HashMap;
+
+const METERS_PER_CELL: f64 = 250.0;
+
+#[test]
+fn geometric_lengths_are_symmetric_for_every_pair() {
+    let mut lanes = create_lanes();
+    apply_geometric_lengths(&mut lanes, METERS_PER_CELL);
+    let lengths: HashMap<(IntersectionId, IntersectionId), f64> = lanes
+        .iter()
+        .map(|l| ((l.from, l.to), l.length_meters))
+        .collect();
+
+    let mut pairs = 0;
+    for lane in &lanes {
+        if let Some(&reverse) = lengths.get(&(lane.to, lane.from)) {
+            assert_eq!(lane.length_meters, reverse, "{}", lane.name);
+            pairs += 1;
+        }
+    }
+    assert_eq!(pairs, lanes.len());
+}
+
+#[test]
+fn geometric_length_is_the_scaled_straight_line_distance() {
+    let (a, b, c) = (
+        IntersectionId(1, 1),
+        IntersectionId(1, 2),
+        IntersectionId(2, 2),
+    );
+    assert_eq!(geometric_length(a, b, METERS_PER_CELL), METERS_PER_CELL);
+    assert_eq!(
+        geometric_length(a, c, METERS_PER_CELL),
+        2f64.sqrt() * METERS_PER_CELL
+    );
+    assert_eq!(
+        geometric_length(c, a, METERS_PER_CELL),
+        geometric_length(a, c, METERS_PER_CELL)
+    );
+}
@@ -0,0 +1,78 @@
+// Grids generated by GridBuilder.
+use rts_assignment::c1_tp063879::grid::{GridBuilder, DEFAULT_GRID_LANE_LENGTH_METERS};
+use rts_assignment::c1_tp063879::intersections::{IntersectionControl, IntersectionId};
+use rts_assignment::c1_tp063879::lanes::DEFAULT_LANE_SPEED_LIMIT_KMH;
+use rts_assignment::c1_tp063879::route_generation::validate_connectivity;
+use std::collections::HashSet;
+
+#[test]
+fn a_three_by_three_grid_has_nine_nodes_and_twenty_four_lanes() {
+    let (intersections, lanes) = GridBuilder::rectangular(3, 3).build().unwrap();
+    assert_eq!(intersections.len(), 9);
+    // 6 horizontal and 6 vertical neighbour pairs, one lane each way.
+    assert_eq!(lanes.len(), 24);
+
+    // Every lane joins neighbours, and its reverse exists.
+    let hops: HashSet<(IntersectionId, IntersectionId)> =
+        lanes.iter().map(|l| (l.from, l.to)).collect();
+    assert_eq!(hops.len(), lanes.len());
+    for lane in &lanes {
+        let (from, to) = (lane.from, lane.to);
+        assert_eq!((from.0 - to.0).abs() + (from.1 - to.1).abs(), 1);
+        assert!(hops.contains(&(to, from)));
+        assert_eq!(lane.length_meters, DEFAULT_GRID_LANE_LENGTH_METERS);
+        assert_eq!(lane.speed_limit, Some(DEFAULT_LANE_SPEED_LIMIT_KMH));
+    }
+
+    // Default rules: left column in, right column out, all signalled, every node routable.
+    let entries = intersections.iter().filter(|i| i.is_entry).count();
+    let exits = intersections.iter().filter(|i| i.is_exit).count();
+    assert_eq!((entries, exits), (3, 3));
+    assert!(intersections
+        .iter()
+        .all(|i| i.control == IntersectionControl::TrafficLight));
+    assert!(validate_connectivity(&intersections, &lanes).is_empty());
+}
+
+#[test]
+fn the_assignment_rules_can_be_replaced() {
+    let (intersections, lanes) = GridBuilder::rectangular(3, 3)
+        .with_entries(|row, _| row == 0)
+        .with_exits(|row, _| row == 2)
+        .with_control(|row, col| {
+            if (row + col) % 2 == 0 {
+                IntersectionControl::TrafficLight
+            } else {
+                IntersectionControl::Normal
+            }
+        })
+        .with_lane_length(|from, to| if from.0 == to.0 { 100.0 } else { 300.0 })
+        .build()
+        .unwrap();
+    for intersection in &intersections {
+        let IntersectionId(row, _) = intersection.id;
+        assert_eq!(intersection.is_entry, row == 0);
+        assert_eq!(intersection.is_exit, row == 2);
+    }
+    let signalled = intersections
+        .iter()
+        .filter(|i| i.control == IntersectionControl::TrafficLight)
+        .count();
+    assert_eq!(signalled, 5);
+    for lane in &lanes {
+        let expected = if lane.from.0 == lane.to.0 {
+            100.0
+        } else {
+            300.0
+        };
+        assert_eq!(lane.length_meters, expected);
+    }
+}
+
+#[test]
+fn a_non_positive_lane_length_fails_the_build() {
+    assert!(GridBuilder::rectangular(2, 2)
+        .with_lane_length(|_, _| 0.0)
+        .build()
+        .is_err());
+}
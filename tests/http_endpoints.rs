@@ -0,0 +1,190 @@
+// The shared GET helper behind /health and /metrics answers its path and 404s anything else,
+// /metrics serves the Prometheus text format, and /health goes stale once no message has been
+// processed within the window.
+use rts_assignment::c4_tp071994::metrics::{serve_metrics, MetricsState};
+use rts_assignment::health::{serve_health, Liveness, SharedLiveness};
+use rts_assignment::http::{serve_get, HttpResponse};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.expect("connect");
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+// A port nothing is listening on, for servers that bind their own listener.
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+// Gets `path`, retrying while the server is still binding its port.
+async fn get_when_up(addr: std::net::SocketAddr, path: &str) -> String {
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return get(addr, path).await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("nothing listening on {}", addr);
+}
+
+// Parses a text exposition body into sample values by metric name, checking every sample is
+// preceded by its HELP and TYPE lines.
+fn parse_exposition(body: &str) -> HashMap<String, f64> {
+    let mut samples = HashMap::new();
+    let mut described = Vec::new();
+    for line in body.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut parts = comment.splitn(3, ' ');
+            let (keyword, name) = (parts.next().unwrap(), parts.next().unwrap());
+            assert!(matches!(keyword, "HELP" | "TYPE"), "{}", line);
+            if keyword == "TYPE" {
+                assert!(
+                    matches!(parts.next(), Some("counter" | "gauge")),
+                    "{}",
+                    line
+                );
+            }
+            described.push(name.to_string());
+            continue;
+        }
+        let (name, value) = line.split_once(' ').expect("name and value");
+        assert_eq!(
+            described.iter().filter(|d| *d == name).count(),
+            2,
+            "{} lacks HELP/TYPE",
+            name
+        );
+        samples.insert(
+            name.to_string(),
+            value.parse::<f64>().expect("numeric value"),
+        );
+    }
+    samples
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn serves_the_configured_path_and_404s_others() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_get(listener, "/status", || HttpResponse {
+        status: "200 OK",
+        content_type: "text/plain",
+        body: "fine".to_string(),
+    }));
+
+    let ok = get(addr, "/status").await;
+    assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"), "{}", ok);
+    assert!(ok.contains("Content-Length: 4\r\n"), "{}", ok);
+    assert!(ok.ends_with("\r\n\r\nfine"), "{}", ok);
+
+    // The server keeps answering after the first connection.
+    let missing = get(addr, "/elsewhere").await;
+    assert!(
+        missing.starts_with("HTTP/1.1 404 Not Found\r\n"),
+        "{}",
+        missing
+    );
+
+    // Only the whole path matches; a query string doesn't change it.
+    for longer in ["/statusz", "/status-anything", "/status/"] {
+        let missing = get(addr, longer).await;
+        assert!(
+            missing.starts_with("HTTP/1.1 404 Not Found\r\n"),
+            "{}: {}",
+            longer,
+            missing
+        );
+    }
+    let with_query = get(addr, "/status?verbose=1").await;
+    assert!(
+        with_query.starts_with("HTTP/1.1 200 OK\r\n"),
+        "{}",
+        with_query
+    );
+
+    server.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn metrics_scrape_parses_as_prometheus_text() {
+    let metrics = Arc::new(Mutex::new(MetricsState::default()));
+    let port = free_port();
+    let server = tokio::spawn(serve_metrics(Arc::clone(&metrics), port));
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+
+    let response = get_when_up(addr, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    let samples = parse_exposition(body);
+    assert_eq!(samples.len(), 6);
+    assert_eq!(samples["traffic_congestion_alerts_total"], 0.0);
+
+    // Updates from the listeners show up in the next scrape.
+    {
+        let mut m = metrics.lock().unwrap();
+        m.congestion_alerts_total = 3;
+        m.accidents_total = 2;
+        m.average_delay_seconds = 4.5;
+    }
+    let response = get(addr, "/metrics").await;
+    let samples = parse_exposition(response.split_once("\r\n\r\n").unwrap().1);
+    assert_eq!(samples["traffic_congestion_alerts_total"], 3.0);
+    assert_eq!(samples["traffic_accidents_total"], 2.0);
+    assert_eq!(samples["traffic_average_delay_seconds"], 4.5);
+
+    server.abort();
+}
+
+#[test]
+fn liveness_goes_stale_once_the_window_passes_without_messages() {
+    let liveness = Liveness {
+        started_at: 1_000,
+        last_message_at: None,
+    };
+    // Fresh from startup until the window runs out.
+    assert!(liveness.is_fresh_at(1_060, 60));
+    assert!(!liveness.is_fresh_at(1_061, 60));
+
+    let liveness = Liveness {
+        last_message_at: Some(2_000),
+        ..liveness
+    };
+    assert!(liveness.is_fresh_at(2_030, 60));
+    assert!(!liveness.is_fresh_at(2_061, 60));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn health_reports_stale_until_a_message_is_processed() {
+    let liveness: SharedLiveness = Arc::new(Mutex::new(Liveness {
+        started_at: 0,
+        last_message_at: Some(0),
+    }));
+    let port = free_port();
+    let server = tokio::spawn(serve_health(Arc::clone(&liveness), port, 60));
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+
+    let stale = get_when_up(addr, "/health").await;
+    assert!(
+        stale.starts_with("HTTP/1.1 503 Service Unavailable\r\n"),
+        "{}",
+        stale
+    );
+    assert!(stale.ends_with("STALE"), "{}", stale);
+
+    liveness.lock().unwrap().record_message();
+    let ok = get(addr, "/health").await;
+    assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"), "{}", ok);
+    assert!(ok.ends_with("OK"), "{}", ok);
+
+    server.abort();
+}
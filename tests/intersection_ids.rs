@@ -0,0 +1,45 @@
+// Intersection ids parse from the strings the services log and publish, and round-trip through
+// their canonical text form.
+use rts_assignment::c1_tp063879::intersections::{IntersectionId, ParseIntersectionIdError};
+use rts_assignment::c4_tp071994::traffic_monitoring_system::parse_intersection;
+
+#[test]
+fn parse_intersection_reads_sample_strings() {
+    assert_eq!(parse_intersection("IntersectionId(0, 2)"), Some((0, 2)));
+    assert_eq!(parse_intersection("IntersectionId(5, 5)"), Some((5, 5)));
+    assert_eq!(parse_intersection(" IntersectionId(3,1) "), Some((3, 1)));
+    assert_eq!(parse_intersection("4, 0"), Some((4, 0)));
+    assert_eq!(parse_intersection("IntersectionId(-1, 7)"), Some((-1, 7)));
+
+    // Coordinates must fit the i8 IntersectionId uses.
+    assert_eq!(parse_intersection("IntersectionId(200, 1)"), None);
+    assert_eq!(parse_intersection("Intersection 02"), None);
+    assert_eq!(parse_intersection("IntersectionId(1)"), None);
+    assert_eq!(parse_intersection(""), None);
+}
+
+#[test]
+fn ids_round_trip_through_their_canonical_string() {
+    let ids = [
+        IntersectionId(0, 0),
+        IntersectionId(1, 2),
+        IntersectionId(5, 5),
+        IntersectionId(-1, 7),
+        IntersectionId(3, -4),
+        IntersectionId(i8::MIN, i8::MAX),
+    ];
+    for id in ids {
+        assert_eq!(id.to_string().parse::<IntersectionId>(), Ok(id));
+    }
+    assert_eq!(IntersectionId(-1, 7).to_string(), "IntersectionId(-1, 7)");
+
+    // The bare form a user types parses too; anything else is an error naming the input.
+    assert_eq!(" 4 , 0 ".parse(), Ok(IntersectionId(4, 0)));
+    assert_eq!(
+        "IntersectionId(200, 1)".parse::<IntersectionId>(),
+        Err(ParseIntersectionIdError(
+            "IntersectionId(200, 1)".to_string()
+        ))
+    );
+    assert!("Intersection 02".parse::<IntersectionId>().is_err());
+}
@@ -0,0 +1,176 @@
+// Which lane definitions are accepted, and how many vehicles a lane admits given its following
+// gap and current restrictions.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::{
+    create_lanes, Lane, LaneError, LaneStore, OccupancyMetric, DEFAULT_EMERGENCY_CAPACITY_FACTOR,
+    DEFAULT_MIN_GAP_METERS,
+};
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const FROM: IntersectionId = IntersectionId(0, 0);
+const TO: IntersectionId = IntersectionId(0, 1);
+
+fn lane(length: f64) -> Lane {
+    Lane::new("test lane".to_string(), FROM, TO, length).unwrap()
+}
+
+fn car(id: u64) -> Vehicle {
+    Vehicle::new(id, VehicleType::Car, FROM, TO, 50.0)
+}
+
+// Adds cars until the lane refuses one, returning how many it took.
+fn fill_with_cars(lane: &mut Lane) -> usize {
+    let mut id = 0;
+    while lane.add_vehicle(&car(id)) {
+        id += 1;
+    }
+    id as usize
+}
+
+#[test]
+fn an_enforced_gap_admits_fewer_vehicles() {
+    let mut bumper_to_bumper = lane(20.0).with_min_gap(0.0);
+    let mut spaced = lane(20.0);
+    assert_eq!(spaced.min_gap, DEFAULT_MIN_GAP_METERS);
+
+    let packed = fill_with_cars(&mut bumper_to_bumper);
+    let gapped = fill_with_cars(&mut spaced);
+    assert_eq!(packed, 10);
+    assert_eq!(gapped, 5);
+    // Both lanes are full, counting the gaps as taken space.
+    assert_eq!(bumper_to_bumper.occupancy(), 1.0);
+    assert_eq!(spaced.occupancy(), 1.0);
+}
+
+#[test]
+fn count_occupancy_tells_one_bus_from_three_cars_of_the_same_length() {
+    let mut with_bus = lane(20.0).with_min_gap(0.0);
+    let mut with_cars = lane(20.0).with_min_gap(0.0);
+    assert!(with_bus.add_vehicle(&Vehicle::new(1, VehicleType::Bus, FROM, TO, 30.0)));
+    for id in 1..=3 {
+        assert!(with_cars.add_vehicle(&car(id)));
+    }
+
+    // Same space taken up, so the length metric can't tell them apart.
+    assert_eq!(with_bus.occupancy(), with_cars.occupancy());
+    assert_eq!(
+        with_bus.occupancy_for(OccupancyMetric::Length),
+        with_cars.occupancy_for(OccupancyMetric::Length)
+    );
+    // By count the three cars fill three times as much of the lane's capacity.
+    assert_eq!(with_bus.vehicle_capacity(), 10);
+    assert_eq!(with_cars.vehicle_capacity(), 10);
+    assert_eq!(with_bus.occupancy_for(OccupancyMetric::Count), 0.1);
+    assert_eq!(with_cars.occupancy_for(OccupancyMetric::Count), 0.3);
+}
+
+#[test]
+fn lanes_without_a_positive_length_are_rejected() {
+    for length in [0.0, -5.0, f64::NAN] {
+        match Lane::new("bad".to_string(), FROM, TO, length) {
+            Err(LaneError::NonPositiveLength { name, .. }) => assert_eq!(name, "bad"),
+            other => panic!("length {} gave {:?}", length, other),
+        }
+    }
+}
+
+#[test]
+fn lanes_that_loop_back_to_their_start_are_rejected() {
+    match Lane::new("loop".to_string(), FROM, FROM, 100.0) {
+        Err(LaneError::SelfLoop { name, at }) => {
+            assert_eq!(name, "loop");
+            assert_eq!(at, FROM);
+        }
+        other => panic!("self-loop gave {:?}", other),
+    }
+}
+
+#[test]
+fn valid_lanes_are_built_as_before() {
+    let lane = lane(120.0);
+    assert_eq!(lane.name, "test lane");
+    assert_eq!((lane.from, lane.to), (FROM, TO));
+    assert_eq!(lane.length_meters, 120.0);
+    assert_eq!(lane.current_vehicle_length, 0.0);
+    assert!(lane.vehicle_queue.is_empty());
+    // The built-in grid's literals are all valid.
+    let lanes = create_lanes();
+    assert!(!lanes.is_empty());
+    assert!(lanes
+        .iter()
+        .all(|lane| lane.length_meters > 0.0 && lane.from != lane.to));
+}
+
+#[test]
+fn a_lane_with_an_emergency_vehicle_keeps_its_capacity_factor() {
+    let van = Vehicle::new(1_000, VehicleType::EmergencyVan, FROM, TO, 80.0);
+    let space = lane(200.0).space_needed(&car(0));
+
+    let mut shared = lane(200.0);
+    assert_eq!(
+        shared.emergency_capacity_factor,
+        DEFAULT_EMERGENCY_CAPACITY_FACTOR
+    );
+    assert!(shared.add_vehicle(&van));
+    let admitted = fill_with_cars(&mut shared);
+    assert_eq!(admitted, (100.0 / space).floor() as usize);
+    assert!(admitted > 0);
+    assert!(shared.occupancy() <= 0.5);
+    assert!(shared.occupancy() + space / 200.0 > 0.5);
+
+    // With a factor of 0.0 the emergency vehicle keeps the lane to itself.
+    let mut blocked = lane(200.0).with_emergency_capacity_factor(0.0);
+    assert!(blocked.add_vehicle(&van));
+    assert_eq!(fill_with_cars(&mut blocked), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_car_still_drives_onto_a_built_in_lane_with_an_emergency_vehicle_on_it() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    // Leave from an intersection without lights so nothing but the van could hold the car up.
+    let lane = lanes
+        .iter()
+        .find(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control != IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving an uncontrolled intersection")
+        .clone();
+    let controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+    let ctx = JourneyContext {
+        time_scale: 50.0,
+        ..JourneyContext::new(
+            Arc::new(RwLock::new(intersections)),
+            Arc::new(LaneStore::new(lanes)),
+            Arc::new(RwLock::new(controller)),
+            Arc::new(SimulationConfig {
+                crash_probability: 0.0,
+                overspeed_crash_probability: 0.0,
+                collision_probability: 0.0,
+                ..SimulationConfig::default()
+            }),
+        )
+    };
+    let van = Vehicle::new(1_000, VehicleType::EmergencyVan, lane.from, lane.to, 80.0);
+    assert!(ctx.lanes.lock_lane(&lane.name).unwrap().add_vehicle(&van));
+
+    ctx.active_ids.lock().unwrap().insert(1);
+    let vehicle_events = Arc::clone(&ctx.vehicle_events);
+    let car = Vehicle::new(1, VehicleType::Car, lane.from, lane.to, 40.0);
+    let journey = simulate_vehicle_journey(car, vec![lane], SmallRng::seed_from_u64(1), ctx);
+    tokio::time::timeout(Duration::from_secs(10), journey)
+        .await
+        .expect("the car was kept off the lane");
+    assert_eq!(vehicle_events.lock().unwrap().len(), 1);
+}
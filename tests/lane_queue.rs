@@ -0,0 +1,141 @@
+// Order of the vehicles queued on a lane, and its peak length.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::lanes::{Lane, LaneStore, OVERTAKING_MAX_OCCUPANCY};
+use rts_assignment::c1_tp063879::simulation::peak_queue_lengths;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+
+const FROM: IntersectionId = IntersectionId(0, 0);
+const TO: IntersectionId = IntersectionId(0, 1);
+
+fn lane(length: f64) -> Lane {
+    Lane::new("test lane".to_string(), FROM, TO, length).unwrap()
+}
+
+fn vehicle(id: u64, vehicle_type: VehicleType, speed: f64) -> Vehicle {
+    Vehicle::new(id, vehicle_type, FROM, TO, speed)
+}
+
+fn queue_ids(lane: &Lane) -> Vec<u64> {
+    lane.vehicle_queue.iter().map(|v| v.id).collect()
+}
+
+#[test]
+fn ambulances_are_served_before_queued_cars() {
+    let mut lane = lane(300.0);
+    for id in 1..=3 {
+        assert!(lane.add_vehicle(&vehicle(id, VehicleType::Car, 50.0)));
+    }
+    assert!(lane.add_vehicle(&vehicle(10, VehicleType::EmergencyVan, 80.0)));
+    assert_eq!(queue_ids(&lane), [10, 1, 2, 3]);
+
+    // A second ambulance queues behind the first but still ahead of the cars.
+    assert!(lane.add_vehicle(&vehicle(11, VehicleType::EmergencyVan, 80.0)));
+    assert_eq!(queue_ids(&lane), [10, 11, 1, 2, 3]);
+
+    // The front vehicle leaves first: both ambulances go before any car.
+    let mut served = Vec::new();
+    while let Some(front) = lane.vehicle_queue.front().cloned() {
+        lane.remove_vehicle(&front);
+        served.push(front.id);
+    }
+    assert_eq!(served, [10, 11, 1, 2, 3]);
+    assert!(!lane.has_emergency_vehicle);
+}
+
+// A slow truck at the front with a faster car queued right behind it.
+fn truck_then_car(lane: &mut Lane) {
+    assert!(lane.add_vehicle(&vehicle(1, VehicleType::Truck, 15.0)));
+    assert!(lane.add_vehicle(&vehicle(2, VehicleType::Car, 40.0)));
+}
+
+#[test]
+fn fast_car_overtakes_slow_truck_on_an_uncongested_lane() {
+    let mut lane = lane(300.0).with_overtaking(true);
+    truck_then_car(&mut lane);
+    assert!(lane.occupancy() < OVERTAKING_MAX_OCCUPANCY);
+
+    assert_eq!(lane.try_overtake(2), Some(1));
+    assert_eq!(queue_ids(&lane), [2, 1]);
+    // The truck is slower, so it cannot pass back.
+    assert_eq!(lane.try_overtake(1), None);
+}
+
+#[test]
+fn fast_car_stays_behind_slow_truck_on_a_congested_lane() {
+    let mut lane = lane(20.0).with_overtaking(true);
+    truck_then_car(&mut lane);
+    assert!(lane.occupancy() >= OVERTAKING_MAX_OCCUPANCY);
+
+    assert_eq!(lane.try_overtake(2), None);
+    assert_eq!(queue_ids(&lane), [1, 2]);
+}
+
+#[test]
+fn overtaking_needs_the_lane_to_allow_it_and_no_emergency_vehicle() {
+    let mut closed = lane(300.0);
+    truck_then_car(&mut closed);
+    assert_eq!(closed.try_overtake(2), None);
+
+    let mut lane = lane(300.0).with_overtaking(true);
+    truck_then_car(&mut lane);
+    assert!(lane.add_vehicle(&vehicle(3, VehicleType::EmergencyVan, 60.0)));
+    assert_eq!(lane.try_overtake(2), None);
+    assert_eq!(queue_ids(&lane), [3, 1, 2]);
+}
+
+#[test]
+fn cars_cannot_enter_a_lane_reserved_for_an_emergency_until_it_passes() {
+    let mut lane = lane(300.0);
+    lane.reserve_for_emergency();
+    assert!(!lane.add_vehicle(&vehicle(1, VehicleType::Car, 50.0)));
+
+    // The emergency vehicle itself still gets on, and passes through.
+    let ambulance = vehicle(10, VehicleType::EmergencyVan, 80.0);
+    assert!(lane.add_vehicle(&ambulance));
+    assert!(!lane.add_vehicle(&vehicle(2, VehicleType::Car, 50.0)));
+    lane.remove_vehicle(&ambulance);
+    lane.release_emergency_reservation();
+
+    assert!(lane.add_vehicle(&vehicle(3, VehicleType::Car, 50.0)));
+    assert_eq!(queue_ids(&lane), [3]);
+}
+
+#[test]
+fn max_queue_len_keeps_the_peak_after_vehicles_leave() {
+    let mut lane = lane(300.0);
+    let cars: Vec<Vehicle> = (1..=4)
+        .map(|id| vehicle(id, VehicleType::Car, 50.0))
+        .collect();
+    for car in &cars[..3] {
+        assert!(lane.add_vehicle(car));
+    }
+    lane.remove_vehicle(&cars[0]);
+    lane.remove_vehicle(&cars[1]);
+    assert!(lane.add_vehicle(&cars[3]));
+    assert_eq!(lane.vehicle_queue.len(), 2);
+    assert_eq!(lane.max_queue_len, 3);
+
+    // Snapshots clone the lane, peak included.
+    assert_eq!(lane.clone().max_queue_len, 3);
+    let store = LaneStore::new(vec![lane]);
+    assert_eq!(peak_queue_lengths(&store), [("test lane".to_string(), 3)]);
+}
+
+#[test]
+fn a_vehicle_blocked_for_several_ticks_is_counted_once() {
+    let mut lane = lane(300.0);
+    let car = vehicle(1, VehicleType::Car, 50.0);
+    assert!(lane.add_vehicle(&car));
+    let occupancy = lane.occupancy();
+    assert!(occupancy > 0.0);
+
+    // The waiting path re-adds the vehicle every tick it's held at the light.
+    for _ in 0..5 {
+        assert!(lane.add_vehicle(&car));
+    }
+    assert_eq!(queue_ids(&lane), [1]);
+    assert_eq!(lane.occupancy(), occupancy);
+
+    lane.remove_vehicle(&car);
+    assert_eq!(lane.occupancy(), 0.0);
+}
@@ -0,0 +1,134 @@
+// The lane store's name index stays in step with its lanes, and its per-lane locks hold up under
+// many concurrent vehicles.
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionId};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, Lane, LaneStore, OccupancyMetric};
+use rts_assignment::c1_tp063879::simulation::collect_traffic_data_from_store;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+fn lane(name: &str, length: f64) -> Lane {
+    Lane::new(
+        name.to_string(),
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        length,
+    )
+    .unwrap()
+}
+
+// Every lane in the store can be found by its own name.
+fn assert_index_consistent(store: &LaneStore) {
+    for lane in store.snapshot() {
+        let found = store.lock_lane(&lane.name).expect("indexed lane");
+        assert_eq!(found.name, lane.name);
+        assert_eq!(found.length_meters, lane.length_meters);
+    }
+}
+
+#[test]
+fn the_index_stays_consistent_after_add_and_remove() {
+    let mut store = LaneStore::new(vec![lane("a", 100.0), lane("b", 200.0), lane("c", 300.0)]);
+    assert_index_consistent(&store);
+
+    store.push(lane("d", 400.0));
+    assert_eq!(store.len(), 4);
+    assert_index_consistent(&store);
+
+    // Removing from the middle shifts later lanes down; they must still be found.
+    assert_eq!(store.remove("b").map(|l| l.length_meters), Some(200.0));
+    assert_eq!(store.len(), 3);
+    assert!(store.lock_lane("b").is_none());
+    assert!(store.remove("b").is_none());
+    assert_index_consistent(&store);
+
+    // Pushing an existing name replaces that lane in place.
+    store.push(lane("c", 350.0));
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.lock_lane("c").unwrap().length_meters, 350.0);
+    assert_index_consistent(&store);
+
+    let names: Vec<String> = store.into_vec().into_iter().map(|l| l.name).collect();
+    assert_eq!(names, ["a", "c", "d"]);
+}
+
+const VEHICLES: usize = 200;
+const HOPS: usize = 12;
+
+#[test]
+fn many_concurrent_vehicles_neither_deadlock_nor_lose_occupancy() {
+    let store = Arc::new(LaneStore::new(create_lanes()));
+    let names: Arc<Vec<String>> = Arc::new(store.snapshot().into_iter().map(|l| l.name).collect());
+    let (done_tx, done_rx) = mpsc::channel();
+
+    let mut workers = Vec::new();
+    for id in 0..VEHICLES {
+        let (store, names) = (Arc::clone(&store), Arc::clone(&names));
+        workers.push(thread::spawn(move || {
+            let vehicle = Vehicle::new(
+                id as u64,
+                VehicleType::Car,
+                IntersectionId(0, 0),
+                IntersectionId(3, 3),
+                50.0,
+            );
+            // Hop across lanes, holding one lane lock at a time as the vehicle tasks do. The
+            // vehicle stays on its last lane.
+            for hop in 0..HOPS {
+                let lane = &names[(id * 7 + hop * 3) % names.len()];
+                while !store.lock_lane(lane).unwrap().add_vehicle(&vehicle) {
+                    thread::yield_now();
+                }
+                if hop + 1 < HOPS {
+                    store.lock_lane(lane).unwrap().remove_vehicle(&vehicle);
+                }
+            }
+        }));
+    }
+    // A reader taking every lane lock at once while the vehicles move.
+    let reader = {
+        let store = Arc::clone(&store);
+        thread::spawn(move || {
+            let intersections = create_intersections();
+            for _ in 0..50 {
+                collect_traffic_data_from_store(
+                    &store,
+                    &intersections,
+                    vec![],
+                    OccupancyMetric::Count,
+                );
+            }
+        })
+    };
+    thread::spawn(move || {
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        reader.join().unwrap();
+        let _ = done_tx.send(());
+    });
+    done_rx
+        .recv_timeout(Duration::from_secs(30))
+        .expect("lane store deadlocked");
+
+    // Each vehicle is on exactly the lane it finished on.
+    let lanes = store.snapshot();
+    let mut expected = vec![0; names.len()];
+    for id in 0..VEHICLES {
+        expected[(id * 7 + (HOPS - 1) * 3) % names.len()] += 1;
+    }
+    for (lane, count) in lanes.iter().zip(expected) {
+        assert_eq!(lane.vehicle_queue.len(), count, "{}", lane.name);
+        let reserved: f64 = lane
+            .vehicle_queue
+            .iter()
+            .map(|v| lane.space_needed(v))
+            .sum();
+        assert!(
+            (lane.current_vehicle_length - reserved).abs() < 1e-9,
+            "{}",
+            lane.name
+        );
+    }
+}
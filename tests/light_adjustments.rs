@@ -0,0 +1,274 @@
+// Light adjustments applied in-process to the controller, without a broker.
+use rts_assignment::bus::{InMemoryBus, MessageBus};
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::apply_signal_adjustments;
+use rts_assignment::c2_tp063881::traffic_analyzer::AnalyzerThresholds;
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    IntersectionController, TrafficLightController, TrafficLightPhase,
+};
+use rts_assignment::global_variables::{queue_name, QUEUE_SIGNAL_ADJUSTMENTS};
+use rts_assignment::shared_data::{CongestionAlert, LightAdjustment, TrafficData};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+fn controller() -> TrafficLightController {
+    TrafficLightController::initialize(create_intersections(), &create_lanes())
+}
+
+fn traffic_light() -> IntersectionId {
+    create_intersections()
+        .into_iter()
+        .find(|i| i.control == IntersectionControl::TrafficLight)
+        .expect("an intersection with traffic lights")
+        .id
+}
+
+fn current_duration(controller: &TrafficLightController, id: IntersectionId) -> u64 {
+    controller.controllers[&id].current_phase_duration()
+}
+
+#[test]
+fn apply_adjustment_lengthens_the_current_phase() {
+    let mut controller = controller();
+    let id = traffic_light();
+    let before = current_duration(&controller, id);
+
+    assert!(controller.apply_adjustment(id, 5));
+    assert_eq!(current_duration(&controller, id), before + 5);
+    // Other phases are untouched.
+    let ctrl = &controller.controllers[&id];
+    for (index, phase) in ctrl.phases.iter().enumerate() {
+        if index != ctrl.current_phase_index {
+            assert_eq!(phase.duration, before);
+        }
+    }
+}
+
+#[test]
+fn an_extension_lasts_only_until_the_phase_ends() {
+    let mut controller = controller();
+    let id = traffic_light();
+    let base = current_duration(&controller, id);
+    let phases = controller.controllers[&id].phases.len();
+    controller.apply_adjustment(id, 5);
+
+    // The extended green runs its extra seconds once...
+    for _ in 0..base + 4 {
+        controller.update_all();
+    }
+    assert_eq!(controller.controllers[&id].current_phase_index, 0);
+    controller.update_all();
+    assert_eq!(controller.controllers[&id].current_phase_index, 1 % phases);
+
+    // ...and the next time round the phase is back to its configured length.
+    for _ in 0..base * (phases as u64 - 1) {
+        controller.update_all();
+    }
+    assert_eq!(controller.controllers[&id].current_phase_index, 0);
+    assert_eq!(current_duration(&controller, id), base);
+    assert_eq!(controller.controllers[&id].phases[0].duration, base);
+}
+
+#[test]
+fn only_alerts_that_change_a_light_produce_an_adjustment() {
+    let mut controller = controller();
+    let alert = |timestamp, intersection: Option<String>| CongestionAlert {
+        timestamp,
+        intersection,
+        message: "congested".to_string(),
+        congestion_perc: 90.0,
+        recommended_action: String::new(),
+        run_id: "run".to_string(),
+    };
+    let light = Some(traffic_light().to_string());
+
+    let adjustment = controller.respond_to_alert(&alert(1, light.clone()));
+    assert_eq!(
+        adjustment.map(|a| (a.intersection_id, a.run_id)),
+        Some((traffic_light().to_string(), "run".to_string()))
+    );
+    // A re-delivered alert, an intersection without a light and an alert without an intersection
+    // leave the lights alone, so there is nothing to publish.
+    assert!(controller.respond_to_alert(&alert(1, light)).is_none());
+    let uncontrolled = create_intersections()
+        .into_iter()
+        .find(|i| i.control != IntersectionControl::TrafficLight)
+        .expect("an intersection without traffic lights")
+        .id;
+    assert!(controller
+        .respond_to_alert(&alert(2, Some(uncontrolled.to_string())))
+        .is_none());
+    assert!(controller.respond_to_alert(&alert(3, None)).is_none());
+}
+
+#[test]
+fn apply_adjustment_reports_intersections_without_a_light() {
+    let mut controller = controller();
+    let uncontrolled = create_intersections()
+        .into_iter()
+        .find(|i| i.control != IntersectionControl::TrafficLight)
+        .expect("an intersection without traffic lights")
+        .id;
+    assert!(!controller.apply_adjustment(uncontrolled, 5));
+}
+
+fn adjustment(timestamp: u64, id: IntersectionId, add_seconds_green: u32) -> LightAdjustment {
+    LightAdjustment {
+        timestamp,
+        intersection_id: id.to_string(),
+        add_seconds_green,
+        run_id: String::new(),
+    }
+}
+
+#[test]
+fn a_redelivered_adjustment_is_applied_once() {
+    let mut controller = controller().with_max_green(100);
+    let id = traffic_light();
+    let before = current_duration(&controller, id);
+
+    assert!(controller.apply_light_adjustment(&adjustment(1_000, id, 5)));
+    assert!(!controller.apply_light_adjustment(&adjustment(1_000, id, 5)));
+    assert_eq!(current_duration(&controller, id), before + 5);
+
+    // A new alert for the same intersection is a different key.
+    assert!(controller.apply_light_adjustment(&adjustment(1_001, id, 5)));
+    assert_eq!(current_duration(&controller, id), before + 10);
+}
+
+#[test]
+fn repeated_adjustments_never_exceed_max_green() {
+    let id = traffic_light();
+    let max_green = current_duration(&controller(), id) + 12;
+    let mut controller = controller().with_max_green(max_green);
+
+    for timestamp in 0..10 {
+        controller.apply_light_adjustment(&adjustment(timestamp, id, 5));
+        assert!(current_duration(&controller, id) <= max_green);
+    }
+    assert_eq!(current_duration(&controller, id), max_green);
+}
+
+// A prediction with only `id` congested, at `congestion`.
+fn prediction(id: IntersectionId, congestion: f64) -> TrafficData {
+    TrafficData {
+        lane_occupancy: HashMap::new(),
+        accident_lanes: HashSet::new(),
+        intersection_congestion: HashMap::from([(id.to_string(), congestion)]),
+        intersection_waiting_time: HashMap::new(),
+        vehicle_data: Vec::new(),
+        intersection_max_direction_congestion: HashMap::new(),
+        lane_waiting_time: HashMap::new(),
+        lane_speeds: HashMap::new(),
+    }
+}
+
+#[test]
+fn predictions_are_acted_on_at_the_analyzers_congestion_threshold() {
+    let id = traffic_light();
+    let defaults = AnalyzerThresholds::default();
+    let raised = AnalyzerThresholds {
+        intersection_congestion: 0.7,
+        ..defaults
+    };
+    let congested = prediction(id, 0.6);
+
+    assert_eq!(controller().apply_prediction(&congested, &defaults), [id]);
+    assert!(controller()
+        .apply_prediction(&congested, &raised)
+        .is_empty());
+}
+
+#[test]
+fn predicted_lane_congestion_is_compared_against_the_lane_threshold() {
+    let mut controller = controller();
+    let id = traffic_light();
+    let lane = controller.controllers[&id].phases[0].green_lanes[0].clone();
+    let predicted = HashMap::from([(lane, 0.85)]);
+
+    controller.adjust_phases_based_on_prediction(id, &predicted, &AnalyzerThresholds::default());
+    assert_eq!(controller.controllers[&id].phases[0].duration, 2);
+
+    let raised = AnalyzerThresholds {
+        lane_congestion: 0.9,
+        ..AnalyzerThresholds::default()
+    };
+    controller.adjust_phases_based_on_prediction(id, &predicted, &raised);
+    assert_eq!(controller.controllers[&id].phases[0].duration, 1);
+}
+
+#[test]
+fn rebalancing_gives_the_busier_phase_more_green_within_the_same_cycle() {
+    let intersection = create_intersections()
+        .into_iter()
+        .find(|i| i.control == IntersectionControl::TrafficLight)
+        .expect("an intersection with traffic lights");
+    let phase = |lanes: &[&str]| TrafficLightPhase {
+        green_lanes: lanes.iter().map(|l| l.to_string()).collect(),
+        duration: 10,
+        yellow_secs: 0,
+    };
+    let mut ctrl = IntersectionController::new(
+        intersection,
+        vec![phase(&["main in", "main out"]), phase(&["side"])],
+        vec![
+            "main in".to_string(),
+            "main out".to_string(),
+            "side".to_string(),
+        ],
+    );
+    let occupancy = HashMap::from([
+        ("main in".to_string(), 0.9),
+        ("main out".to_string(), 0.7),
+        ("side".to_string(), 0.2),
+    ]);
+
+    ctrl.rebalance_phases(&occupancy, 20);
+    let durations: Vec<u64> = ctrl.phases.iter().map(|p| p.duration).collect();
+    assert_eq!(durations.iter().sum::<u64>(), 20);
+    assert!(durations[0] > durations[1], "{:?}", durations);
+    // 0.8 and 0.2 of the 18 seconds above the 1-second minimums (14.4 and 3.6), with the leftover
+    // second going to the larger remainder.
+    assert_eq!(durations, [15, 5]);
+
+    // Without any demand the cycle is split evenly.
+    ctrl.rebalance_phases(&HashMap::new(), 20);
+    assert!(ctrl.phases.iter().all(|p| p.duration == 10));
+}
+
+#[test]
+fn the_simulation_applies_the_controllers_adjustments_for_its_run() {
+    let bus = InMemoryBus::new();
+    let queue = queue_name(QUEUE_SIGNAL_ADJUSTMENTS);
+    let subscription = bus.subscribe(&queue).unwrap();
+    let controller = RwLock::new(controller().with_max_green(100));
+    let id = traffic_light();
+    let before = current_duration(&controller.read().unwrap(), id);
+
+    let tagged = |timestamp, run_id: &str| LightAdjustment {
+        run_id: run_id.to_string(),
+        ..adjustment(timestamp, id, 5)
+    };
+    for adjustment in [tagged(1, "this-run"), tagged(2, "other-run"), tagged(3, "")] {
+        bus.publish(&queue, &serde_json::to_vec(&adjustment).unwrap())
+            .unwrap();
+    }
+    bus.publish(&queue, b"not an adjustment").unwrap();
+
+    assert_eq!(
+        apply_signal_adjustments(&subscription, &controller, "this-run"),
+        2
+    );
+    assert_eq!(
+        current_duration(&controller.read().unwrap(), id),
+        before + 10
+    );
+    // Nothing is left waiting.
+    assert_eq!(
+        apply_signal_adjustments(&subscription, &controller, "this-run"),
+        0
+    );
+}
@@ -0,0 +1,199 @@
+// A lane's signal runs green, yellow and red over a full phase cycle, with all-red pedestrian
+// crossings at the configured cadence; an intersection without phases stays green. Emergency
+// overrides that hold the phases too long are force-cleared.
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    IntersectionController, LightState, TrafficLightController, TrafficLightPhase,
+};
+
+const LANES: [&str; 2] = ["a", "b"];
+
+fn phase(lane: &str, duration: u64, yellow_secs: u64) -> TrafficLightPhase {
+    TrafficLightPhase {
+        green_lanes: vec![lane.to_string()],
+        duration,
+        yellow_secs,
+    }
+}
+
+// A light controller whose only traffic light runs the given controller setup over lanes a and b.
+fn controller_with(
+    build: impl FnOnce(IntersectionController) -> IntersectionController,
+    phases: Vec<TrafficLightPhase>,
+) -> (TrafficLightController, IntersectionId) {
+    let intersection = create_intersections()
+        .into_iter()
+        .find(|i| i.control == IntersectionControl::TrafficLight)
+        .expect("an intersection with traffic lights");
+    let id = intersection.id;
+    let mut controller = TrafficLightController::initialize(Vec::new(), &[]);
+    let lanes = LANES.iter().map(|l| l.to_string()).collect();
+    controller.controllers.insert(
+        id,
+        build(IntersectionController::new(intersection, phases, lanes)),
+    );
+    (controller, id)
+}
+
+#[test]
+fn lane_signal_covers_green_yellow_and_red_across_a_cycle() {
+    let (mut controller, id) = controller_with(|c| c, vec![phase("a", 5, 2), phase("b", 5, 2)]);
+
+    let mut signals = Vec::new();
+    for _ in 0..10 {
+        signals.push(controller.lane_signal(id, "a"));
+        assert_eq!(
+            controller.is_lane_green(id, "a"),
+            signals.last() == Some(&LightState::Green)
+        );
+        controller.update_all();
+    }
+    use LightState::{Green, Red, Yellow};
+    assert_eq!(
+        signals,
+        [Green, Green, Green, Yellow, Yellow, Red, Red, Red, Red, Red]
+    );
+    // The next cycle starts green again.
+    assert_eq!(controller.lane_signal(id, "a"), Green);
+}
+
+#[test]
+fn pedestrian_crossing_holds_all_red_every_second_cycle() {
+    let (mut controller, id) = controller_with(
+        |c| c.with_pedestrian_phase(2, 3),
+        vec![phase("a", 2, 0), phase("b", 2, 0)],
+    );
+
+    // Seconds at which no lane at the intersection is green.
+    let mut all_red = Vec::new();
+    for second in 0..24 {
+        if LANES.iter().all(|lane| !controller.is_lane_green(id, lane)) {
+            all_red.push(second);
+        }
+        controller.update_all();
+    }
+    // Each cycle takes 4 seconds; a 3-second crossing follows the second and fourth cycles.
+    assert_eq!(all_red, [8, 9, 10, 19, 20, 21]);
+}
+
+#[test]
+fn emergency_override_preempts_a_pedestrian_crossing() {
+    let (mut controller, id) = controller_with(
+        |c| c.with_pedestrian_phase(1, 5),
+        vec![phase("a", 1, 0), phase("b", 1, 0)],
+    );
+    controller.update_all();
+    controller.update_all();
+    assert!(controller.controllers[&id].is_pedestrian_crossing());
+    assert!(!controller.is_lane_green(id, "b"));
+
+    controller.set_emergency_override_route(id, vec!["b".to_string()]);
+    assert!(controller.is_lane_green(id, "b"));
+    assert!(!controller.is_lane_green(id, "a"));
+}
+
+#[test]
+fn a_controller_without_phases_behaves_as_uncontrolled() {
+    let (mut controller, id) = controller_with(|c| c.with_pedestrian_phase(1, 2), Vec::new());
+    for _ in 0..5 {
+        controller.update_all();
+        for lane in LANES {
+            assert_eq!(controller.lane_signal(id, lane), LightState::Green);
+            assert!(controller.is_lane_green(id, lane));
+        }
+    }
+    let ctrl = &controller.controllers[&id];
+    ctrl.apply_current_phase();
+    assert!(ctrl.current_phase().is_none());
+    assert_eq!(ctrl.secs_until_change(), 0);
+    assert!(!ctrl.is_pedestrian_crossing());
+}
+
+#[test]
+fn a_fresh_controller_reports_only_the_first_phase_green() {
+    let controller = TrafficLightController::initialize(create_intersections(), &create_lanes());
+    let states = controller.current_states();
+    assert!(!states.is_empty());
+    assert_eq!(states.len(), controller.controllers.len());
+
+    for (id, lanes) in &states {
+        let ctrl = &controller.controllers[id];
+        assert_eq!(ctrl.current_phase_index, 0);
+        let green = &ctrl.phases[0].green_lanes;
+        let names: Vec<&String> = lanes.iter().map(|(lane, _)| lane).collect();
+        assert_eq!(names, ctrl.all_lanes.iter().collect::<Vec<_>>());
+        for (lane, state) in lanes {
+            let expected = if green.contains(lane) {
+                LightState::Green
+            } else {
+                LightState::Red
+            };
+            assert_eq!(*state, expected, "{} at {}", lane, id);
+        }
+    }
+}
+
+#[test]
+fn a_held_emergency_override_is_force_cleared_after_the_maximum() {
+    let (mut controller, id) = controller_with(
+        |mut c| {
+            c.max_override_secs = 4;
+            c
+        },
+        vec![phase("a", 3, 0), phase("b", 3, 0)],
+    );
+    let hold = |controller: &mut TrafficLightController| {
+        controller.set_emergency_override_route(id, vec!["b".to_string()]);
+    };
+
+    // Emergency vehicles keep arriving for b, so the override is renewed every second.
+    for _ in 0..3 {
+        hold(&mut controller);
+        assert!(controller.is_lane_green(id, "b"));
+        assert!(!controller.is_lane_green(id, "a"));
+        controller.update_all();
+    }
+    hold(&mut controller);
+    controller.update_all();
+    assert!(controller.controllers[&id].emergency_override.is_none());
+
+    // The starved phase runs its full green; new overrides are refused meanwhile.
+    for _ in 0..3 {
+        hold(&mut controller);
+        assert!(controller.controllers[&id].emergency_override.is_none());
+        assert!(controller.is_lane_green(id, "a"));
+        controller.update_all();
+    }
+    assert!(controller.is_lane_green(id, "b"));
+    hold(&mut controller);
+    assert!(controller.controllers[&id].emergency_override.is_some());
+}
+
+#[test]
+fn a_force_clear_restarts_the_interrupted_phase() {
+    let (mut controller, id) = controller_with(
+        |mut c| {
+            c.max_override_secs = 2;
+            c
+        },
+        vec![phase("a", 3, 0), phase("b", 3, 0)],
+    );
+    // Phase a is two seconds into its three-second green when the override arrives.
+    controller.update_all();
+    controller.update_all();
+    controller.set_emergency_override_route(id, vec!["b".to_string()]);
+    controller.update_all();
+    controller.update_all();
+    assert!(controller.controllers[&id].emergency_override.is_none());
+    assert_eq!(controller.controllers[&id].elapsed_in_phase, 0);
+
+    // a gets its whole green back instead of the one second it had left.
+    for _ in 0..3 {
+        assert!(controller.is_lane_green(id, "a"));
+        controller.update_all();
+    }
+    assert!(controller.is_lane_green(id, "b"));
+}
@@ -0,0 +1,152 @@
+// CSV logs go under the configured output directory, rotate once they reach the size limit and
+// can be read back for a time window.
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    in_time_window, log_congestion_alert, log_intersection_metrics, log_light_adjustment,
+    read_congestion_alerts, rotated_path, OutputPaths,
+};
+use rts_assignment::shared_data::{CongestionAlert, LightAdjustment, TrafficData, TrafficUpdate};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+fn temp_paths(name: &str) -> OutputPaths {
+    let dir = std::env::temp_dir().join(format!("rts_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("create output dir");
+    OutputPaths::in_dir(dir)
+}
+
+fn alert(timestamp: u64) -> CongestionAlert {
+    CongestionAlert {
+        timestamp,
+        intersection: Some("IntersectionId(0, 1)".to_string()),
+        message: "Intersection IntersectionId(0, 1) is heavily congested (0.80)".to_string(),
+        congestion_perc: 0.8,
+        recommended_action: "Adjust traffic light timings to avoid congestion.".to_string(),
+        run_id: String::new(),
+    }
+}
+
+fn lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn logs_are_written_to_the_output_directory() {
+    let names = ["congestion_alerts.csv", "light_adjustments.csv"];
+    let cwd_sizes = || -> Vec<Option<u64>> {
+        names
+            .iter()
+            .map(|name| fs::metadata(name).ok().map(|m| m.len()))
+            .collect()
+    };
+    let before = cwd_sizes();
+
+    let paths = temp_paths("output_paths");
+    log_congestion_alert(&paths, alert(100));
+    log_light_adjustment(
+        &paths,
+        LightAdjustment {
+            timestamp: 100,
+            intersection_id: "IntersectionId(0, 1)".to_string(),
+            add_seconds_green: 4,
+            run_id: String::new(),
+        },
+    );
+
+    for name in names {
+        assert!(paths.base_dir.join(name).exists(), "{} missing", name);
+    }
+    assert_eq!(
+        cwd_sizes(),
+        before,
+        "a log was written to the working directory"
+    );
+    fs::remove_dir_all(&paths.base_dir).unwrap();
+}
+
+#[test]
+fn writing_past_the_limit_rotates_and_re_emits_the_header() {
+    let mut paths = temp_paths("rotation");
+    paths.max_csv_bytes = Some(1);
+    let log = paths.congestion_alerts.clone();
+
+    log_congestion_alert(&paths, alert(100));
+    let first = lines(&log);
+    assert_eq!(first.len(), 2);
+    assert!(first[0].starts_with("timestamp,"));
+
+    // The file is now over the limit, so the next record starts a fresh one.
+    log_congestion_alert(&paths, alert(200));
+    assert_eq!(lines(&rotated_path(&log, 1)), first);
+    let second = lines(&log);
+    assert_eq!(second.len(), 2);
+    assert_eq!(second[0], first[0]);
+    assert!(second[1].starts_with("200,"));
+
+    // Older copies shift up as more files rotate out.
+    log_congestion_alert(&paths, alert(300));
+    assert_eq!(lines(&rotated_path(&log, 2)), first);
+    assert_eq!(lines(&rotated_path(&log, 1)), second);
+    fs::remove_dir_all(&paths.base_dir).unwrap();
+}
+
+#[test]
+fn a_time_window_keeps_only_records_inside_it() {
+    let paths = temp_paths("time_window");
+    for timestamp in [100, 200, 300, 400] {
+        log_congestion_alert(&paths, alert(timestamp));
+    }
+
+    let timestamps = |window| -> Vec<u64> {
+        read_congestion_alerts(&paths, window)
+            .unwrap()
+            .iter()
+            .map(|a| a.timestamp)
+            .collect()
+    };
+    assert_eq!(timestamps(None), [100, 200, 300, 400]);
+    // The window is inclusive at both ends.
+    assert_eq!(timestamps(Some((200, 300))), [200, 300]);
+    assert!(timestamps(Some((500, 600))).is_empty());
+    assert!(in_time_window(7, None));
+    fs::remove_dir_all(&paths.base_dir).unwrap();
+}
+
+#[test]
+fn each_intersection_in_an_update_gets_its_own_metrics_row() {
+    let paths = temp_paths("intersection_metrics");
+    let update = TrafficUpdate {
+        current_data: TrafficData {
+            lane_occupancy: HashMap::new(),
+            accident_lanes: HashSet::new(),
+            intersection_congestion: HashMap::from([
+                ("IntersectionId(1, 1)".to_string(), 0.25),
+                ("IntersectionId(0, 1)".to_string(), 0.5),
+            ]),
+            intersection_waiting_time: HashMap::from([("IntersectionId(0, 1)".to_string(), 3.0)]),
+            vehicle_data: Vec::new(),
+            intersection_max_direction_congestion: HashMap::new(),
+            lane_waiting_time: HashMap::new(),
+            lane_speeds: HashMap::new(),
+        },
+        timestamp: 500,
+        run_id: String::new(),
+        throughput_per_min: 0.0,
+    };
+    log_intersection_metrics(&paths, &update);
+
+    let rows = lines(&paths.intersection_metrics);
+    assert_eq!(
+        rows,
+        [
+            "timestamp,intersection_id,congestion,waiting_time",
+            "500,\"IntersectionId(0, 1)\",0.5,3.0",
+            "500,\"IntersectionId(1, 1)\",0.25,0.0",
+        ]
+    );
+}
@@ -0,0 +1,176 @@
+// End-to-end run of the simulation -> analyzer -> controller -> monitoring pipeline
+// against a real broker. Ignored by default; run it with `cargo test -- --ignored` and
+// AMQP_URL pointing at a reachable RabbitMQ instance.
+use amiquip::{QueueDeclareOptions, QueueDeleteOptions};
+use rts_assignment::amqp::{connect_with_retry, CONNECT_BASE_DELAY, CONNECT_MAX_ATTEMPTS};
+use rts_assignment::c1_tp063879::config::{RushHourConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{run_simulation_for, RunContext};
+use rts_assignment::c2_tp063881::traffic_analyzer::{start_analyzer_rabbitmq, AnalyzerThresholds};
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    start_traffic_controller_rabbitmq, TrafficLightController,
+};
+use rts_assignment::c4_tp071994::metrics::MetricsState;
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    listen_congestion_alerts, listen_traffic_event, OutputPaths,
+};
+use rts_assignment::global_variables::{
+    amqp_url, queue_env_var, queue_name, DEFAULT_CONGESTION_BINDING, QUEUE_CONGESTION_ALERTS,
+    QUEUE_CONTROLLER_ALERTS, QUEUE_DEAD_LETTER, QUEUE_LIGHT_ADJUSTMENTS, QUEUE_SIGNAL_ADJUSTMENTS,
+    QUEUE_SIMULATION_CONTROL, QUEUE_SIMULATION_EVENTS, QUEUE_SPEEDING_ALERTS, QUEUE_TRAFFIC_DATA,
+    QUEUE_TRAFFIC_EVENTS, QUEUE_TRAFFIC_PREDICTIONS,
+};
+use rts_assignment::health::SharedLiveness;
+use rts_assignment::shared_data::current_timestamp;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::watch;
+
+// Every queue the pipeline touches.
+const QUEUES: [&str; 11] = [
+    QUEUE_TRAFFIC_DATA,
+    QUEUE_CONGESTION_ALERTS,
+    QUEUE_TRAFFIC_EVENTS,
+    QUEUE_LIGHT_ADJUSTMENTS,
+    QUEUE_SIMULATION_EVENTS,
+    QUEUE_SIMULATION_CONTROL,
+    QUEUE_SPEEDING_ALERTS,
+    QUEUE_TRAFFIC_PREDICTIONS,
+    QUEUE_CONTROLLER_ALERTS,
+    QUEUE_SIGNAL_ADJUSTMENTS,
+    QUEUE_DEAD_LETTER,
+];
+
+// Gives every queue a name unique to this test run, via the QUEUE_<NAME> overrides.
+fn use_unique_queue_names(suffix: &str) {
+    for default in QUEUES {
+        std::env::set_var(queue_env_var(default), format!("{}_{}", default, suffix));
+    }
+}
+
+fn delete_queues() {
+    let Ok(mut connection) = connect_with_retry(&amqp_url(), 1, CONNECT_BASE_DELAY) else {
+        return;
+    };
+    if let Ok(channel) = connection.open_channel(None) {
+        for default in QUEUES {
+            if let Ok(queue) =
+                channel.queue_declare(queue_name(default), QueueDeclareOptions::default())
+            {
+                let _ = queue.delete(QueueDeleteOptions::default());
+            }
+        }
+    }
+    let _ = connection.close();
+}
+
+fn csv_records(path: &Path) -> usize {
+    csv::Reader::from_path(path)
+        .map(|mut rdr| rdr.records().filter(|r| r.is_ok()).count())
+        .unwrap_or(0)
+}
+
+#[ignore = "needs a RabbitMQ broker at AMQP_URL"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn congestion_alerts_and_traffic_events_reach_the_csv_logs() {
+    connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY)
+        .expect("broker at AMQP_URL");
+
+    let suffix = format!("{}_{}", std::process::id(), current_timestamp());
+    use_unique_queue_names(&suffix);
+    let output_dir = std::env::temp_dir().join(format!("rts_pipeline_{}", suffix));
+    std::fs::create_dir_all(&output_dir).expect("create output dir");
+    let paths = OutputPaths::in_dir(&output_dir);
+    let run_id = format!("pipeline-{}", suffix);
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let metrics = Arc::new(Mutex::new(MetricsState::default()));
+    let mut services = Vec::new();
+    {
+        let (paths, metrics, shutdown) = (paths.clone(), Arc::clone(&metrics), shutdown_rx.clone());
+        services.push(tokio::spawn(async move {
+            let _ = listen_congestion_alerts(
+                None,
+                DEFAULT_CONGESTION_BINDING.to_string(),
+                paths,
+                metrics,
+                shutdown,
+            )
+            .await;
+        }));
+    }
+    {
+        let (paths, metrics, shutdown) = (paths.clone(), Arc::clone(&metrics), shutdown_rx.clone());
+        services.push(tokio::spawn(async move {
+            let _ = listen_traffic_event(None, paths, metrics, shutdown).await;
+        }));
+    }
+    {
+        let shutdown = shutdown_rx.clone();
+        services.push(tokio::spawn(async move {
+            let _ = start_analyzer_rabbitmq(
+                None,
+                AnalyzerThresholds::default(),
+                SharedLiveness::default(),
+                shutdown,
+            )
+            .await;
+        }));
+    }
+    let controller = Arc::new(RwLock::new(TrafficLightController::initialize(
+        create_intersections(),
+        &create_lanes(),
+    )));
+    services.push(tokio::spawn({
+        let shutdown = shutdown_rx.clone();
+        async move {
+            let _ =
+                start_traffic_controller_rabbitmq(controller, SharedLiveness::default(), shutdown)
+                    .await;
+        }
+    }));
+    // Let the consumers declare their queues before anything is published.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // A short, heavy run so intersections congest past the alert threshold.
+    let config = SimulationConfig {
+        rush_hour: RushHourConfig::new(10, 15, 15),
+        ..SimulationConfig::default()
+    };
+    let summary = run_simulation_for(
+        Arc::new(RwLock::new(create_intersections())),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        RunContext {
+            time_scale: 10.0,
+            max_ticks: Some(20),
+            ..RunContext::new(7, run_id)
+        },
+    )
+    .await;
+    assert!(summary.total_spawned > 0);
+
+    // Give the consumers time to drain what the run published.
+    let mut alerts = 0;
+    let mut events = 0;
+    for _ in 0..30 {
+        alerts = csv_records(&paths.congestion_alerts);
+        events = csv_records(&paths.traffic_event);
+        if alerts > 0 && events > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let _ = shutdown_tx.send(true);
+    for service in services {
+        let _ = tokio::time::timeout(Duration::from_secs(5), service).await;
+    }
+    delete_queues();
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    assert!(alerts > 0, "no congestion alert reached the CSV log");
+    assert!(events > 0, "no traffic event reached the CSV log");
+}
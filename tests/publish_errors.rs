@@ -0,0 +1,91 @@
+// A broker that rejects every publish must not stop the simulation loop.
+use rts_assignment::bus::{
+    AmiquipBus, BusResult, MessageBus, Subscription, PUBLISH_FAILURES_BEFORE_RECONNECT,
+};
+use rts_assignment::c1_tp063879::config::{RushHourConfig, SimulationConfig};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{run_simulation_on, RunContext};
+use rts_assignment::global_variables::amqp_url;
+use rts_assignment::shared_data::current_timestamp;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const TICKS: u64 = 5;
+
+#[derive(Default)]
+struct FailingBus {
+    publishes: AtomicU32,
+    reconnects: AtomicU32,
+}
+
+impl MessageBus for FailingBus {
+    fn publish(&self, _queue: &str, _payload: &[u8]) -> BusResult<()> {
+        self.publishes.fetch_add(1, Ordering::SeqCst);
+        Err("broker unavailable".into())
+    }
+
+    fn subscribe(&self, _queue: &str) -> BusResult<Subscription> {
+        Err("broker unavailable".into())
+    }
+
+    fn reconnect(&self) -> BusResult<()> {
+        self.reconnects.fetch_add(1, Ordering::SeqCst);
+        Err("broker unavailable".into())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_publishes_do_not_end_the_run() {
+    let bus = FailingBus::default();
+    let config = SimulationConfig {
+        rush_hour: RushHourConfig::new(40, 0, 0),
+        ..SimulationConfig::default()
+    };
+    let summary = run_simulation_on(
+        &bus,
+        Arc::new(RwLock::new(create_intersections())),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        RunContext {
+            time_scale: 20.0,
+            max_ticks: Some(TICKS),
+            ..RunContext::new(7, "publish-errors")
+        },
+    )
+    .await;
+
+    assert_eq!(summary.total_spawned, 0);
+    // Every tick still tried to publish its traffic update.
+    let publishes = bus.publishes.load(Ordering::SeqCst);
+    assert!(publishes >= TICKS as u32, "only {} publishes", publishes);
+    assert!(
+        bus.reconnects.load(Ordering::SeqCst) >= publishes / PUBLISH_FAILURES_BEFORE_RECONNECT,
+        "repeated failures should trigger reconnects"
+    );
+}
+
+// The reconnect runs in the background, so the async loop that asked for it keeps going.
+// Ignored by default; run it with `cargo test -- --ignored` and AMQP_URL pointing at a
+// reachable RabbitMQ instance.
+#[ignore = "needs a RabbitMQ broker at AMQP_URL"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reconnecting_returns_before_the_broker_is_back() {
+    let bus = AmiquipBus::connect(&amqp_url()).expect("broker at AMQP_URL");
+    let queue = format!("reconnect_{}_{}", std::process::id(), current_timestamp());
+
+    let started = Instant::now();
+    bus.reconnect().expect("reconnect");
+    // A second call while the first is still running is a no-op.
+    bus.reconnect().expect("reconnect");
+    assert!(started.elapsed() < Duration::from_millis(100));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while bus.is_reconnecting() {
+        assert!(Instant::now() < deadline, "reconnect never finished");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    bus.publish(&queue, b"{}").expect("publish after reconnect");
+    let _ = bus.close();
+}
@@ -0,0 +1,111 @@
+// A vehicle held at a red light through several phases must report exactly the time it waited.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionControl};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    TrafficLightController, TrafficLightPhase,
+};
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+const TIME_SCALE: f64 = 10.0;
+const PHASE_SECS: u64 = 3;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn waiting_time_covers_every_red_phase() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let lane = lanes
+        .iter()
+        .find(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control == IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving a traffic light")
+        .clone();
+
+    // Two full phases of red for our lane before it turns green.
+    let mut controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+    {
+        let ctrl = controller.controllers.get_mut(&lane.from).unwrap();
+        let red = |duration| TrafficLightPhase {
+            green_lanes: vec![],
+            duration,
+            yellow_secs: 0,
+        };
+        ctrl.phases = vec![
+            red(PHASE_SECS),
+            red(PHASE_SECS),
+            TrafficLightPhase {
+                green_lanes: vec![lane.name.clone()],
+                duration: 60,
+                yellow_secs: 0,
+            },
+        ];
+        ctrl.current_phase_index = 0;
+        ctrl.elapsed_in_phase = 0;
+        ctrl.pedestrian_phase = None;
+    }
+    let controller = Arc::new(RwLock::new(controller));
+    let paused = Arc::new(AtomicBool::new(false));
+    let update_loop = tokio::spawn(TrafficLightController::run_update_loop(
+        Arc::clone(&controller),
+        TIME_SCALE,
+        Arc::clone(&paused),
+        Duration::from_secs(1),
+    ));
+
+    let config = SimulationConfig {
+        crash_probability: 0.0,
+        overspeed_crash_probability: 0.0,
+        collision_probability: 0.0,
+        ..SimulationConfig::default()
+    };
+    let vehicle = Vehicle::new(1, VehicleType::Car, lane.from, lane.to, 40.0);
+    let waited_at = lane.from;
+    let intersections = Arc::new(RwLock::new(intersections));
+    let ctx = JourneyContext {
+        active_ids: Arc::new(Mutex::new(HashSet::from([1]))),
+        time_scale: TIME_SCALE,
+        paused,
+        ..JourneyContext::new(
+            Arc::clone(&intersections),
+            Arc::new(LaneStore::new(lanes)),
+            controller,
+            Arc::new(config),
+        )
+    };
+    let vehicle_events = Arc::clone(&ctx.vehicle_events);
+    simulate_vehicle_journey(vehicle, vec![lane], SmallRng::seed_from_u64(1), ctx).await;
+    update_loop.abort();
+
+    let events = vehicle_events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let expected = 2 * PHASE_SECS;
+    let waited = events[0].waiting_time;
+    // The journey and the light controller start within one controller tick of each other, and the
+    // controller's first tick runs as soon as it starts.
+    assert!(
+        waited.abs_diff(expected) <= 1,
+        "waited {} seconds, expected {}",
+        waited,
+        expected
+    );
+
+    // The wait is also credited to the intersection the vehicle was held at.
+    let intersections = intersections.read().unwrap();
+    for intersection in intersections.iter() {
+        if intersection.id == waited_at {
+            assert!(intersection.avg_waiting_time() > 0.0);
+        } else {
+            assert_eq!(intersection.avg_waiting_time(), 0.0);
+        }
+    }
+}
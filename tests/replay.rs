@@ -0,0 +1,65 @@
+// Recorded traffic_data.csv rows are re-published as TrafficUpdates; malformed rows are skipped.
+use rts_assignment::bus::InMemoryBus;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::collect_traffic_data;
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    replay_traffic_data_csv, ReplaySummary, TrafficDataRecord,
+};
+use rts_assignment::global_variables::{queue_name, QUEUE_TRAFFIC_DATA};
+use rts_assignment::shared_data::TrafficUpdate;
+use std::time::Duration;
+
+fn update(run_id: &str, timestamp: u64) -> TrafficUpdate {
+    TrafficUpdate {
+        current_data: collect_traffic_data(&create_lanes(), &create_intersections(), vec![]),
+        timestamp,
+        run_id: run_id.to_string(),
+        throughput_per_min: 0.0,
+    }
+}
+
+#[test]
+fn replay_publishes_each_recorded_update_and_skips_malformed_rows() {
+    let path = std::env::temp_dir().join(format!("rts_replay_{}.csv", std::process::id()));
+    {
+        let mut writer = csv::Writer::from_path(&path).unwrap();
+        for (timestamp, raw_data) in [
+            (1, serde_json::to_string(&update("first", 1)).unwrap()),
+            (2, "not a traffic update".to_string()),
+            (3, serde_json::to_string(&update("second", 3)).unwrap()),
+        ] {
+            writer
+                .serialize(TrafficDataRecord {
+                    timestamp,
+                    raw_data,
+                })
+                .unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    let bus = InMemoryBus::new();
+    let summary = replay_traffic_data_csv(&path, &bus, Duration::ZERO).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+        summary,
+        ReplaySummary {
+            published: 2,
+            skipped: 1
+        }
+    );
+    let replayed: Vec<(String, u64)> = bus
+        .published_to(&queue_name(QUEUE_TRAFFIC_DATA))
+        .iter()
+        .map(|payload| {
+            let update: TrafficUpdate = serde_json::from_slice(payload).unwrap();
+            (update.run_id, update.timestamp)
+        })
+        .collect();
+    assert_eq!(
+        replayed,
+        vec![("first".to_string(), 1), ("second".to_string(), 3)]
+    );
+}
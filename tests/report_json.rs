@@ -0,0 +1,79 @@
+// The JSON monitoring report counts what was logged, treats missing logs as empty and
+// round-trips back into MonitoringReport.
+use rts_assignment::c4_tp071994::traffic_monitoring_system::{
+    generate_report_json, log_traffic_data, log_traffic_event, MonitoringReport, OutputPaths,
+    TrafficDataRecord,
+};
+use rts_assignment::shared_data::TrafficEvent;
+
+fn temp_paths(name: &str) -> OutputPaths {
+    let dir = std::env::temp_dir().join(format!("rts_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create output dir");
+    OutputPaths::in_dir(dir)
+}
+
+fn traffic_event(
+    timestamp: u64,
+    average_vehicle_delay: f64,
+    total_accidents: usize,
+) -> TrafficEvent {
+    TrafficEvent {
+        timestamp,
+        average_vehicle_delay,
+        total_accidents,
+        accident_details: Vec::new(),
+        congestion_index: 0.0,
+        total_fuel_used: 0.0,
+        throughput_per_min: 0.0,
+        run_id: String::new(),
+    }
+}
+
+#[test]
+fn a_report_with_no_logs_is_all_zero() {
+    let paths = temp_paths("report_empty");
+    let json = generate_report_json(&paths).expect("report without logs");
+    let report: MonitoringReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        report,
+        MonitoringReport {
+            congestion_alerts: 0,
+            light_adjustments: 0,
+            traffic_data: 0,
+            traffic_events: 0,
+            average_vehicle_delay: 0.0,
+            total_accidents: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+        }
+    );
+}
+
+#[test]
+fn the_report_round_trips_through_json() {
+    let paths = temp_paths("report_round_trip");
+    for timestamp in [100, 300] {
+        log_traffic_data(
+            &paths,
+            TrafficDataRecord {
+                timestamp,
+                raw_data: "{}".to_string(),
+            },
+        );
+    }
+    log_traffic_event(&paths, traffic_event(200, 4.0, 1));
+    log_traffic_event(&paths, traffic_event(250, 6.0, 2));
+
+    let json = generate_report_json(&paths).expect("report");
+    let report: MonitoringReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report.traffic_data, 2);
+    assert_eq!(report.traffic_events, 2);
+    assert_eq!(report.congestion_alerts, 0);
+    assert_eq!(report.light_adjustments, 0);
+    assert_eq!(report.average_vehicle_delay, 5.0);
+    assert_eq!(report.total_accidents, 3);
+    assert_eq!(report.first_timestamp, Some(100));
+    assert_eq!(report.last_timestamp, Some(300));
+    assert_eq!(serde_json::to_string_pretty(&report).unwrap(), json);
+}
@@ -0,0 +1,32 @@
+// Route assignment only accepts routes that start where the vehicle currently is.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::lanes::Lane;
+use rts_assignment::c1_tp063879::simulation::assign_route;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+
+fn lane(from: IntersectionId, to: IntersectionId) -> Lane {
+    Lane::new(format!("{} -> {}", from, to), from, to, 100.0).unwrap()
+}
+
+#[test]
+fn reroute_starting_elsewhere_is_rejected() {
+    let (a, b, c) = (
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        IntersectionId(1, 1),
+    );
+    let vehicle = Vehicle::new(1, VehicleType::Car, a, c, 50.0);
+    let mut route = vec![lane(a, b), lane(b, c)];
+
+    // Starts at b, but the vehicle is still at a.
+    assert!(!assign_route(&vehicle, &mut route, vec![lane(b, c)]));
+    assert!(!assign_route(&vehicle, &mut route, Vec::new()));
+    let hops: Vec<(IntersectionId, IntersectionId)> =
+        route.iter().map(|l| (l.from, l.to)).collect();
+    assert_eq!(hops, [(a, b), (b, c)]);
+
+    // A route from the vehicle's own intersection replaces the old one.
+    assert!(assign_route(&vehicle, &mut route, vec![lane(a, c)]));
+    assert_eq!(route.len(), 1);
+    assert_eq!(route[0].from, a);
+}
@@ -0,0 +1,279 @@
+// Routes generated over the default grid.
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, entry_only_intersections, Intersection, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, Lane, LaneAxis};
+use rts_assignment::c1_tp063879::route_generation::{
+    generate_lane_route_by, generate_restricted_lane_route_by, generate_route_with_objective,
+    generate_shortest_lane_route, turn_restrictions, validate_connectivity, RouteObjective,
+};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::collections::HashSet;
+
+fn ids() -> Vec<IntersectionId> {
+    create_intersections().into_iter().map(|i| i.id).collect()
+}
+
+fn passes_through(route: &[Lane], nodes: &HashSet<IntersectionId>) -> bool {
+    route.iter().any(|lane| nodes.contains(&lane.to))
+}
+
+#[test]
+fn routes_never_pass_through_an_entry_only_intersection() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let entry_only = entry_only_intersections(&intersections);
+    assert!(!entry_only.is_empty());
+
+    let mut detours = 0;
+    for &from in &ids() {
+        for &to in &ids() {
+            if from == to || entry_only.contains(&to) {
+                continue;
+            }
+            let route = generate_shortest_lane_route(&lanes, from, to, &entry_only)
+                .expect("every other intersection is reachable");
+            assert_eq!(route.first().unwrap().from, from);
+            assert_eq!(route.last().unwrap().to, to);
+            // Only the first lane may leave an entry-only intersection, and none drives into one.
+            assert!(!passes_through(&route, &entry_only));
+            for lane in &route[1..] {
+                assert!(!entry_only.contains(&lane.from));
+            }
+
+            let unconstrained =
+                generate_shortest_lane_route(&lanes, from, to, &HashSet::new()).unwrap();
+            if passes_through(&unconstrained, &entry_only) {
+                detours += 1;
+            }
+        }
+    }
+    // Some shortest paths would otherwise cut through an entry point.
+    assert!(detours > 0);
+}
+
+#[test]
+fn an_entry_only_intersection_cannot_be_a_destination() {
+    let intersections = create_intersections();
+    let entry_only = entry_only_intersections(&intersections);
+    let entry = *entry_only.iter().next().unwrap();
+    let from = ids().into_iter().find(|id| *id != entry).unwrap();
+    assert!(generate_shortest_lane_route(&create_lanes(), from, entry, &entry_only).is_none());
+}
+
+#[test]
+fn the_default_grid_is_fully_connected() {
+    assert!(validate_connectivity(&create_intersections(), &create_lanes()).is_empty());
+}
+
+#[test]
+fn disconnected_intersections_are_reported() {
+    let intersections = create_intersections();
+    // Nothing drives into (1, 1), and nothing leaves (2, 2).
+    let unreachable = IntersectionId(1, 1);
+    let dead_end = IntersectionId(2, 2);
+    let lanes: Vec<Lane> = create_lanes()
+        .into_iter()
+        .filter(|lane| lane.to != unreachable && lane.from != dead_end)
+        .collect();
+    assert_eq!(
+        validate_connectivity(&intersections, &lanes),
+        [unreachable, dead_end]
+    );
+}
+
+#[test]
+fn routing_takes_the_diagonal_when_it_is_shorter() {
+    let lanes = create_lanes();
+    let (from, to) = (IntersectionId(1, 1), IntersectionId(2, 2));
+    let no_constraint = HashSet::new();
+
+    let route = generate_shortest_lane_route(&lanes, from, to, &no_constraint).unwrap();
+    assert_eq!(route.len(), 1);
+    assert_eq!(route[0].axis(), LaneAxis::Diagonal);
+
+    // Around the block is longer than the shortcut.
+    let manhattan: Vec<Lane> = lanes
+        .iter()
+        .filter(|lane| lane.axis() != LaneAxis::Diagonal)
+        .cloned()
+        .collect();
+    let detour = generate_shortest_lane_route(&manhattan, from, to, &no_constraint).unwrap();
+    let length = |route: &[Lane]| route.iter().map(|l| l.length_meters).sum::<f64>();
+    assert!(detour.len() > 1);
+    assert!(length(&route) < length(&detour));
+}
+
+#[test]
+fn a_diagonal_lane_gets_a_phase_of_its_own() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let controller = TrafficLightController::initialize(intersections, &lanes);
+    let diagonals: Vec<&Lane> = lanes
+        .iter()
+        .filter(|lane| lane.axis() == LaneAxis::Diagonal)
+        .collect();
+    assert!(!diagonals.is_empty());
+
+    let mut checked = 0;
+    for diagonal in diagonals {
+        let Some(ctrl) = controller.controllers.get(&diagonal.from) else {
+            continue;
+        };
+        let phases: Vec<_> = ctrl
+            .phases
+            .iter()
+            .filter(|phase| phase.green_lanes.contains(&diagonal.name))
+            .collect();
+        assert_eq!(phases.len(), 1);
+        // No crossing lane shares its green.
+        for name in &phases[0].green_lanes {
+            let lane = lanes.iter().find(|l| &l.name == name).unwrap();
+            assert_eq!(lane.axis(), LaneAxis::Diagonal);
+        }
+        checked += 1;
+    }
+    assert!(checked > 0, "no diagonal leaves a traffic light");
+}
+
+#[test]
+fn equal_length_routes_are_broken_the_same_way_whatever_the_lane_order() {
+    // Two 200m ways from (0, 0) to (1, 1): through (0, 1) or through (1, 0).
+    let (start, top, side, end) = (
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        IntersectionId(1, 0),
+        IntersectionId(1, 1),
+    );
+    let lane = |name: &str, from, to| Lane::new(name.to_string(), from, to, 100.0).unwrap();
+    let mut lanes = vec![
+        lane("b1", start, side),
+        lane("b2", side, end),
+        lane("a1", start, top),
+        lane("a2", top, end),
+    ];
+    let no_constraint = HashSet::new();
+
+    for rotation in 0..lanes.len() {
+        lanes.rotate_left(rotation);
+        for order in [lanes.clone(), lanes.iter().rev().cloned().collect()] {
+            let route = generate_shortest_lane_route(&order, start, end, &no_constraint).unwrap();
+            let names: Vec<&str> = route.iter().map(|l| l.name.as_str()).collect();
+            assert_eq!(names, ["a1", "a2"]);
+        }
+    }
+}
+
+#[test]
+fn a_custom_cost_picks_a_different_path_than_distance() {
+    let lanes = create_lanes();
+    let (from, to) = (IntersectionId(1, 1), IntersectionId(2, 2));
+    let no_constraint = HashSet::new();
+    let shortest = generate_shortest_lane_route(&lanes, from, to, &no_constraint).unwrap();
+    let by_length =
+        generate_lane_route_by(&lanes, from, to, &no_constraint, |l| l.length_meters).unwrap();
+    assert_eq!(shortest, by_length);
+
+    // Treat the diagonal shortcut as jammed: it's dearer than any way around the block.
+    let jammed = &shortest[0].name;
+    let congestion_aware = generate_lane_route_by(&lanes, from, to, &no_constraint, |l| {
+        if &l.name == jammed {
+            10_000.0
+        } else {
+            l.length_meters
+        }
+    })
+    .unwrap();
+    assert!(congestion_aware.iter().all(|l| &l.name != jammed));
+    assert_eq!(congestion_aware.first().unwrap().from, from);
+    assert_eq!(congestion_aware.last().unwrap().to, to);
+
+    // A lane with an infinite cost is never used.
+    let closed = generate_lane_route_by(&lanes, from, to, &no_constraint, |l| {
+        if &l.name == jammed {
+            f64::INFINITY
+        } else {
+            1.0
+        }
+    })
+    .unwrap();
+    assert!(closed.iter().all(|l| &l.name != jammed));
+}
+
+#[test]
+fn turn_weight_picks_the_straightest_route_and_distance_weight_the_shortest() {
+    let id = IntersectionId;
+    let lane = |from: IntersectionId, to: IntersectionId, length| {
+        Lane::new(format!("{}->{}", from, to), from, to, length).unwrap()
+    };
+    // Straight along row 0 (1000 m, no turns) or a zig-zag through row 1 (400 m, two turns).
+    let lanes = vec![
+        lane(id(0, 0), id(0, 1), 500.0),
+        lane(id(0, 1), id(0, 2), 500.0),
+        lane(id(0, 0), id(1, 0), 100.0),
+        lane(id(1, 0), id(1, 1), 100.0),
+        lane(id(1, 1), id(1, 2), 100.0),
+        lane(id(1, 2), id(0, 2), 100.0),
+    ];
+    let route = |objective: RouteObjective| {
+        generate_route_with_objective(&lanes, id(0, 0), id(0, 2), &HashSet::new(), &objective)
+            .unwrap()
+    };
+    let total = |route: &[Lane]| route.iter().map(|lane| lane.length_meters).sum::<f64>();
+
+    let straightest = route(RouteObjective {
+        distance_weight: 0.0,
+        time_weight: 0.0,
+        turn_weight: 1.0,
+    });
+    assert_eq!(straightest.len(), 2);
+    assert!(straightest
+        .iter()
+        .all(|lane| lane.from.0 == 0 && lane.to.0 == 0));
+
+    let shortest = route(RouteObjective {
+        distance_weight: 1.0,
+        time_weight: 0.0,
+        turn_weight: 0.0,
+    });
+    assert_eq!(shortest.len(), 4);
+    assert_eq!(total(&shortest), 400.0);
+    assert!(total(&shortest) < total(&straightest));
+}
+
+#[test]
+fn a_forbidden_turn_is_routed_around() {
+    let lanes = create_lanes();
+    let no_constraint = HashSet::new();
+    let (from, to) = (IntersectionId(0, 0), IntersectionId(2, 2));
+    let shortest = generate_shortest_lane_route(&lanes, from, to, &no_constraint).unwrap();
+    assert!(shortest.len() >= 2);
+
+    // Ban the first turn the shortest route makes.
+    let (arrive, via, leave) = (shortest[0].from, shortest[0].to, shortest[1].to);
+    let intersections: Vec<Intersection> = create_intersections()
+        .into_iter()
+        .map(|i| {
+            if i.id == via {
+                i.with_forbidden_turn(arrive, leave)
+            } else {
+                i
+            }
+        })
+        .collect();
+    let restrictions = turn_restrictions(&intersections);
+    assert_eq!(restrictions, HashSet::from([(arrive, via, leave)]));
+
+    let route =
+        generate_restricted_lane_route_by(&lanes, from, to, &no_constraint, &restrictions, |l| {
+            l.length_meters
+        })
+        .expect("another way round");
+    assert_eq!(route.first().unwrap().from, from);
+    assert_eq!(route.last().unwrap().to, to);
+    for pair in route.windows(2) {
+        assert_eq!(pair[0].to, pair[1].from);
+        assert!(!restrictions.contains(&(pair[0].from, pair[0].to, pair[1].to)));
+    }
+    assert_ne!(route, shortest);
+}
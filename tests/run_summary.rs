@@ -0,0 +1,43 @@
+// End-of-run statistics over the vehicles that finished their journey.
+use rts_assignment::c1_tp063879::simulation::{summarize_run, RunSummary};
+use rts_assignment::shared_data::VehicleData;
+
+fn finished(id: u64, waiting_time: u64, crashed: bool) -> VehicleData {
+    VehicleData {
+        id,
+        waiting_time,
+        accident_timestamp: crashed.then_some(1_000 + id),
+        severity: if crashed { 3 } else { 0 },
+        current_lane: "(0,0) -> (0,1)".to_string(),
+        collided_with: None,
+        fuel_used: 0.0,
+    }
+}
+
+#[test]
+fn summary_statistics_of_a_small_run() {
+    let events = [
+        finished(1, 4, false),
+        finished(2, 0, false),
+        finished(3, 10, true),
+        finished(4, 2, false),
+        finished(5, 6, true),
+    ];
+    let summary = summarize_run(&events);
+    assert_eq!(summary.total_vehicles, 5);
+    assert_eq!(summary.arrivals, 3);
+    assert_eq!(summary.crashes, 2);
+    // Waits sorted: 0, 2, 4, 6, 10.
+    assert_eq!(summary.mean_waiting_time, 4.4);
+    assert_eq!(summary.median_waiting_time, 4.0);
+    assert_eq!(summary.p95_waiting_time, 10.0);
+    assert_eq!(summary.accident_rate, 0.4);
+
+    // An even count takes the mean of the middle two.
+    assert_eq!(summarize_run(&events[..4]).median_waiting_time, 3.0);
+}
+
+#[test]
+fn an_empty_run_summarizes_to_zeros() {
+    assert_eq!(summarize_run(&[]), RunSummary::default());
+}
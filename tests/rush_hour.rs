@@ -0,0 +1,29 @@
+// The rush-hour curve shared by the runners ramps from off-peak to peak and back each cycle.
+use rts_assignment::c1_tp063879::config::RushHourConfig;
+
+#[test]
+fn spawn_count_ramps_two_to_five_to_two_over_a_cycle() {
+    let rush_hour = RushHourConfig::default();
+    let counts: Vec<usize> = (0..=40).map(|t| rush_hour.spawn_count(t)).collect();
+
+    assert_eq!(counts[0], 2);
+    assert_eq!(counts[20], 5);
+    assert_eq!(counts[40], 2);
+    assert_eq!(*counts.iter().max().unwrap(), 5);
+    assert_eq!(*counts.iter().min().unwrap(), 2);
+    // Rising through the first half, falling through the second.
+    assert!(counts[..=20].windows(2).all(|w| w[0] <= w[1]));
+    assert!(counts[20..].windows(2).all(|w| w[0] >= w[1]));
+
+    // The next cycle repeats it.
+    let next: Vec<usize> = (40..=80).map(|t| rush_hour.spawn_count(t)).collect();
+    assert_eq!(next, counts);
+}
+
+#[test]
+fn the_cycle_and_range_are_configurable() {
+    let rush_hour = RushHourConfig::new(10, 1, 9);
+    assert_eq!(rush_hour.spawn_count(0), 1);
+    assert_eq!(rush_hour.spawn_count(5), 9);
+    assert_eq!(rush_hour.spawn_count(10), 1);
+}
@@ -0,0 +1,45 @@
+// Two simulations seeded alike spawn exactly the same vehicles on the same routes.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::simulation::spawn_vehicle_from;
+use rts_assignment::c1_tp063879::vehicles::Vehicle;
+
+const SPAWNS: usize = 25;
+
+fn spawn_sequence(seed: u64) -> Vec<(Vehicle, Vec<String>)> {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let config = SimulationConfig::default();
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut next_vehicle_id = 1;
+    (0..SPAWNS)
+        .filter_map(|_| {
+            spawn_vehicle_from(
+                &intersections,
+                &lanes,
+                &mut next_vehicle_id,
+                &mut rng,
+                &config,
+                None,
+                12,
+            )
+        })
+        .map(|(vehicle, route)| (vehicle, route.into_iter().map(|l| l.name).collect()))
+        .collect()
+}
+
+#[test]
+fn the_same_seed_spawns_the_same_vehicles() {
+    let first = spawn_sequence(42);
+    let second = spawn_sequence(42);
+    assert!(first.len() > SPAWNS / 2, "only {} spawns", first.len());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn a_different_seed_spawns_different_vehicles() {
+    assert_ne!(spawn_sequence(42), spawn_sequence(43));
+}
@@ -0,0 +1,90 @@
+// The controller behind an RwLock serves concurrent readers while a writer advances the lights.
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::sync::{mpsc, Arc, Barrier, RwLock};
+use std::thread;
+use std::time::Duration;
+
+const READERS: usize = 4;
+const READS_PER_READER: usize = 2_000;
+const WRITES: usize = 20;
+
+fn controller() -> TrafficLightController {
+    TrafficLightController::initialize(create_intersections(), &create_lanes())
+}
+
+#[test]
+fn readers_share_the_lock_and_see_every_write() {
+    let shared = Arc::new(RwLock::new(controller()));
+    let queries: Arc<Vec<_>> = Arc::new(
+        create_lanes()
+            .into_iter()
+            .map(|l| (l.from, l.name))
+            .collect(),
+    );
+    // Every reader waits at the barrier while holding its read guard, which only completes if
+    // the readers hold the lock at the same time.
+    let inside = Arc::new(Barrier::new(READERS));
+    let (done_tx, done_rx) = mpsc::channel();
+
+    let mut threads = Vec::new();
+    for _ in 0..READERS {
+        let (shared, queries, inside) = (
+            Arc::clone(&shared),
+            Arc::clone(&queries),
+            Arc::clone(&inside),
+        );
+        threads.push(thread::spawn(move || {
+            {
+                let _guard = shared.read().unwrap();
+                inside.wait();
+            }
+            for i in 0..READS_PER_READER {
+                let guard = shared.read().unwrap();
+                let (id, name) = &queries[i % queries.len()];
+                guard.is_lane_green(*id, name);
+                // A reader never sees a light between phases.
+                for ctrl in guard.controllers.values() {
+                    assert!(ctrl.current_phase_index < ctrl.phases.len());
+                }
+            }
+        }));
+    }
+    let writer = {
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            for _ in 0..WRITES {
+                shared.write().unwrap().update_all();
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+    thread::spawn(move || {
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        writer.join().unwrap();
+        let _ = done_tx.send(());
+    });
+    done_rx
+        .recv_timeout(Duration::from_secs(30))
+        .expect("readers and writer finished");
+
+    // No write was lost: the lights match a controller advanced the same number of times.
+    let mut expected = controller();
+    for _ in 0..WRITES {
+        expected.update_all();
+    }
+    let shared = shared.read().unwrap();
+    for (id, ctrl) in &expected.controllers {
+        assert_eq!(
+            shared.controllers[id].current_phase_index,
+            ctrl.current_phase_index
+        );
+        assert_eq!(
+            shared.controllers[id].elapsed_in_phase,
+            ctrl.elapsed_in_phase
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// A saved simulation snapshot loads back equal and restores the light timings.
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::snapshot::{ActiveVehicle, SimulationState};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::collections::HashMap;
+
+#[test]
+fn a_saved_snapshot_loads_back_equal() {
+    let intersections = create_intersections();
+    let mut lanes = create_lanes();
+    let mut controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+    for _ in 0..11 {
+        controller.update_all();
+    }
+
+    // One vehicle part-way through its journey, queued on its current lane.
+    let (first, second) = (lanes[0].clone(), lanes[1].clone());
+    let mut vehicle = Vehicle::new(7, VehicleType::Bus, first.from, second.to, 45.5);
+    vehicle.current_intersection = first.from;
+    vehicle.record_wait(3);
+    assert!(lanes[0].add_vehicle(&vehicle));
+    let journeys = HashMap::from([(
+        vehicle.id,
+        ActiveVehicle {
+            vehicle,
+            remaining_route: vec![first.name.clone(), second.name.clone()],
+        },
+    )]);
+
+    let state = SimulationState::capture(&lanes, &intersections, &controller, 8, &journeys);
+    let path = std::env::temp_dir().join(format!("rts_snapshot_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    state.save_snapshot(path).unwrap();
+    let loaded = SimulationState::load_snapshot(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, state);
+    assert_eq!(loaded.active_vehicles.len(), 1);
+    assert_eq!(loaded.lanes[0].vehicle_queue.len(), 1);
+
+    // A fresh controller picks up where the snapshot's lights were.
+    let mut restored = TrafficLightController::initialize(create_intersections(), &create_lanes());
+    loaded.restore_controller(&mut restored);
+    for (id, ctrl) in &controller.controllers {
+        assert_eq!(
+            restored.controllers[id].current_phase_index,
+            ctrl.current_phase_index
+        );
+        assert_eq!(
+            restored.controllers[id].elapsed_in_phase,
+            ctrl.elapsed_in_phase
+        );
+    }
+}
@@ -0,0 +1,125 @@
+// Spawning backs off on a congested grid, and ramp meters cap what each entry releases.
+use rts_assignment::c1_tp063879::config::{RampMeterConfig, RushHourConfig, SpawnMeteringConfig};
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::simulation::{mean_congestion, RampMeters, SpawnMeter};
+use rts_assignment::shared_data::TrafficData;
+use std::collections::{HashMap, HashSet};
+
+// Every intersection at `congestion`.
+fn traffic_data(congestion: f64) -> TrafficData {
+    TrafficData {
+        lane_occupancy: HashMap::new(),
+        accident_lanes: HashSet::new(),
+        intersection_congestion: ["(0, 0)", "(0, 1)", "(1, 1)"]
+            .iter()
+            .map(|i| (i.to_string(), congestion))
+            .collect(),
+        intersection_waiting_time: HashMap::new(),
+        vehicle_data: Vec::new(),
+        intersection_max_direction_congestion: HashMap::new(),
+        lane_waiting_time: HashMap::new(),
+        lane_speeds: HashMap::new(),
+    }
+}
+
+fn metering() -> SpawnMeteringConfig {
+    SpawnMeteringConfig {
+        enabled: true,
+        ..SpawnMeteringConfig::default()
+    }
+}
+
+#[test]
+fn high_congestion_spawns_fewer_than_the_rush_hour_count() {
+    // The peak of the default rush-hour curve.
+    let scheduled = RushHourConfig::default().spawn_count(20);
+    let jammed = traffic_data(0.9);
+    assert_eq!(mean_congestion(&jammed), 0.9);
+
+    let mut meter = SpawnMeter::new(metering());
+    assert_eq!(meter.effective_count(scheduled), scheduled);
+    meter.observe(mean_congestion(&jammed));
+    assert!(meter.effective_count(scheduled) < scheduled);
+}
+
+#[test]
+fn spawning_recovers_as_congestion_drains_but_never_exceeds_the_schedule() {
+    let scheduled = RushHourConfig::default().spawn_count(20);
+    let mut meter = SpawnMeter::new(metering());
+    for _ in 0..3 {
+        meter.observe(mean_congestion(&traffic_data(0.9)));
+    }
+    let throttled = meter.effective_count(scheduled);
+
+    // Between the water marks the scale holds.
+    meter.observe(mean_congestion(&traffic_data(0.6)));
+    assert_eq!(meter.effective_count(scheduled), throttled);
+
+    for _ in 0..20 {
+        meter.observe(mean_congestion(&traffic_data(0.1)));
+    }
+    assert_eq!(meter.scale(), 1.0);
+    assert_eq!(meter.effective_count(scheduled), scheduled);
+}
+
+#[test]
+fn a_disabled_meter_passes_the_schedule_through() {
+    let mut meter = SpawnMeter::new(SpawnMeteringConfig::default());
+    meter.observe(mean_congestion(&traffic_data(1.0)));
+    assert_eq!(meter.effective_count(5), 5);
+}
+
+#[test]
+fn an_entry_releases_no_more_than_its_quota_per_minute() {
+    let mut meters = RampMeters::new(HashMap::from([(
+        "Intersection 00".to_string(),
+        RampMeterConfig {
+            max_vehicles: 4,
+            window_secs: 60,
+        },
+    )]));
+
+    // Three vehicles try to enter every second for a minute.
+    let mut released = 0;
+    for second in 0..60 {
+        for _ in 0..3 {
+            if meters.try_release("Intersection 00", second) {
+                released += 1;
+            }
+        }
+    }
+    assert_eq!(released, 4);
+    assert!(!meters.has_quota("Intersection 00", 59));
+
+    // The next window starts with a fresh quota; unmetered entries are never held back.
+    assert!(meters.has_quota("Intersection 00", 60));
+    assert!(meters.try_release("Intersection 00", 60));
+    assert!((0..100).all(|_| meters.try_release("Intersection 10", 0)));
+}
+
+#[test]
+fn no_more_than_a_quota_of_vehicles_waits_at_a_ramp() {
+    let mut meters = RampMeters::new(HashMap::from([(
+        "Intersection 00".to_string(),
+        RampMeterConfig {
+            max_vehicles: 2,
+            window_secs: 60,
+        },
+    )]));
+    let entry = IntersectionId(0, 0);
+
+    // Five vehicles arrive at a ramp whose quota is used up: two wait, three are turned away.
+    let held: Vec<bool> = (0..5)
+        .map(|_| meters.hold_back(entry, "Intersection 00"))
+        .collect();
+    assert_eq!(held, [true, true, false, false, false]);
+    assert_eq!(meters.dropped(), 3);
+    assert_eq!(meters.take_held_back(), [entry, entry]);
+
+    // Retrying empties the queue, so the next tick can hold a full quota again.
+    assert!(meters.take_held_back().is_empty());
+    assert!(meters.hold_back(entry, "Intersection 00"));
+    assert!(meters.hold_back(entry, "Intersection 00"));
+    assert!(!meters.hold_back(entry, "Intersection 00"));
+    assert_eq!(meters.dropped(), 4);
+}
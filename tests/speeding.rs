@@ -0,0 +1,134 @@
+// Vehicles over a lane's speed limit are recorded on the lane, and the analyzer flags lanes full
+// of speeders as hotspots.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionControl};
+use rts_assignment::c1_tp063879::lanes::{
+    create_lanes, LaneStore, OccupancyMetric, DEFAULT_LANE_SPEED_LIMIT_KMH,
+};
+use rts_assignment::c1_tp063879::simulation::{
+    collect_traffic_data_from_store, simulate_vehicle_journey, JourneyContext,
+};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c2_tp063881::traffic_analyzer::{
+    analyze_speeding, AnalyzerThresholds, SPEEDING_MIN_SAMPLES,
+};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+
+const TIME_SCALE: f64 = 50.0;
+const SPEED_LIMIT: f64 = 30.0;
+
+// Drives one car per speed along a lane limited to SPEED_LIMIT. Returns that lane's name and the
+// hotspot alerts for the resulting traffic data as (lane, overspeed ratio).
+async fn hotspots_after(speeds: &[f64]) -> (String, Vec<(String, f64)>) {
+    let cars: Vec<(VehicleType, f64)> = speeds.iter().map(|&s| (VehicleType::Car, s)).collect();
+    let (lane, store) = drive(&cars, Some(SPEED_LIMIT)).await;
+    let data = collect_traffic_data_from_store(
+        &store,
+        &create_intersections(),
+        Vec::new(),
+        OccupancyMetric::Length,
+    );
+    let alerts = analyze_speeding(&data, &AnalyzerThresholds::default())
+        .into_iter()
+        .map(|alert| (alert.lane, alert.overspeed_ratio))
+        .collect();
+    (lane, alerts)
+}
+
+// Drives one vehicle per (type, speed) along a built-in lane, with its limit replaced by
+// `speed_limit` if given. Returns that lane's name and the lanes afterwards.
+async fn drive(
+    vehicles: &[(VehicleType, f64)],
+    speed_limit: Option<f64>,
+) -> (String, Arc<LaneStore>) {
+    let intersections = create_intersections();
+    let mut lanes = create_lanes();
+    // Start at an intersection without lights so no car is held up.
+    let index = lanes
+        .iter()
+        .position(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control != IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving an uncontrolled intersection");
+    if let Some(speed_limit) = speed_limit {
+        lanes[index] = lanes[index].clone().with_speed_limit(speed_limit);
+    }
+    let lane = lanes[index].clone();
+    let controller = Arc::new(RwLock::new(TrafficLightController::initialize(
+        intersections.clone(),
+        &lanes,
+    )));
+    let store = Arc::new(LaneStore::new(lanes));
+    let shared_intersections = Arc::new(RwLock::new(intersections.clone()));
+    let config = Arc::new(SimulationConfig {
+        crash_probability: 0.0,
+        overspeed_crash_probability: 0.0,
+        collision_probability: 0.0,
+        ..SimulationConfig::default()
+    });
+
+    for (id, &(vehicle_type, speed)) in vehicles.iter().enumerate() {
+        let id = id as u64;
+        let ctx = JourneyContext {
+            active_ids: Arc::new(Mutex::new(HashSet::from([id]))),
+            time_scale: TIME_SCALE,
+            ..JourneyContext::new(
+                Arc::clone(&shared_intersections),
+                Arc::clone(&store),
+                Arc::clone(&controller),
+                Arc::clone(&config),
+            )
+        };
+        simulate_vehicle_journey(
+            Vehicle::new(id, vehicle_type, lane.from, lane.to, speed),
+            vec![lane.clone()],
+            SmallRng::seed_from_u64(id),
+            ctx,
+        )
+        .await;
+    }
+    (lane.name, store)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_lane_full_of_speeders_raises_a_hotspot_alert() {
+    let speeds = vec![SPEED_LIMIT * 2.0; SPEEDING_MIN_SAMPLES];
+    let (lane, alerts) = hotspots_after(&speeds).await;
+    assert_eq!(alerts, [(lane, 1.0)]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn vehicles_within_the_limit_raise_no_alert() {
+    let speeds = vec![SPEED_LIMIT; SPEEDING_MIN_SAMPLES];
+    let (_, alerts) = hotspots_after(&speeds).await;
+    assert!(alerts.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn built_in_lanes_judge_speed_against_their_posted_limit() {
+    // 132 km/h is below the car spawn-range threshold of 134 but over the posted limit, and
+    // emergency vans are exempt from the limit.
+    assert!(create_lanes()
+        .iter()
+        .all(|lane| lane.speed_limit == Some(DEFAULT_LANE_SPEED_LIMIT_KMH)));
+    let vehicles = [
+        (VehicleType::Car, 132.0),
+        (VehicleType::Car, 120.0),
+        (VehicleType::EmergencyVan, 170.0),
+    ];
+    let (lane, store) = drive(&vehicles, None).await;
+    let overspeeding: Vec<bool> = store
+        .lock_lane(&lane)
+        .unwrap()
+        .recent_speeds
+        .iter()
+        .map(|&(_, overspeeding)| overspeeding)
+        .collect();
+    assert_eq!(overspeeding, [true, false, false]);
+}
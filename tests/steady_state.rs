@@ -0,0 +1,33 @@
+// The steady-state detector fires once network metrics settle, and not while they still climb.
+use rts_assignment::c1_tp063879::simulation::SteadyStateDetector;
+
+#[test]
+fn stabilizing_metrics_reach_steady_state_once() {
+    let mut detector = SteadyStateDetector::new(5, 0.05, 0.10);
+    // A warm-up ramp, then congestion and vehicle counts level off.
+    let ramp = [(0.05, 2), (0.15, 6), (0.25, 10), (0.35, 14)];
+    let plateau = [(0.40, 20), (0.41, 21), (0.40, 20), (0.42, 21), (0.41, 20)];
+
+    for &(congestion, vehicles) in &ramp {
+        assert!(!detector.observe(congestion, vehicles));
+    }
+    let fired: Vec<bool> = plateau
+        .iter()
+        .map(|&(congestion, vehicles)| detector.observe(congestion, vehicles))
+        .collect();
+    assert_eq!(fired, [false, false, false, false, true]);
+    assert!(detector.is_reached());
+
+    // Later ticks don't fire the event again.
+    assert!(!detector.observe(0.41, 20));
+}
+
+#[test]
+fn rising_metrics_never_reach_steady_state() {
+    let mut detector = SteadyStateDetector::new(5, 0.05, 0.10);
+    for tick in 0..30 {
+        let congestion = (tick as f64 * 0.03).min(1.0);
+        assert!(!detector.observe(congestion, 2 * tick));
+    }
+    assert!(!detector.is_reached());
+}
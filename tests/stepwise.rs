@@ -0,0 +1,55 @@
+// The stepwise runner moves vehicles deterministically, one lane per step.
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, entry_only_intersections, IntersectionControl,
+};
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c1_tp063879::route_generation::generate_shortest_lane_route;
+use rts_assignment::c1_tp063879::stepwise::{run_simulation_stepwise, StepwiseState};
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+
+#[test]
+fn a_single_vehicle_advances_one_lane_per_step() {
+    // Without traffic lights nothing but the route decides where the vehicle is.
+    let mut intersections = create_intersections();
+    for intersection in &mut intersections {
+        intersection.control = IntersectionControl::Normal;
+    }
+    let lanes = create_lanes();
+    let entry = intersections.iter().find(|i| i.is_entry).unwrap().id;
+    let exit = intersections
+        .iter()
+        .rev()
+        .find(|i| i.is_exit && i.id != entry)
+        .unwrap()
+        .id;
+    let route = generate_shortest_lane_route(
+        &lanes,
+        entry,
+        exit,
+        &entry_only_intersections(&intersections),
+    )
+    .expect("a route from entry to exit");
+    assert!(route.len() > 1, "want a route of several lanes");
+
+    let mut state = StepwiseState::new(intersections, lanes);
+    assert!(state.add_vehicle(
+        Vehicle::new(1, VehicleType::Car, entry, exit, 50.0),
+        route.clone()
+    ));
+    assert_eq!(state.vehicle_lane(1), None);
+
+    for lane in &route {
+        run_simulation_stepwise(&mut state, 1);
+        assert_eq!(state.vehicle_lane(1), Some(lane.name.as_str()));
+        let on_lane = state.lanes.iter().find(|l| l.name == lane.name).unwrap();
+        assert!(on_lane.contains_vehicle(1));
+    }
+
+    run_simulation_stepwise(&mut state, 1);
+    assert_eq!(state.vehicle_lane(1), None);
+    assert!(state.vehicles.is_empty());
+    assert_eq!(state.finished.len(), 1);
+    assert_eq!(state.finished[0].id, 1);
+    assert_eq!(state.finished[0].waiting_time, 0);
+    assert!(state.lanes.iter().all(|l| !l.contains_vehicle(1)));
+}
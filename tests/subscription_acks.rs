@@ -0,0 +1,67 @@
+// A RabbitMQ subscription holds at most SUBSCRIPTION_PREFETCH messages unacknowledged, and a
+// message taken but never acknowledged goes back to the queue.
+// Ignored by default; run it with `cargo test -- --ignored` and AMQP_URL pointing at a
+// reachable RabbitMQ instance.
+use amiquip::{QueueDeclareOptions, QueueDeleteOptions};
+use rts_assignment::amqp::{connect_with_retry, CONNECT_BASE_DELAY, CONNECT_MAX_ATTEMPTS};
+use rts_assignment::bus::{AmiquipBus, MessageBus, SUBSCRIPTION_PREFETCH};
+use rts_assignment::global_variables::amqp_url;
+use rts_assignment::shared_data::current_timestamp;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// Polls the depth of `queue` until it reads `expected` or a few seconds have passed.
+async fn wait_for_depth(bus: &AmiquipBus, queue: &str, expected: u32) -> Option<u32> {
+    let mut depth = None;
+    for _ in 0..40 {
+        depth = bus.queue_depth(queue).unwrap();
+        if depth == Some(expected) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    depth
+}
+
+#[ignore = "needs a RabbitMQ broker at AMQP_URL"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unacknowledged_messages_stay_with_the_broker() {
+    let bus = AmiquipBus::connect(&amqp_url()).expect("broker at AMQP_URL");
+    let queue = format!("acks_{}_{}", std::process::id(), current_timestamp());
+    let prefetch = u32::from(SUBSCRIPTION_PREFETCH);
+    for i in 0..prefetch + 2 {
+        bus.publish(&queue, format!("message {}", i).as_bytes())
+            .unwrap();
+    }
+    let (_shutdown_tx, shutdown) = watch::channel(false);
+
+    // Nothing is acknowledged, so only a prefetch worth leaves the queue.
+    let subscription = bus.subscribe(&queue).unwrap();
+    let first = subscription.next_until(&shutdown).unwrap();
+    assert_eq!(wait_for_depth(&bus, &queue, 2).await, Some(2));
+
+    // Acknowledging the first lets one more through.
+    subscription.ack();
+    assert_eq!(wait_for_depth(&bus, &queue, 1).await, Some(1));
+
+    // The rest were never acknowledged and are redelivered to the next subscriber, the first
+    // one excepted.
+    drop(subscription);
+    assert_eq!(
+        wait_for_depth(&bus, &queue, prefetch + 1).await,
+        Some(prefetch + 1)
+    );
+    let subscription = bus.subscribe(&queue).unwrap();
+    let redelivered = subscription.next_until(&shutdown).unwrap();
+    assert_ne!(redelivered, first);
+    drop(subscription);
+
+    let mut connection =
+        connect_with_retry(&amqp_url(), CONNECT_MAX_ATTEMPTS, CONNECT_BASE_DELAY).unwrap();
+    let channel = connection.open_channel(None).unwrap();
+    if let Ok(declared) = channel.queue_declare(queue, QueueDeclareOptions::default()) {
+        let _ = declared.delete(QueueDeleteOptions::default());
+    }
+    let _ = connection.close();
+    let _ = bus.close();
+}
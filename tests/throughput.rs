@@ -0,0 +1,58 @@
+// Network throughput counts arrivals per simulated minute and is carried into the traffic events.
+use rts_assignment::c1_tp063879::simulation::throughput_per_min;
+use rts_assignment::c2_tp063881::traffic_analyzer::{analyze_traffic_events, AnalyzerThresholds};
+use rts_assignment::shared_data::{TrafficData, TrafficUpdate, VehicleData};
+use std::collections::{HashMap, HashSet};
+
+fn event(id: u64, accident_timestamp: Option<u64>) -> VehicleData {
+    VehicleData {
+        id,
+        waiting_time: 0,
+        accident_timestamp,
+        severity: 0,
+        current_lane: "(0, 0) -> (0, 1)".to_string(),
+        collided_with: None,
+        fuel_used: 0.0,
+    }
+}
+
+// Six arrivals and two crashes.
+fn batch() -> Vec<VehicleData> {
+    let mut events: Vec<VehicleData> = (1..=6).map(|id| event(id, None)).collect();
+    events.push(event(7, Some(1_000)));
+    events.push(event(8, Some(1_001)));
+    events
+}
+
+#[test]
+fn throughput_counts_arrivals_per_minute_over_the_interval() {
+    let events = batch();
+    assert_eq!(throughput_per_min(&events, 30.0), 12.0);
+    assert_eq!(throughput_per_min(&events, 60.0), 6.0);
+    assert_eq!(throughput_per_min(&events, 120.0), 3.0);
+    assert_eq!(throughput_per_min(&[], 30.0), 0.0);
+    assert_eq!(throughput_per_min(&events, 0.0), 0.0);
+}
+
+#[test]
+fn the_analyzer_carries_throughput_into_its_event() {
+    let events = batch();
+    let update = TrafficUpdate {
+        current_data: TrafficData {
+            lane_occupancy: HashMap::new(),
+            accident_lanes: HashSet::new(),
+            intersection_congestion: HashMap::new(),
+            intersection_waiting_time: HashMap::new(),
+            vehicle_data: events.clone(),
+            intersection_max_direction_congestion: HashMap::new(),
+            lane_waiting_time: HashMap::new(),
+            lane_speeds: HashMap::new(),
+        },
+        timestamp: 1_000,
+        run_id: String::new(),
+        throughput_per_min: throughput_per_min(&events, 30.0),
+    };
+    let event = analyze_traffic_events(update, &AnalyzerThresholds::default(), None);
+    assert_eq!(event.throughput_per_min, 12.0);
+    assert_eq!(event.total_accidents, 2);
+}
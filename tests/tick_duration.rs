@@ -0,0 +1,57 @@
+// Halving the tick doubles the spawn iterations in a simulated window, while demand profiles
+// still deliver their vehicles per minute regardless of the tick.
+use rts_assignment::c1_tp063879::config::{
+    DemandProfiles, DemandRecord, RushHourConfig, SimulationConfig,
+};
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::simulation::scheduled_spawns;
+use std::collections::HashMap;
+
+const WINDOW_MILLIS: u64 = 60_000;
+
+// Runs the main loop's spawn schedule over one simulated minute and returns the number of
+// iterations and the vehicles scheduled across them.
+fn spawns_in_window(config: &SimulationConfig, profiles: Option<&DemandProfiles>) -> (u64, usize) {
+    let intersections = create_intersections();
+    let mut carry = HashMap::new();
+    let iterations = WINDOW_MILLIS / config.tick_millis;
+    let mut spawned = 0;
+    for tick in 0..iterations {
+        // Elapsed time has whole-second resolution, as in run_simulation_for.
+        let elapsed = tick * config.tick_millis / 1000;
+        spawned += scheduled_spawns(&intersections, profiles, &mut carry, config, elapsed).len();
+    }
+    (iterations, spawned)
+}
+
+fn config_with_tick(tick_millis: u64) -> SimulationConfig {
+    SimulationConfig {
+        rush_hour: RushHourConfig::new(40, 2, 2),
+        tick_millis,
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn halving_the_tick_doubles_the_rush_hour_spawn_iterations() {
+    let (full_iterations, full_spawns) = spawns_in_window(&config_with_tick(1000), None);
+    let (half_iterations, half_spawns) = spawns_in_window(&config_with_tick(500), None);
+    assert_eq!(full_iterations, 60);
+    assert_eq!(half_iterations, 2 * full_iterations);
+    // The rush-hour curve gives a count per iteration, so twice the iterations spawn twice as many.
+    assert_eq!(full_spawns, 120);
+    assert_eq!(half_spawns, 2 * full_spawns);
+}
+
+#[test]
+fn demand_profiles_spawn_their_rate_per_minute_at_any_tick() {
+    let profiles = DemandProfiles::from_records(vec![DemandRecord {
+        entry_id: "Intersection 00".to_string(),
+        minute: 0,
+        vehicles_per_minute: 30.0,
+    }]);
+    for tick_millis in [1000, 500, 250, 200] {
+        let (_, spawned) = spawns_in_window(&config_with_tick(tick_millis), Some(&profiles));
+        assert_eq!(spawned, 30, "{}ms ticks", tick_millis);
+    }
+}
@@ -0,0 +1,47 @@
+// Exporting the controller's timing plan covers every signalled intersection and its phases.
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c1_tp063879::lanes::create_lanes;
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    IntersectionPlan, TrafficLightController,
+};
+use std::collections::HashSet;
+use std::fs;
+
+#[test]
+fn exported_plan_matches_the_default_controller() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    let controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+
+    let path = std::env::temp_dir().join(format!("rts_plan_{}.json", std::process::id()));
+    controller.export_plan(path.to_str().unwrap()).unwrap();
+    let plan: Vec<IntersectionPlan> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // Every signalled intersection with outgoing lanes, in id order.
+    let mut expected: Vec<IntersectionId> = intersections
+        .iter()
+        .filter(|i| i.control == IntersectionControl::TrafficLight)
+        .filter(|i| lanes.iter().any(|l| l.from == i.id))
+        .map(|i| i.id)
+        .collect();
+    expected.sort_by_key(|id| (id.0, id.1));
+    let exported: Vec<IntersectionId> = plan.iter().map(|p| p.intersection_id).collect();
+    assert!(!exported.is_empty());
+    assert_eq!(exported, expected);
+
+    // One phase per axis the intersection's outgoing lanes run along, all at the start.
+    for entry in &plan {
+        let axes: HashSet<_> = lanes
+            .iter()
+            .filter(|l| l.from == entry.intersection_id)
+            .map(|l| l.axis())
+            .collect();
+        assert_eq!(entry.phases.len(), axes.len(), "{}", entry.intersection_id);
+        assert_eq!(entry.current_phase_index, 0);
+        assert_eq!(entry.offset_secs, 0);
+    }
+}
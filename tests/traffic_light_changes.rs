@@ -0,0 +1,58 @@
+// Signal switches are reported on the controller's light_changes channel.
+use rts_assignment::c1_tp063879::intersections::{
+    create_intersections, IntersectionControl, IntersectionId,
+};
+use rts_assignment::c3_tp063987::traffic_light_controller::{
+    IntersectionController, TrafficLightPhase,
+};
+use rts_assignment::shared_data::TrafficLightChanged;
+use std::sync::mpsc;
+
+fn phase(lane: &str, duration: u64) -> TrafficLightPhase {
+    TrafficLightPhase {
+        green_lanes: vec![lane.to_string()],
+        duration,
+        yellow_secs: 0,
+    }
+}
+
+#[test]
+fn a_phase_advance_produces_exactly_one_change_event() {
+    let intersection = create_intersections()
+        .into_iter()
+        .find(|i| i.control == IntersectionControl::TrafficLight)
+        .expect("an intersection with traffic lights");
+    let id = intersection.id;
+    let (sender, receiver) = mpsc::channel();
+    let mut controller = IntersectionController::new(
+        intersection,
+        vec![phase("a", 2), phase("b", 2)],
+        vec!["a".to_string(), "b".to_string()],
+    )
+    .with_light_changes(sender);
+
+    // Half-way through the first phase nothing has switched yet.
+    controller.update();
+    assert!(receiver.try_recv().is_err());
+
+    controller.update();
+    assert_eq!(controller.current_phase_index, 1);
+    let changes: Vec<TrafficLightChanged> = receiver.try_iter().collect();
+    assert_eq!(
+        changes,
+        vec![TrafficLightChanged {
+            intersection_id: u32::from(id),
+            is_green: true,
+        }]
+    );
+}
+
+#[test]
+fn intersection_ids_map_to_row_and_column_bytes() {
+    assert_eq!(u32::from(IntersectionId(1, 2)), 0x0102);
+    assert_eq!(u32::from(IntersectionId(0, 0)), 0);
+    assert_ne!(
+        u32::from(IntersectionId(-1, 0)),
+        u32::from(IntersectionId(0, -1))
+    );
+}
@@ -0,0 +1,178 @@
+// Vehicle types are drawn in the proportions of the configured weights, which may vary with the
+// hour of the simulated day.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::bus::InMemoryBus;
+use rts_assignment::c1_tp063879::config::{
+    hour_of_day, RushHourConfig, SimulationConfig, VehicleMixPeriod,
+};
+use rts_assignment::c1_tp063879::event_log::SimulationEvent;
+use rts_assignment::c1_tp063879::intersections::create_intersections;
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{run_simulation_on, RunContext};
+use rts_assignment::c1_tp063879::vehicles::{random_vehicle_type, VehicleType, VehicleWeights};
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+
+const DRAWS: usize = 100_000;
+
+// Share of each type over DRAWS seeded draws, as (car, truck, bus, emergency_van).
+fn sampled_shares(weights: &VehicleWeights) -> (f64, f64, f64, f64) {
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut counts = [0usize; 4];
+    for _ in 0..DRAWS {
+        let i = match random_vehicle_type(&mut rng, weights) {
+            VehicleType::Car => 0,
+            VehicleType::Truck => 1,
+            VehicleType::Bus => 2,
+            VehicleType::EmergencyVan => 3,
+        };
+        counts[i] += 1;
+    }
+    let share = |n: usize| n as f64 / DRAWS as f64;
+    (
+        share(counts[0]),
+        share(counts[1]),
+        share(counts[2]),
+        share(counts[3]),
+    )
+}
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 0.01,
+        "expected about {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn default_weights_give_the_documented_mix() {
+    let (car, truck, bus, emergency_van) = sampled_shares(&VehicleWeights::default());
+    assert_near(car, 0.70);
+    assert_near(truck, 0.20);
+    assert_near(bus, 0.09);
+    assert_near(emergency_van, 0.01);
+}
+
+#[test]
+fn weights_need_not_sum_to_one() {
+    let weights = VehicleWeights {
+        car: 2.0,
+        truck: 1.0,
+        bus: 1.0,
+        emergency_van: 0.0,
+    };
+    let (car, truck, bus, emergency_van) = sampled_shares(&weights);
+    assert_near(car, 0.5);
+    assert_near(truck, 0.25);
+    assert_near(bus, 0.25);
+    assert_eq!(emergency_van, 0.0);
+}
+
+#[test]
+fn a_night_schedule_raises_the_truck_share() {
+    let night = VehicleMixPeriod {
+        start_hour: 22,
+        end_hour: 5,
+        mix: VehicleWeights {
+            car: 0.45,
+            truck: 0.45,
+            bus: 0.09,
+            emergency_van: 0.01,
+        },
+    };
+    let config = SimulationConfig {
+        vehicle_mix_schedule: vec![night],
+        ..SimulationConfig::default()
+    };
+    // 23:00 and 02:00 UTC fall in the wrapped night period; 12:00 doesn't.
+    let (day_hour, night_hours) = (hour_of_day(12 * 3_600), [23, 2].map(|h| h * 3_600));
+    let (_, day_trucks, _, _) = sampled_shares(config.vehicle_mix_at(day_hour));
+    for secs in night_hours {
+        let (_, night_trucks, _, _) = sampled_shares(config.vehicle_mix_at(hour_of_day(secs)));
+        assert_near(night_trucks, 0.45);
+        assert!(night_trucks > day_trucks);
+    }
+    assert_near(day_trucks, 0.20);
+
+    // Without a schedule the mix is the same at every hour.
+    let constant = SimulationConfig::default();
+    for hour in 0..24 {
+        assert_eq!(
+            constant.vehicle_mix_at(hour).truck,
+            constant.vehicle_mix.truck
+        );
+    }
+}
+
+// Types of the vehicles a short seeded run spawns, in spawn order. The run starts at 23:00 on the
+// simulated clock, inside a night period that adds trucks to an all-car day mix.
+async fn night_run_vehicle_types(seed: u64) -> Vec<String> {
+    let only = |car, truck| VehicleWeights {
+        car,
+        truck,
+        bus: 0.0,
+        emergency_van: 0.0,
+    };
+    let config = SimulationConfig {
+        vehicle_mix: only(1.0, 0.0),
+        vehicle_mix_schedule: vec![VehicleMixPeriod {
+            start_hour: 22,
+            end_hour: 5,
+            mix: only(1.0, 1.0),
+        }],
+        start_hour: 23,
+        rush_hour: RushHourConfig::new(40, 3, 3),
+        crash_probability: 0.0,
+        overspeed_crash_probability: 0.0,
+        collision_probability: 0.0,
+        // Keep routing independent of how far earlier vehicles got, so both runs draw alike.
+        route_occupancy_threshold: f64::INFINITY,
+        ..SimulationConfig::default()
+    };
+    let (sender, receiver) = mpsc::channel();
+    run_simulation_on(
+        &InMemoryBus::new(),
+        Arc::new(RwLock::new(create_intersections())),
+        Arc::new(LaneStore::new(create_lanes())),
+        config,
+        RunContext {
+            time_scale: 50.0,
+            max_ticks: Some(3),
+            event_stream: Some(sender),
+            ..RunContext::new(seed, "vehicle-mix")
+        },
+    )
+    .await;
+    receiver
+        .try_iter()
+        .filter_map(|event| match event {
+            SimulationEvent::VehicleSpawned { vehicle_type, .. } => Some(vehicle_type),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn seeded_runs_with_a_schedule_spawn_the_same_vehicle_types() {
+    let first = night_run_vehicle_types(21).await;
+    let second = night_run_vehicle_types(21).await;
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+    // The hour comes from the simulated clock, so the night mix applies whatever the wall clock.
+    assert!(first.iter().any(|t| t == "Truck"), "{:?}", first);
+}
+
+#[test]
+fn the_simulated_hour_starts_at_start_hour_and_wraps_at_midnight() {
+    let config = SimulationConfig {
+        start_hour: 22,
+        ..SimulationConfig::default()
+    };
+    assert_eq!(config.hour_at(0), 22);
+    assert_eq!(config.hour_at(3_599), 22);
+    assert_eq!(config.hour_at(2 * 3_600), 0);
+    assert_eq!(config.hour_at(7 * 3_600 + 30), 5);
+}
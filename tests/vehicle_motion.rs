@@ -0,0 +1,90 @@
+// Lane traversal times follow the constant-acceleration equations of motion, and fuel burns
+// with waiting and distance.
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+
+fn vehicle(vehicle_type: VehicleType, speed: f64, acceleration: f64) -> Vehicle {
+    let mut vehicle = Vehicle::new(
+        1,
+        vehicle_type,
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        speed,
+    );
+    vehicle.acceleration = acceleration;
+    vehicle
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn time_to_traverse_matches_closed_form() {
+    let (v, a) = (20.0, 2.0);
+    let car = vehicle(VehicleType::Car, v, a);
+
+    // From a standstill on a short lane top speed is never reached: L = a*t^2/2.
+    assert_close(car.time_to_traverse(50.0, 0.0), (2.0 * 50.0 / a).sqrt());
+
+    // On a long lane: accelerate for v/a seconds over v^2/(2a) metres, then cruise.
+    let accel_distance = v * v / (2.0 * a);
+    assert_close(
+        car.time_to_traverse(300.0, 0.0),
+        v / a + (300.0 - accel_distance) / v,
+    );
+
+    // Entering with some speed: L = v0*t + a*t^2/2.
+    let v0 = 10.0;
+    assert_close(
+        car.time_to_traverse(40.0, v0),
+        ((v0 * v0 + 2.0 * a * 40.0).sqrt() - v0) / a,
+    );
+
+    // Already at top speed (or faster) the lane is driven at constant speed.
+    assert_close(car.time_to_traverse(100.0, v), 100.0 / v);
+    assert_close(car.time_to_traverse(100.0, 2.0 * v), 100.0 / v);
+}
+
+#[test]
+fn emergency_vans_accelerate_harder_than_cars() {
+    let car = Vehicle::new(
+        1,
+        VehicleType::Car,
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        20.0,
+    );
+    let van = Vehicle::new(
+        2,
+        VehicleType::EmergencyVan,
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        20.0,
+    );
+    assert!(van.acceleration > car.acceleration);
+    assert!(van.time_to_traverse(50.0, 0.0) < car.time_to_traverse(50.0, 0.0));
+}
+
+#[test]
+fn longer_waits_burn_more_idle_fuel() {
+    let mut short_wait = vehicle(VehicleType::Car, 20.0, 2.0);
+    let mut long_wait = vehicle(VehicleType::Car, 20.0, 2.0);
+    for v in [&mut short_wait, &mut long_wait] {
+        v.record_distance(500.0);
+    }
+    short_wait.record_wait(5);
+    long_wait.record_wait(30);
+    assert!(long_wait.fuel_used > short_wait.fuel_used);
+
+    // The same wait costs a truck more than a car.
+    let mut truck = vehicle(VehicleType::Truck, 20.0, 2.0);
+    truck.record_distance(500.0);
+    truck.record_wait(30);
+    assert!(truck.fuel_used > long_wait.fuel_used);
+}
@@ -0,0 +1,80 @@
+// Live positions of vehicles part-way through their journeys.
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rts_assignment::c1_tp063879::config::SimulationConfig;
+use rts_assignment::c1_tp063879::intersections::{create_intersections, IntersectionControl};
+use rts_assignment::c1_tp063879::lanes::{create_lanes, LaneStore};
+use rts_assignment::c1_tp063879::simulation::{simulate_vehicle_journey, JourneyContext};
+use rts_assignment::c1_tp063879::snapshot::snapshot_positions;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c3_tp063987::traffic_light_controller::TrafficLightController;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+const TIME_SCALE: f64 = 10.0;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_vehicle_mid_lane_reports_a_fraction_between_zero_and_one() {
+    let intersections = create_intersections();
+    let lanes = create_lanes();
+    // Start at an intersection without lights so the vehicle sets off straight away.
+    let lane = lanes
+        .iter()
+        .find(|lane| {
+            intersections
+                .iter()
+                .any(|i| i.id == lane.from && i.control != IntersectionControl::TrafficLight)
+        })
+        .expect("a lane leaving an uncontrolled intersection")
+        .clone();
+    let controller = TrafficLightController::initialize(intersections.clone(), &lanes);
+
+    let config = SimulationConfig {
+        crash_probability: 0.0,
+        overspeed_crash_probability: 0.0,
+        collision_probability: 0.0,
+        ..SimulationConfig::default()
+    };
+    let vehicle = Vehicle::new(1, VehicleType::Car, lane.from, lane.to, 40.0);
+    let travel_secs = vehicle.time_to_traverse(lane.length_meters, 0.0) / TIME_SCALE;
+    let ctx = JourneyContext {
+        active_ids: Arc::new(Mutex::new(HashSet::from([1]))),
+        time_scale: TIME_SCALE,
+        ..JourneyContext::new(
+            Arc::new(RwLock::new(intersections)),
+            Arc::new(LaneStore::new(lanes)),
+            Arc::new(RwLock::new(controller)),
+            Arc::new(config),
+        )
+    };
+    let positions = Arc::clone(&ctx.positions);
+    let journey = tokio::spawn(simulate_vehicle_journey(
+        vehicle,
+        vec![lane.clone()],
+        SmallRng::seed_from_u64(1),
+        ctx,
+    ));
+
+    // Wait until the vehicle is a little way along the lane.
+    let deadline = Instant::now() + Duration::from_secs_f64(travel_secs);
+    let progress = loop {
+        let snapshot = snapshot_positions(&positions);
+        if let Some(progress) = snapshot.get(&1).filter(|p| p.fraction_traversed > 0.0) {
+            break progress.clone();
+        }
+        assert!(Instant::now() < deadline, "vehicle never started the lane");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(progress.lane, lane.name);
+    assert!(!progress.waiting);
+    assert!(
+        progress.fraction_traversed > 0.0 && progress.fraction_traversed < 1.0,
+        "fraction {}",
+        progress.fraction_traversed
+    );
+
+    // Once the journey is over the vehicle is no longer tracked.
+    journey.await.unwrap();
+    assert!(snapshot_positions(&positions).is_empty());
+}
@@ -0,0 +1,72 @@
+// Snow slows vehicles down and raises their crash odds relative to clear weather.
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rts_assignment::c1_tp063879::intersections::IntersectionId;
+use rts_assignment::c1_tp063879::vehicles::{Vehicle, VehicleType};
+use rts_assignment::c1_tp063879::weather::{weather_at, Weather, WeatherChange};
+
+const LANE_LENGTH: f64 = 200.0;
+const BASE_CRASH_PROBABILITY: f64 = 0.05;
+
+fn car() -> Vehicle {
+    Vehicle::new(
+        1,
+        VehicleType::Car,
+        IntersectionId(0, 0),
+        IntersectionId(0, 1),
+        40.0,
+    )
+}
+
+// Crashes over a fixed sequence of rolls at the journey's per-lane probability for `weather`.
+fn crashes_in(weather: Weather) -> usize {
+    let mut rng = SmallRng::seed_from_u64(3);
+    let probability = (BASE_CRASH_PROBABILITY * weather.crash_factor()).clamp(0.0, 1.0);
+    (0..10_000).filter(|_| rng.random_bool(probability)).count()
+}
+
+#[test]
+fn clear_weather_matches_the_unscaled_model() {
+    let car = car();
+    assert_eq!(
+        car.time_to_traverse_in(LANE_LENGTH, 0.0, Weather::Clear),
+        car.time_to_traverse(LANE_LENGTH, 0.0)
+    );
+    assert_eq!(
+        car.exit_speed_in(LANE_LENGTH, 0.0, Weather::Clear),
+        car.exit_speed(LANE_LENGTH, 0.0)
+    );
+    assert_eq!(Weather::Clear.crash_factor(), 1.0);
+}
+
+#[test]
+fn snow_lowers_travel_speed_and_raises_the_crash_roll() {
+    let car = car();
+    assert!(
+        car.time_to_traverse_in(LANE_LENGTH, 0.0, Weather::Snow)
+            > car.time_to_traverse_in(LANE_LENGTH, 0.0, Weather::Clear)
+    );
+    assert!(
+        car.exit_speed_in(LANE_LENGTH, 0.0, Weather::Snow)
+            < car.exit_speed_in(LANE_LENGTH, 0.0, Weather::Clear)
+    );
+    assert!(crashes_in(Weather::Snow) > crashes_in(Weather::Clear));
+}
+
+#[test]
+fn the_schedule_switches_weather_at_its_start_times() {
+    let schedule = [
+        WeatherChange {
+            start_secs: 60,
+            weather: Weather::Snow,
+        },
+        WeatherChange {
+            start_secs: 10,
+            weather: Weather::Rain,
+        },
+    ];
+    assert_eq!(weather_at(&schedule, 0), Weather::Clear);
+    assert_eq!(weather_at(&schedule, 10), Weather::Rain);
+    assert_eq!(weather_at(&schedule, 59), Weather::Rain);
+    assert_eq!(weather_at(&schedule, 60), Weather::Snow);
+}